@@ -0,0 +1,59 @@
+-- Tables of the Ethereum data indexed from Starknet. Each row holds the JSON document of the
+-- indexed type, as served by the RPC, along with the columns filtered by the RPC queries.
+-- Hashes, addresses and topics are stored as raw bytes.
+
+CREATE TABLE IF NOT EXISTS headers (
+    number BIGINT PRIMARY KEY,
+    hash BYTEA NOT NULL,
+    logs_bloom BYTEA NOT NULL,
+    header JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS headers_hash_idx ON headers (hash);
+
+CREATE TABLE IF NOT EXISTS transactions (
+    id BIGSERIAL PRIMARY KEY,
+    hash BYTEA NOT NULL UNIQUE,
+    block_hash BYTEA,
+    block_number BIGINT,
+    transaction_index BIGINT,
+    tx JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transactions_block_hash_idx ON transactions (block_hash);
+CREATE INDEX IF NOT EXISTS transactions_block_number_idx ON transactions (block_number, transaction_index);
+
+CREATE TABLE IF NOT EXISTS receipts (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_hash BYTEA NOT NULL UNIQUE,
+    block_hash BYTEA,
+    block_number BIGINT,
+    transaction_index BIGINT,
+    receipt JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS receipts_block_hash_idx ON receipts (block_hash);
+CREATE INDEX IF NOT EXISTS receipts_block_number_idx ON receipts (block_number, transaction_index);
+
+CREATE TABLE IF NOT EXISTS logs (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_hash BYTEA,
+    block_hash BYTEA,
+    block_number BIGINT,
+    transaction_index BIGINT,
+    log_index BIGINT,
+    address BYTEA NOT NULL,
+    topic0 BYTEA,
+    topic1 BYTEA,
+    topic2 BYTEA,
+    topic3 BYTEA,
+    log JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS logs_transaction_hash_idx ON logs (transaction_hash);
+CREATE INDEX IF NOT EXISTS logs_block_hash_idx ON logs (block_hash);
+CREATE INDEX IF NOT EXISTS logs_block_number_idx ON logs (block_number, transaction_index, log_index);
+CREATE INDEX IF NOT EXISTS logs_address_idx ON logs (address);
+CREATE INDEX IF NOT EXISTS logs_topic0_idx ON logs (topic0);
+
+CREATE TABLE IF NOT EXISTS transaction_hashes (
+    eth_hash BYTEA PRIMARY KEY,
+    hashes JSONB NOT NULL,
+    execution_cost JSONB
+);
@@ -0,0 +1,124 @@
+-- Table written by the Postgres sink of the Apibara indexer (SINK_TYPE=postgres), see the
+-- `indexer` directory. Each row is an item of the indexer output, i.e. the document of the
+-- `collection` (headers, transactions, receipts or logs) in `data`, along with the cursor of
+-- the block it was indexed from. The triggers below project the items into the tables read by
+-- the RPC, and remove them when the sink invalidates the pending or reorganized blocks.
+
+CREATE TABLE IF NOT EXISTS indexer_items (
+    collection TEXT NOT NULL,
+    data JSONB NOT NULL,
+    _cursor INT8RANGE NOT NULL
+);
+CREATE INDEX IF NOT EXISTS indexer_items_cursor_idx ON indexer_items USING GIST (_cursor);
+
+-- Converts a 0x prefixed hex string to bytes.
+CREATE OR REPLACE FUNCTION hex_to_bytes(value TEXT) RETURNS BYTEA AS $$
+    SELECT decode(substr(value, 3), 'hex');
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- Converts a quantity to a BIGINT. Quantities are 0x prefixed hex strings, except the log
+-- indexes which the indexer writes as decimal strings.
+CREATE OR REPLACE FUNCTION quantity_to_bigint(value TEXT) RETURNS BIGINT AS $$
+    SELECT CASE
+        WHEN value LIKE '0x%' THEN ('x' || lpad(substr(value, 3), 16, '0'))::BIT(64)::BIGINT
+        ELSE value::BIGINT
+    END;
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION project_indexer_item() RETURNS TRIGGER AS $$
+DECLARE
+    document JSONB;
+BEGIN
+    CASE NEW.collection
+    WHEN 'headers' THEN
+        document := NEW.data -> 'header';
+        INSERT INTO headers (number, hash, logs_bloom, header)
+        VALUES (
+            quantity_to_bigint(document ->> 'number'),
+            hex_to_bytes(document ->> 'hash'),
+            hex_to_bytes(document ->> 'logsBloom'),
+            document
+        )
+        ON CONFLICT (number) DO UPDATE SET
+            hash = EXCLUDED.hash, logs_bloom = EXCLUDED.logs_bloom, header = EXCLUDED.header;
+    WHEN 'transactions' THEN
+        document := NEW.data -> 'tx';
+        INSERT INTO transactions (hash, block_hash, block_number, transaction_index, tx)
+        VALUES (
+            hex_to_bytes(document ->> 'hash'),
+            hex_to_bytes(document ->> 'blockHash'),
+            quantity_to_bigint(document ->> 'blockNumber'),
+            quantity_to_bigint(document ->> 'transactionIndex'),
+            document
+        )
+        ON CONFLICT (hash) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash, block_number = EXCLUDED.block_number,
+            transaction_index = EXCLUDED.transaction_index, tx = EXCLUDED.tx;
+    WHEN 'receipts' THEN
+        document := NEW.data -> 'receipt';
+        INSERT INTO receipts (transaction_hash, block_hash, block_number, transaction_index, receipt)
+        VALUES (
+            hex_to_bytes(document ->> 'transactionHash'),
+            hex_to_bytes(document ->> 'blockHash'),
+            quantity_to_bigint(document ->> 'blockNumber'),
+            quantity_to_bigint(document ->> 'transactionIndex'),
+            document
+        )
+        ON CONFLICT (transaction_hash) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash, block_number = EXCLUDED.block_number,
+            transaction_index = EXCLUDED.transaction_index, receipt = EXCLUDED.receipt;
+    WHEN 'logs' THEN
+        document := NEW.data -> 'log';
+        INSERT INTO logs (
+            transaction_hash, block_hash, block_number, transaction_index, log_index, address,
+            topic0, topic1, topic2, topic3, log
+        )
+        VALUES (
+            hex_to_bytes(document ->> 'transactionHash'),
+            hex_to_bytes(document ->> 'blockHash'),
+            quantity_to_bigint(document ->> 'blockNumber'),
+            quantity_to_bigint(document ->> 'transactionIndex'),
+            quantity_to_bigint(document ->> 'logIndex'),
+            hex_to_bytes(document ->> 'address'),
+            hex_to_bytes(document -> 'topics' ->> 0),
+            hex_to_bytes(document -> 'topics' ->> 1),
+            hex_to_bytes(document -> 'topics' ->> 2),
+            hex_to_bytes(document -> 'topics' ->> 3),
+            document
+        );
+    ELSE
+        RAISE EXCEPTION 'unknown indexer collection %', NEW.collection;
+    END CASE;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Removes the projection of the invalidated item. The documents are compared as a whole, so that
+-- the accepted version of a pending document, written with the same key, is kept.
+CREATE OR REPLACE FUNCTION remove_indexer_item() RETURNS TRIGGER AS $$
+BEGIN
+    CASE OLD.collection
+    WHEN 'headers' THEN
+        DELETE FROM headers
+        WHERE number = quantity_to_bigint(OLD.data -> 'header' ->> 'number') AND header = OLD.data -> 'header';
+    WHEN 'transactions' THEN
+        DELETE FROM transactions
+        WHERE hash = hex_to_bytes(OLD.data -> 'tx' ->> 'hash') AND tx = OLD.data -> 'tx';
+    WHEN 'receipts' THEN
+        DELETE FROM receipts
+        WHERE transaction_hash = hex_to_bytes(OLD.data -> 'receipt' ->> 'transactionHash')
+            AND receipt = OLD.data -> 'receipt';
+    WHEN 'logs' THEN
+        DELETE FROM logs
+        WHERE transaction_hash = hex_to_bytes(OLD.data -> 'log' ->> 'transactionHash') AND log = OLD.data -> 'log';
+    ELSE
+        NULL;
+    END CASE;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER indexer_items_insert AFTER INSERT ON indexer_items
+    FOR EACH ROW EXECUTE FUNCTION project_indexer_item();
+CREATE OR REPLACE TRIGGER indexer_items_delete AFTER DELETE ON indexer_items
+    FOR EACH ROW EXECUTE FUNCTION remove_indexer_item();
@@ -0,0 +1,244 @@
+use clap::Parser;
+use eyre::OptionExt;
+use futures::{stream, StreamExt};
+use kakarot_rpc::rpc_diff::{generalize_path, IgnoreRule, Normalizer, RpcCall};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    ops::Range,
+    path::PathBuf,
+};
+use url::Url;
+
+/// Sends the same calls to the Kakarot RPC and to a reference node, e.g. an anvil or reth node
+/// running the Hive chain imported in Kakarot with `hive_chain`, and reports the field level
+/// differences of the responses per method, once the known and accepted differences are removed.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The URL of the Kakarot RPC.
+    #[clap(long, default_value = "http://127.0.0.1:3030")]
+    kakarot_url: Url,
+    /// The URL of the reference node.
+    #[clap(long, default_value = "http://127.0.0.1:8545")]
+    reference_url: Url,
+    /// JSONL file of the calls to send, one `{"method": ..., "params": ...}` per line. Capture
+    /// files can be used. If not set, the calls are generated from the blocks of the reference
+    /// chain.
+    #[clap(long)]
+    calls: Option<PathBuf>,
+    /// Range of blocks of the reference chain the calls are generated from, e.g. `1..100`.
+    /// Defaults to the whole chain.
+    #[clap(long, value_parser = parse_block_range)]
+    blocks: Option<Range<u64>>,
+    /// Also compare the blocks, whose hashes and header fields differ as the transactions are
+    /// packed into different blocks on Starknet.
+    #[clap(long)]
+    compare_blocks: bool,
+    /// Additional accepted differences, as `[<method glob>:]<path>`, e.g. `eth_call:error.data`.
+    #[clap(long = "ignore")]
+    ignores: Vec<IgnoreRule>,
+    /// Only accept the differences given with `--ignore`.
+    #[clap(long)]
+    strict: bool,
+    /// Compare the messages of the errors.
+    #[clap(long)]
+    compare_error_messages: bool,
+    /// Number of calls in flight at once.
+    #[clap(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Maximum number of differences printed per call.
+    #[clap(long, default_value_t = 10)]
+    max_diffs: usize,
+}
+
+fn parse_block_range(range: &str) -> Result<Range<u64>, String> {
+    let invalid = || format!("invalid block range {range}, expected <from>..<to>");
+    let (from, to) = range.split_once("..").ok_or_else(invalid)?;
+    Ok(from.trim().parse().map_err(|_| invalid())?..to.trim().parse().map_err(|_| invalid())?)
+}
+
+/// A JSON-RPC client of one of the compared nodes.
+#[derive(Debug, Clone)]
+struct Node {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl Node {
+    /// Sends the call and returns the whole JSON-RPC response.
+    async fn send(&self, call: &RpcCall) -> eyre::Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": &call.method, "params": &call.params });
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header("content-type", "application/json")
+            .body(serde_json::to_vec(&request)?)
+            .send()
+            .await?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Sends the call and returns its result, failing on an error response.
+    async fn request(&self, method: &str, params: Value) -> eyre::Result<Value> {
+        let mut response = self.send(&RpcCall { method: method.to_string(), params }).await?;
+        if let Some(error) = response.get("error") {
+            eyre::bail!("{method} failed: {error}");
+        }
+        response.get_mut("result").map(Value::take).ok_or_eyre("missing result")
+    }
+}
+
+/// Returns the calls checking the chain state, the blocks and transactions of the range of
+/// blocks of the reference chain.
+async fn generate_calls(reference: &Node, args: &Args) -> eyre::Result<Vec<RpcCall>> {
+    let call = |method: &str, params: Value| RpcCall { method: method.to_string(), params };
+    let mut calls = vec![call("eth_chainId", json!([]))];
+
+    let latest = reference.request("eth_blockNumber", json!([])).await?;
+    let latest = u64::from_str_radix(latest.as_str().unwrap_or_default().trim_start_matches("0x"), 16)?;
+    let blocks = args.blocks.clone().unwrap_or(1..latest + 1);
+
+    let mut accounts = Vec::new();
+    for number in blocks {
+        let number = format!("{number:#x}");
+        let block = reference.request("eth_getBlockByNumber", json!([number, true])).await?;
+        if block.is_null() {
+            break;
+        }
+        if args.compare_blocks {
+            calls.push(call("eth_getBlockByNumber", json!([number, true])));
+            calls.push(call("eth_getBlockByNumber", json!([number, false])));
+        }
+
+        for transaction in block["transactions"].as_array().into_iter().flatten() {
+            let hash = &transaction["hash"];
+            calls.push(call("eth_getTransactionByHash", json!([hash])));
+            calls.push(call("eth_getTransactionReceipt", json!([hash])));
+
+            // Replay the calls of the transaction against the latest state, the errors are
+            // compared as well.
+            let request = json!({
+                "from": transaction["from"],
+                "to": transaction["to"],
+                "input": transaction["input"],
+                "value": transaction["value"],
+            });
+            calls.push(call("eth_call", json!([request, "latest"])));
+            calls.push(call("eth_estimateGas", json!([request, "latest"])));
+
+            accounts.extend([&transaction["from"], &transaction["to"]].into_iter().filter(|a| !a.is_null()).cloned());
+        }
+    }
+
+    accounts.sort_by_key(ToString::to_string);
+    accounts.dedup();
+    for account in accounts {
+        calls.push(call("eth_getBalance", json!([account, "latest"])));
+        calls.push(call("eth_getTransactionCount", json!([account, "latest"])));
+        calls.push(call("eth_getCode", json!([account, "latest"])));
+    }
+
+    // Errors of the unknown transactions and blocks.
+    let unknown = format!("{:#066x}", 0xdead_u64);
+    calls.push(call("eth_getTransactionByHash", json!([unknown])));
+    calls.push(call("eth_getTransactionReceipt", json!([unknown])));
+    calls.push(call("eth_getBlockByHash", json!([unknown, false])));
+    calls.push(call("eth_getBalance", json!(["0xinvalid", "latest"])));
+
+    Ok(calls)
+}
+
+fn read_calls(path: &PathBuf) -> eyre::Result<Vec<RpcCall>> {
+    let mut calls = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            calls.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(calls)
+}
+
+/// Differences of a method.
+#[derive(Debug, Default)]
+struct MethodReport {
+    calls: usize,
+    mismatches: usize,
+    failures: usize,
+    /// Number of differences per field, with the array indices generalized.
+    fields: BTreeMap<String, usize>,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut normalizer = if args.strict { Normalizer::default() } else { Normalizer::kakarot() };
+    normalizer.rules.extend(args.ignores.iter().cloned());
+    normalizer.compare_error_messages |= args.compare_error_messages;
+
+    let client = reqwest::Client::new();
+    let kakarot = Node { client: client.clone(), url: args.kakarot_url.clone() };
+    let reference = Node { client, url: args.reference_url.clone() };
+
+    let calls = match &args.calls {
+        Some(path) => read_calls(path)?,
+        None => generate_calls(&reference, &args).await?,
+    };
+    println!("comparing {} calls between {} and {}", calls.len(), args.kakarot_url, args.reference_url);
+
+    let mut responses = stream::iter(calls)
+        .map(|call| {
+            let (kakarot, reference) = (&kakarot, &reference);
+            async move {
+                let (actual, expected) = tokio::join!(kakarot.send(&call), reference.send(&call));
+                (call, expected, actual)
+            }
+        })
+        .buffered(args.concurrency.max(1));
+
+    let mut reports = BTreeMap::<String, MethodReport>::new();
+    while let Some((call, expected, actual)) = responses.next().await {
+        let report = reports.entry(call.method.clone()).or_default();
+        report.calls += 1;
+
+        let (expected, actual) = match (expected, actual) {
+            (Ok(expected), Ok(actual)) => (expected, actual),
+            (Err(err), _) | (_, Err(err)) => {
+                report.failures += 1;
+                println!("\n{} {}: request failed: {err}", call.method, call.params);
+                continue;
+            }
+        };
+
+        let diffs = normalizer.diff_responses(&call.method, &expected, &actual);
+        if diffs.is_empty() {
+            continue;
+        }
+        report.mismatches += 1;
+        println!("\n{} {}: {} differences", call.method, call.params, diffs.len());
+        for diff in &diffs {
+            *report.fields.entry(generalize_path(&diff.path)).or_default() += 1;
+        }
+        for diff in diffs.iter().take(args.max_diffs) {
+            println!("  {diff}");
+        }
+    }
+
+    println!("\n{:<40} {:>8} {:>10} {:>8}", "method", "calls", "mismatches", "failures");
+    let mut mismatches = 0;
+    for (method, report) in &reports {
+        println!("{method:<40} {:>8} {:>10} {:>8}", report.calls, report.mismatches, report.failures);
+        for (field, count) in &report.fields {
+            println!("  {field:<38} {count:>8}");
+        }
+        mismatches += report.mismatches + report.failures;
+    }
+
+    if mismatches > 0 {
+        eyre::bail!("{mismatches} calls differ from the reference node");
+    }
+    Ok(())
+}
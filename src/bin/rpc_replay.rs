@@ -0,0 +1,166 @@
+use clap::Parser;
+use futures::{stream, StreamExt};
+use kakarot_rpc::{eth_rpc::middleware::capture::CapturedCall, rpc_diff::diff_responses};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Methods changing the state of the node, only replayed with `--include-writes`.
+const WRITE_METHODS: &[&str] =
+    &["eth_sendRawTransaction", "eth_sendTransaction", "eth_submitWork", "eth_submitHashrate"];
+
+/// Re-issues the calls of a capture file, written by the RPC when `RPC_CAPTURE_PATH` is set,
+/// against a running node and reports the responses which differ from the captured ones.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The capture files to replay, in order.
+    #[clap(required = true)]
+    captures: Vec<PathBuf>,
+    /// The URL of the node to replay the calls against.
+    #[clap(long, default_value = "http://127.0.0.1:3030")]
+    url: Url,
+    /// Number of calls in flight at once.
+    #[clap(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Only replay these methods, comma separated.
+    #[clap(long, value_delimiter = ',')]
+    methods: Vec<String>,
+    /// Replay but don't compare these methods, e.g. the ones depending on the chain head.
+    #[clap(long, value_delimiter = ',')]
+    skip_diff: Vec<String>,
+    /// Also replay the methods changing the state of the node, e.g. `eth_sendRawTransaction`.
+    /// Only use against a node whose state can be modified.
+    #[clap(long)]
+    include_writes: bool,
+    /// Maximum number of differences printed per call.
+    #[clap(long, default_value_t = 10)]
+    max_diffs: usize,
+}
+
+/// Replay statistics of a method.
+#[derive(Debug, Default)]
+struct MethodReport {
+    calls: usize,
+    mismatches: usize,
+    failures: usize,
+    captured_latency: Duration,
+    replayed_latency: Duration,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut calls = Vec::new();
+    let mut skipped_writes = 0;
+    let mut skipped_omitted = 0;
+    for path in &args.captures {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let call: CapturedCall = serde_json::from_str(&line)?;
+            if !args.methods.is_empty() && !args.methods.contains(&call.method) {
+                continue;
+            }
+            if !args.include_writes && WRITE_METHODS.contains(&call.method.as_str()) {
+                skipped_writes += 1;
+                continue;
+            }
+            if call.omitted {
+                skipped_omitted += 1;
+                continue;
+            }
+            calls.push(call);
+        }
+    }
+    println!("replaying {} calls against {}", calls.len(), args.url);
+    if skipped_writes > 0 {
+        println!("skipping {skipped_writes} calls changing the state of the node, see --include-writes");
+    }
+    if skipped_omitted > 0 {
+        println!("skipping {skipped_omitted} calls captured without their parameters, see RPC_CAPTURE_OMIT_METHODS");
+    }
+
+    let client = reqwest::Client::new();
+    let mut replayed = stream::iter(calls.into_iter().enumerate())
+        .map(|(id, call)| {
+            let client = &client;
+            let url = args.url.clone();
+            async move {
+                let request = json!({ "jsonrpc": "2.0", "id": id, "method": &call.method, "params": &call.params });
+                let now = Instant::now();
+                let response = async {
+                    let response = client
+                        .post(url)
+                        .header("content-type", "application/json")
+                        .body(serde_json::to_vec(&request)?)
+                        .send()
+                        .await?;
+                    Ok::<_, eyre::Report>(serde_json::from_slice::<Value>(&response.bytes().await?)?)
+                }
+                .await;
+                (call, response, now.elapsed())
+            }
+        })
+        .buffered(args.concurrency.max(1));
+
+    let mut reports = BTreeMap::<String, MethodReport>::new();
+    while let Some((call, response, latency)) = replayed.next().await {
+        let report = reports.entry(call.method.clone()).or_default();
+        report.calls += 1;
+        report.captured_latency += Duration::from_micros(call.latency_us);
+        report.replayed_latency += latency;
+
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                report.failures += 1;
+                println!("\n{} {}: request failed: {err}", call.method, call.params);
+                continue;
+            }
+        };
+        if args.skip_diff.contains(&call.method) {
+            continue;
+        }
+
+        let diffs = diff_responses(&call.response, &response);
+        if !diffs.is_empty() {
+            report.mismatches += 1;
+            println!("\n{} {}: {} differences", call.method, call.params, diffs.len());
+            for diff in diffs.iter().take(args.max_diffs) {
+                println!("  {diff}");
+            }
+        }
+    }
+
+    println!(
+        "\n{:<40} {:>8} {:>10} {:>8} {:>14} {:>14}",
+        "method", "calls", "mismatches", "failures", "captured (ms)", "replayed (ms)"
+    );
+    let mut mismatches = 0;
+    for (method, report) in &reports {
+        let average = |latency: Duration| latency.as_secs_f64() * 1_000. / report.calls as f64;
+        println!(
+            "{method:<40} {:>8} {:>10} {:>8} {:>14.2} {:>14.2}",
+            report.calls,
+            report.mismatches,
+            report.failures,
+            average(report.captured_latency),
+            average(report.replayed_latency)
+        );
+        mismatches += report.mismatches + report.failures;
+    }
+
+    if mismatches > 0 {
+        eyre::bail!("{mismatches} calls differ from the capture");
+    }
+    Ok(())
+}
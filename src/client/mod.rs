@@ -1,27 +1,29 @@
 use crate::{
+    config::config,
     constants::{ETH_CHAIN_ID, KKRT_BLOCK_GAS_LIMIT},
     pool::{
+        gas_oracle::{GasPriceOracle, GasPriceSuggestion},
+        in_flight::{InFlightTransactions, PooledTransaction},
         mempool::{KakarotPool, TransactionOrdering},
         validate::KakarotTransactionValidatorBuilder,
     },
     providers::{
         eth_provider::{
             database::{
-                filter,
-                filter::EthDatabaseFilterBuilder,
-                types::transaction::{ExtendedTransaction, StoredEthStarknetTransactionHash},
-                Database,
+                ethereum::{build_block, EthereumBlockStore, EthereumStore, EthereumTransactionStore},
+                types::{header::ExtendedBlock, transaction::ExtendedTransaction},
             },
-            error::SignatureError,
+            error::{EthApiError, SignatureError, TransactionError},
             provider::{EthApiResult, EthDataProvider},
-            TransactionProvider, TxPoolProvider,
+            BlockProvider, TransactionProvider, TxPoolProvider,
         },
         sn_provider::StarknetProvider,
     },
 };
-use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Address, Bytes, B256};
+use alloy_eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag};
+use alloy_primitives::{Address, Bloom, Bytes, B256, U256};
 use alloy_rlp::Decodable;
+use alloy_rpc_types::Header;
 use alloy_rpc_types_txpool::TxpoolContent;
 use alloy_serde::WithOtherFields;
 use async_trait::async_trait;
@@ -34,7 +36,14 @@ use reth_transaction_pool::{
     TransactionOrigin, TransactionPool,
 };
 use starknet::providers::Provider;
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 #[async_trait]
 pub trait KakarotTransactions {
@@ -48,12 +57,42 @@ pub trait TransactionHashProvider {
     async fn transaction_by_hash(&self, hash: B256) -> EthApiResult<Option<ExtendedTransaction>>;
 }
 
+#[async_trait]
+pub trait PendingBlockProvider {
+    /// Returns the pending block, built from the indexed pending block followed by the
+    /// transactions in flight to Starknet and the best transactions of the pool.
+    ///
+    /// The transactions of the pool aren't executed: calls on the pending block run on the
+    /// pending Starknet block, which only accounts for the transactions relayed to Starknet.
+    async fn pending_block(&self, full: bool) -> EthApiResult<Option<ExtendedBlock>>;
+}
+
+#[async_trait]
+pub trait TransactionCountProvider {
+    /// Returns the nonce for the address at the given block. For the pending block, the
+    /// transactions of the pool and the ones in flight to Starknet are accounted for.
+    async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256>;
+}
+
+#[async_trait]
+pub trait GasPriceOracleProvider {
+    /// Returns the suggested gas price, i.e. the base fee plus the suggested priority fee.
+    async fn suggested_gas_price(&self) -> EthApiResult<U256>;
+
+    /// Returns the suggested priority fee per gas.
+    async fn suggested_priority_fee(&self) -> EthApiResult<U256>;
+}
+
 /// Provides a wrapper structure around the Ethereum Provider
 /// and the Mempool.
 #[derive(Debug, Clone)]
 pub struct EthClient<SP: Provider + Send + Sync> {
     eth_provider: EthDataProvider<SP>,
     pool: Arc<KakarotPool<EthDataProvider<SP>>>,
+    in_flight: Arc<InFlightTransactions>,
+    gas_price_oracle: Arc<GasPriceOracle>,
+    /// Cleared at shutdown to reject the new transactions.
+    accepting_transactions: Arc<AtomicBool>,
 }
 
 impl<SP> EthClient<SP>
@@ -66,7 +105,7 @@ where
     }
 
     /// Tries to start a [`EthClient`] by fetching the current chain id, initializing a [`EthDataProvider`] and a [`Pool`].
-    pub fn new(starknet_provider: SP, pool_config: PoolConfig, database: Database) -> Self {
+    pub fn new(starknet_provider: SP, pool_config: PoolConfig, database: Arc<dyn EthereumStore>) -> Self {
         // Create a new EthDataProvider instance with the initialized database and Starknet provider.
         let eth_provider = EthDataProvider::new(database, StarknetProvider::new(starknet_provider));
 
@@ -84,7 +123,15 @@ where
             pool_config,
         ));
 
-        Self { eth_provider, pool }
+        let gas_price_oracle = Arc::new(GasPriceOracle::new(config().gas_price_oracle.clone()));
+
+        Self {
+            eth_provider,
+            pool,
+            in_flight: Arc::new(InFlightTransactions::new()),
+            gas_price_oracle,
+            accepting_transactions: Arc::new(AtomicBool::new(true)),
+        }
     }
 
     /// Returns a clone of the [`EthDataProvider`]
@@ -96,6 +143,27 @@ where
     pub fn mempool(&self) -> Arc<KakarotPool<EthDataProvider<SP>>> {
         self.pool.clone()
     }
+
+    /// Returns the transactions removed from the pool to be relayed, until they are indexed.
+    pub fn in_flight(&self) -> &InFlightTransactions {
+        &self.in_flight
+    }
+
+    /// Rejects the transactions sent from now on, the ones already in the pool are still
+    /// relayed.
+    pub fn stop_accepting_transactions(&self) {
+        self.accepting_transactions.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns false once the shutdown started and the sent transactions are rejected.
+    pub fn is_accepting_transactions(&self) -> bool {
+        self.accepting_transactions.load(Ordering::Relaxed)
+    }
+
+    /// Returns the gas price suggestion of the [`GasPriceOracle`] for the next block.
+    pub async fn gas_price_suggestion(&self) -> EthApiResult<GasPriceSuggestion> {
+        self.gas_price_oracle.suggest(&self.eth_provider, &self.pool).await
+    }
 }
 
 #[async_trait]
@@ -104,6 +172,10 @@ where
     SP: Provider + Clone + Sync + Send,
 {
     async fn send_raw_transaction(&self, transaction: Bytes) -> EthApiResult<B256> {
+        if !self.is_accepting_transactions() {
+            return Err(TransactionError::ShuttingDown.into());
+        }
+
         // Decode the transaction data
         let transaction_signed = TransactionSigned::decode(&mut transaction.0.as_ref())?;
 
@@ -123,6 +195,17 @@ where
         #[cfg(feature = "hive")]
         self.eth_provider.deploy_evm_transaction_signer(signer).await?;
 
+        // Reject the transaction if relaying it would exceed the Starknet execution resources limits.
+        // Other simulation failures are left to the relayer.
+        match self.eth_provider.simulate_transaction(&transaction_signed).await {
+            Err(err @ EthApiError::Transaction(TransactionError::ExceedsStarknetResources)) => {
+                tracing::warn!(?hash, ?to, from = ?signer, "transaction exceeds the Starknet resources limits");
+                return Err(err);
+            }
+            Err(EthApiError::Unsupported(_)) | Ok(_) => {}
+            Err(err) => tracing::warn!(?err, ?hash, "failed to simulate transaction"),
+        }
+
         // Add the transaction to the pool and wait for it to be picked up by a relayer
         let hash = self
             .pool
@@ -134,6 +217,107 @@ where
     }
 }
 
+#[async_trait]
+impl<SP> PendingBlockProvider for EthClient<SP>
+where
+    SP: Provider + Clone + Sync + Send,
+{
+    async fn pending_block(&self, full: bool) -> EthApiResult<Option<ExtendedBlock>> {
+        let database = self.eth_provider.database();
+        let latest_number = self.eth_provider.block_number().await?.to::<u64>();
+        let pending_number = latest_number.saturating_add(1);
+
+        // Start from the pending block of the indexer if any, or from an empty block on top of the latest one.
+        let (header, mut transactions) = match database.header(pending_number.into()).await? {
+            Some(header) if header.hash.is_zero() => {
+                let transactions = database.transactions(pending_number.into()).await?;
+                (header, transactions)
+            }
+            _ => {
+                let Some(latest) = database.header(latest_number.into()).await? else {
+                    return Ok(None);
+                };
+                let header = Header {
+                    hash: B256::ZERO,
+                    parent_hash: latest.hash,
+                    number: pending_number,
+                    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+                    gas_used: 0,
+                    base_fee_per_gas: Some(self.pool.block_info().pending_basefee),
+                    logs_bloom: Bloom::ZERO,
+                    ..latest
+                };
+                (header, Vec::new())
+            }
+        };
+
+        // Append the transactions in flight, which precede the ones of the pool, skipping the indexed ones.
+        // The indexed transactions are removed from the transactions in flight by the pool maintenance.
+        let in_flight = self.in_flight.transactions();
+        let hashes = in_flight.iter().map(|transaction| *transaction.hash()).collect::<Vec<_>>();
+        let mut included = transactions.iter().map(|transaction| transaction.hash).collect::<HashSet<_>>();
+        included
+            .extend(database.transactions_by_hashes(&hashes).await?.into_iter().map(|transaction| transaction.hash));
+
+        for transaction in in_flight.into_iter().chain(self.pool.best_transactions()) {
+            if !included.insert(*transaction.hash()) {
+                continue;
+            }
+            let mut transaction = pending_transaction(&transaction);
+            transaction.block_hash = Some(header.hash);
+            transaction.block_number = Some(header.number);
+            transaction.transaction_index = Some(transactions.len() as u64);
+            transactions.push(transaction);
+        }
+
+        build_block(header, transactions, full).map(Some)
+    }
+}
+
+#[async_trait]
+impl<SP> TransactionCountProvider for EthClient<SP>
+where
+    SP: Provider + Clone + Sync + Send,
+{
+    async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256> {
+        let nonce = self.eth_provider.transaction_count(address, block_id).await?;
+        if !matches!(block_id, Some(BlockId::Number(BlockNumberOrTag::Pending))) {
+            return Ok(nonce);
+        }
+
+        // The next nonce is the first one from the state nonce not taken by a transaction sent but
+        // not indexed yet, as for geth. The queued transactions after a nonce gap are skipped.
+        let mut sent_nonces = self.in_flight.nonces(address).into_iter().collect::<HashSet<_>>();
+        sent_nonces.extend(self.pool.get_transactions_by_sender(address).iter().map(|transaction| transaction.nonce()));
+        let mut pending_nonce = nonce.saturating_to::<u64>();
+        while sent_nonces.contains(&pending_nonce) {
+            pending_nonce += 1;
+        }
+        Ok(U256::from(pending_nonce))
+    }
+}
+
+/// Converts the pool transaction into an RPC transaction.
+fn pending_transaction(transaction: &PooledTransaction) -> ExtendedTransaction {
+    WithOtherFields::new(
+        TransactionSource::Pool(transaction.transaction.transaction().clone()).into_transaction(&EthTxBuilder {}),
+    )
+}
+
+#[async_trait]
+impl<SP> GasPriceOracleProvider for EthClient<SP>
+where
+    SP: Provider + Clone + Sync + Send,
+{
+    async fn suggested_gas_price(&self) -> EthApiResult<U256> {
+        Ok(U256::from(self.gas_price_suggestion().await?.gas_price()))
+    }
+
+    async fn suggested_priority_fee(&self) -> EthApiResult<U256> {
+        Ok(U256::from(self.gas_price_suggestion().await?.priority_fee))
+    }
+}
+
 #[async_trait]
 impl<SP> TxPoolProvider for EthClient<SP>
 where
@@ -186,22 +370,12 @@ where
         let mut tx = self
             .pool
             .get(&hash)
-            .map(|transaction| {
-                WithOtherFields::new(
-                    TransactionSource::Pool(transaction.transaction.transaction().clone())
-                        .into_transaction(&EthTxBuilder {}),
-                )
-            })
+            .map(|transaction| pending_transaction(&transaction))
             .or(self.eth_provider.transaction_by_hash(hash).await?);
 
         if let Some(ref mut transaction) = tx {
             // Fetch the Starknet transaction hash if it exists.
-            let filter = EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default()
-                .with_tx_hash(&transaction.hash)
-                .build();
-
-            let hash_mapping: Option<StoredEthStarknetTransactionHash> =
-                self.eth_provider.database().get_one(filter, None).await?;
+            let hash_mapping = self.eth_provider.database().transaction_hashes(&transaction.hash).await?;
 
             // Add the Starknet transaction hash to the transaction fields.
             if let Some(hash_mapping) = hash_mapping {
@@ -1,19 +1,41 @@
+use crate::{
+    eth_rpc::{
+        config::RPCConfig,
+        middleware::{
+            capture::{parse_sample_rate, CaptureConfig, DEFAULT_CAPTURE_MAX_FILES, DEFAULT_CAPTURE_MAX_FILE_SIZE},
+            health::HealthConfig,
+            rate_limit::{parse_api_keys, parse_method_weights, RateLimitConfig},
+            timeout::{parse_method_timeouts, TimeoutConfig, DEFAULT_TIMEOUT},
+        },
+        rpc::KakarotRpcModule,
+    },
+    pool::gas_oracle::GasPriceOracleConfig,
+    providers::{
+        eth_provider::finality::SafeBlock,
+        sn_provider::{FailoverConfig, FailoverProvider, FailoverTransport},
+    },
+    shutdown::DEFAULT_SHUTDOWN_TIMEOUT,
+};
+use alloy_primitives::B256;
+use clap::{Arg, Command};
 use eyre::eyre;
 use starknet::core::types::Felt;
-use std::env::var;
+#[cfg(any(test, feature = "testing"))]
+use std::sync::{PoisonError, RwLock};
+use std::{
+    collections::HashMap, env::var, ffi::OsString, fmt, path::PathBuf, str::FromStr, sync::OnceLock, time::Duration,
+};
 use url::Url;
 
-fn env_var_to_field_element(var_name: &str) -> Result<Felt, eyre::Error> {
-    let env_var = var(var_name).map_err(|_| eyre!("missing env var: {var_name}"))?;
-
-    Ok(Felt::from_hex(&env_var)?)
-}
-
 #[derive(Clone, Debug)]
 /// Configuration for the Starknet RPC client.
 pub struct KakarotRpcConfig {
     /// Starknet network.
     pub network_url: Url,
+    /// Starknet networks used when the main one fails, in order of preference.
+    pub fallback_network_urls: Vec<Url>,
+    /// Retries, circuit breaker and hedging of the calls to the Starknet networks.
+    pub failover: FailoverConfig,
     /// Kakarot contract address.
     pub kakarot_address: Felt,
     /// Uninitialized account class hash.
@@ -23,14 +45,792 @@ pub struct KakarotRpcConfig {
 }
 
 impl KakarotRpcConfig {
-    /// `STARKNET_NETWORK` environment variable should be set the URL of a `JsonRpc`
-    /// starknet provider, e.g. <https://starknet-goerli.g.alchemy.com/v2/some_key>.
-    pub fn from_env() -> eyre::Result<Self> {
+    /// Returns a Starknet provider over the main and the fallback networks.
+    pub fn starknet_provider(&self) -> FailoverProvider {
+        FailoverProvider::new(FailoverTransport::new(
+            std::iter::once(self.network_url.clone()).chain(self.fallback_network_urls.iter().cloned()),
+            self.failover,
+        ))
+    }
+}
+
+/// A setting of the RPC, read from a command line flag, the configuration file or an
+/// environment variable, in this order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setting {
+    /// Name of the environment variable.
+    pub env: &'static str,
+    /// Name of the command line flag and of the key in the configuration file.
+    pub key: &'static str,
+    /// Description of the setting.
+    pub help: &'static str,
+}
+
+impl Setting {
+    const fn new(env: &'static str, key: &'static str, help: &'static str) -> Self {
+        Self { env, key, help }
+    }
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (--{} or `{}` in the config file): {}", self.env, self.key, self.key, self.help)
+    }
+}
+
+/// The settings of the RPC.
+pub const SETTINGS: &[Setting] = &[
+    Setting::new("STARKNET_NETWORK", "starknet-network", "URL of the Starknet JSON-RPC provider"),
+    Setting::new(
+        "STARKNET_NETWORK_FALLBACKS",
+        "starknet-network-fallbacks",
+        "comma separated URLs of the Starknet JSON-RPC providers used when the main one fails",
+    ),
+    Setting::new("STARKNET_RETRIES", "starknet-retries", "number of times a failed Starknet read is retried"),
+    Setting::new(
+        "STARKNET_RETRY_BACKOFF_MS",
+        "starknet-retry-backoff-ms",
+        "delay before the first retry in milliseconds, doubled at each retry",
+    ),
+    Setting::new(
+        "STARKNET_CIRCUIT_BREAKER_THRESHOLD",
+        "starknet-circuit-breaker-threshold",
+        "number of consecutive failures after which a Starknet provider is skipped",
+    ),
+    Setting::new(
+        "STARKNET_CIRCUIT_BREAKER_COOLDOWN_MS",
+        "starknet-circuit-breaker-cooldown-ms",
+        "time a failing Starknet provider is skipped for in milliseconds",
+    ),
+    Setting::new(
+        "STARKNET_HEDGE_DELAY_MS",
+        "starknet-hedge-delay-ms",
+        "time after which a read is also sent to the next Starknet provider in milliseconds, disabled if unset",
+    ),
+    Setting::new("KAKAROT_ADDRESS", "kakarot-address", "address of the Kakarot contract"),
+    Setting::new(
+        "UNINITIALIZED_ACCOUNT_CLASS_HASH",
+        "uninitialized-account-class-hash",
+        "class hash of the uninitialized account contract",
+    ),
+    Setting::new("ACCOUNT_CONTRACT_CLASS_HASH", "account-contract-class-hash", "class hash of the account contract"),
+    Setting::new("RELAYERS_ADDRESSES", "relayers-addresses", "comma separated addresses of the relayer accounts"),
+    Setting::new("RELAYER_PRIVATE_KEY", "relayer-private-key", "private key of the relayer accounts"),
+    Setting::new(
+        "SIMULATION_ACCOUNT_ADDRESS",
+        "simulation-account-address",
+        "account used to simulate the relayed transactions, the first relayer by default",
+    ),
+    Setting::new("KAKAROT_RPC_URL", "kakarot-rpc-url", "socket address of the main RPC listener"),
+    Setting::new("KAKAROT_RPC_API", "kakarot-rpc-api", "comma separated namespaces served by the main listener"),
+    Setting::new(
+        "KAKAROT_RPC_LISTENERS",
+        "kakarot-rpc-listeners",
+        "additional listeners, e.g. 127.0.0.1:3031=debug,trace,txpool",
+    ),
+    Setting::new("RPC_MAX_CONNECTIONS", "rpc-max-connections", "maximum number of connections per listener"),
+    Setting::new("PROMETHEUS_PORT", "prometheus-port", "port serving the prometheus metrics"),
+    Setting::new("RPC_MAX_REQUEST_BODY_SIZE", "rpc-max-request-body-size", "maximum size of a request body in bytes"),
+    Setting::new(
+        "RPC_MAX_RESPONSE_BODY_SIZE",
+        "rpc-max-response-body-size",
+        "maximum size of a response body in bytes",
+    ),
+    Setting::new("RPC_MAX_BATCH_SIZE", "rpc-max-batch-size", "maximum number of calls in a batch, 0 disables batches"),
+    Setting::new("RPC_TIMEOUT_MS", "rpc-timeout-ms", "timeout of the calls in milliseconds"),
+    Setting::new(
+        "RPC_METHOD_TIMEOUTS_MS",
+        "rpc-method-timeouts-ms",
+        "comma separated timeouts of the methods, as <method>=<milliseconds>",
+    ),
+    Setting::new("RPC_API_KEYS", "rpc-api-keys", "comma separated API keys, as <name>:<key>[:<units per second>]"),
+    Setting::new("RPC_REQUIRE_API_KEY", "rpc-require-api-key", "reject the requests without a valid API key"),
+    Setting::new("RPC_API_KEY_RATE_LIMIT", "rpc-api-key-rate-limit", "units per second of the API keys"),
+    Setting::new("RPC_IP_RATE_LIMIT", "rpc-ip-rate-limit", "units per second of each client without API key"),
+    Setting::new(
+        "RPC_TRUSTED_PROXIES",
+        "rpc-trusted-proxies",
+        "comma separated IP addresses of the reverse proxies whose x-forwarded-for and x-real-ip headers are trusted",
+    ),
+    Setting::new(
+        "RPC_METHOD_WEIGHTS",
+        "rpc-method-weights",
+        "comma separated units of the methods, as <method>=<units>",
+    ),
+    Setting::new(
+        "RPC_CAPTURE_PATH",
+        "rpc-capture-path",
+        "JSONL file capturing a sample of the calls, disabled if unset",
+    ),
+    Setting::new(
+        "RPC_CAPTURE_SAMPLE_RATE",
+        "rpc-capture-sample-rate",
+        "fraction of the calls captured, between 0 and 1",
+    ),
+    Setting::new(
+        "RPC_CAPTURE_MAX_FILE_SIZE",
+        "rpc-capture-max-file-size",
+        "size in bytes after which the capture file is rotated",
+    ),
+    Setting::new("RPC_CAPTURE_MAX_FILES", "rpc-capture-max-files", "number of rotated capture files kept"),
+    Setting::new(
+        "RPC_CAPTURE_OMIT_METHODS",
+        "rpc-capture-omit-methods",
+        "comma separated methods captured without their parameters and responses",
+    ),
+    Setting::new(
+        "HEALTH_MAX_INDEXER_LAG",
+        "health-max-indexer-lag",
+        "number of blocks the indexer can lag before the RPC isn't ready",
+    ),
+    Setting::new(
+        "HEALTH_MAX_MEMPOOL_TRANSACTIONS",
+        "health-max-mempool-transactions",
+        "number of transactions in the mempool before the readiness reports it as degraded",
+    ),
+    Setting::new(
+        "HEALTH_MIN_FUNDED_RELAYERS",
+        "health-min-funded-relayers",
+        "number of funded relayers below which the readiness reports them as degraded",
+    ),
+    Setting::new(
+        "HEALTH_CHECK_TIMEOUT_MS",
+        "health-check-timeout-ms",
+        "time given to each dependency to answer the readiness check in milliseconds",
+    ),
+    Setting::new(
+        "HEALTH_REPORT_TTL_MS",
+        "health-report-ttl-ms",
+        "time during which a readiness report is served before the dependencies are checked again in milliseconds",
+    ),
+    Setting::new(
+        "SHUTDOWN_TIMEOUT_MS",
+        "shutdown-timeout-ms",
+        "time given to the transactions of the mempool to be relayed at shutdown in milliseconds",
+    ),
+    Setting::new("DATABASE_BACKEND", "database-backend", "storage of the indexed data: mongo, postgres or memory"),
+    Setting::new("MONGO_CONNECTION_STRING", "mongo-connection-string", "connection string of the mongo database"),
+    Setting::new("MONGO_DATABASE_NAME", "mongo-database-name", "name of the mongo database"),
+    Setting::new("MONGO_RUN_MIGRATIONS", "mongo-run-migrations", "apply the pending mongo migrations at startup"),
+    Setting::new(
+        "POSTGRES_CONNECTION_STRING",
+        "postgres-connection-string",
+        "connection string of the postgres database",
+    ),
+    Setting::new(
+        "POSTGRES_RUN_MIGRATIONS",
+        "postgres-run-migrations",
+        "apply the pending postgres migrations at startup",
+    ),
+    Setting::new(
+        "MAIN_RPC_URL",
+        "main-rpc-url",
+        "URL of the RPC the raw transactions are forwarded to, required by the forwarding feature",
+    ),
+    Setting::new("MAX_FELTS_IN_CALLDATA", "max-felts-in-calldata", "maximum number of felts in a calldata"),
+    Setting::new(
+        "WHITE_LISTED_EIP_155_TRANSACTION_HASHES",
+        "white-listed-eip-155-transaction-hashes",
+        "comma separated hashes of the pre EIP-155 transactions accepted",
+    ),
+    Setting::new("MAX_LOGS", "max-logs", "maximum number of logs returned by eth_getLogs"),
+    Setting::new("MAX_LOGS_BLOCK_RANGE", "max-logs-block-range", "maximum number of blocks queried by eth_getLogs"),
+    Setting::new("PROVIDER_CACHE_SIZE", "provider-cache-size", "number of entries of each provider cache"),
+    Setting::new("SAFE_BLOCK_TAG", "safe-block-tag", "block of the safe tag: latest, finalized or confirmations"),
+    Setting::new(
+        "SYNCING_LAG_THRESHOLD",
+        "syncing-lag-threshold",
+        "number of blocks the indexer can lag before eth_syncing reports syncing",
+    ),
+    Setting::new("GAS_PRICE_ORACLE_BLOCKS", "gas-price-oracle-blocks", "number of blocks sampled by the gas oracle"),
+    Setting::new(
+        "GAS_PRICE_ORACLE_PERCENTILE",
+        "gas-price-oracle-percentile",
+        "percentile of the sampled priority fees suggested",
+    ),
+    Setting::new(
+        "GAS_PRICE_ORACLE_MIN_PRIORITY_FEE",
+        "gas-price-oracle-min-priority-fee",
+        "floor of the suggested priority fee per gas",
+    ),
+    Setting::new(
+        "GAS_PRICE_ORACLE_MAX_PRIORITY_FEE",
+        "gas-price-oracle-max-priority-fee",
+        "ceiling of the suggested priority fee per gas",
+    ),
+];
+
+/// Environment variable holding the path of the configuration file, when `--config` isn't set.
+pub const CONFIG_FILE_ENV: &str = "KAKAROT_RPC_CONFIG_FILE";
+
+/// The raw values of the settings, by environment variable name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigSources(HashMap<&'static str, String>);
+
+impl ConfigSources {
+    /// Loads the settings from the command line flags, the configuration file given by
+    /// `--config` or `KAKAROT_RPC_CONFIG_FILE` and the environment, in this order of precedence.
+    pub fn load<I, T>(args: I) -> eyre::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let (flags, config_file) = Self::from_args(args);
+        let config_file = config_file.or_else(|| var(CONFIG_FILE_ENV).ok().map(PathBuf::from));
+        let file = match config_file {
+            Some(path) => Self::from_toml(
+                &std::fs::read_to_string(&path)
+                    .map_err(|err| eyre!("failed to read the config file {}: {err}", path.display()))?,
+            )?,
+            None => Self::default(),
+        };
+
+        Ok(flags.or(file).or(Self::from_env()))
+    }
+
+    /// Parses the command line flags, returning the settings and the path of the configuration
+    /// file, if any. Exits the process on invalid flags or `--help`.
+    pub fn from_args<I, T>(args: I) -> (Self, Option<PathBuf>)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let command = SETTINGS.iter().fold(
+            Command::new("kakarot-rpc")
+                .about("Kakarot RPC")
+                .arg(Arg::new("config").long("config").value_name("PATH").help("path of the TOML configuration file")),
+            |command, setting| {
+                command.arg(Arg::new(setting.key).long(setting.key).value_name(setting.env).help(setting.help))
+            },
+        );
+        let matches = command.get_matches_from(args);
+
+        let mut sources = Self::default();
+        for setting in SETTINGS {
+            if let Some(value) = matches.get_one::<String>(setting.key) {
+                sources.insert(setting.env, value);
+            }
+        }
+        (sources, matches.get_one::<String>("config").map(PathBuf::from))
+    }
+
+    /// Parses a TOML configuration file, whose keys are the names of the command line flags.
+    /// Arrays are joined by commas.
+    pub fn from_toml(content: &str) -> eyre::Result<Self> {
+        let table = content.parse::<toml::Table>()?;
+        let mut sources = Self::default();
+        for (key, value) in table {
+            let setting = SETTINGS
+                .iter()
+                .find(|setting| setting.key == key)
+                .ok_or_else(|| eyre!("unknown key `{key}` in the config file"))?;
+            sources.insert(setting.env, &toml_to_string(&value).ok_or_else(|| eyre!("invalid value for `{key}`"))?);
+        }
+        Ok(sources)
+    }
+
+    /// Reads the settings from the environment variables.
+    pub fn from_env() -> Self {
+        let mut sources = Self::default();
+        for setting in SETTINGS {
+            if let Ok(value) = var(setting.env) {
+                sources.insert(setting.env, &value);
+            }
+        }
+        sources
+    }
+
+    /// Sets the value of the setting, empty values are ignored.
+    pub fn insert(&mut self, env: &'static str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() {
+            self.0.insert(env, value.to_string());
+        }
+    }
+
+    /// Returns the value of the setting.
+    pub fn get(&self, env: &str) -> Option<&str> {
+        self.0.get(env).map(String::as_str)
+    }
+
+    /// Completes the settings with the ones of the fallback.
+    #[must_use]
+    pub fn or(mut self, fallback: Self) -> Self {
+        for (env, value) in fallback.0 {
+            self.0.entry(env).or_insert(value);
+        }
+        self
+    }
+}
+
+fn toml_to_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Integer(value) => Some(value.to_string()),
+        toml::Value::Float(value) => Some(value.to_string()),
+        toml::Value::Boolean(value) => Some(value.to_string()),
+        toml::Value::Array(values) => Some(values.iter().map(toml_to_string).collect::<Option<Vec<_>>>()?.join(",")),
+        toml::Value::Datetime(_) | toml::Value::Table(_) => None,
+    }
+}
+
+/// The storage of the indexed Ethereum data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatabaseConfig {
+    /// `MONGO_*` settings.
+    Mongo { connection_string: String, database_name: String, run_migrations: bool },
+    /// `POSTGRES_*` settings.
+    Postgres { connection_string: String, run_migrations: bool },
+    /// Starts empty and isn't persisted.
+    Memory,
+}
+
+/// The configuration of the RPC, validated at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub starknet: KakarotRpcConfig,
+    pub relayers_addresses: Vec<Felt>,
+    pub relayer_private_key: Felt,
+    pub simulation_account_address: Option<Felt>,
+    pub rpc_listeners: Vec<RPCConfig>,
+    pub rpc_max_connections: u32,
+    pub prometheus_port: u16,
+    pub max_request_body_size: u32,
+    pub max_response_body_size: u32,
+    pub max_batch_size: u32,
+    pub timeouts: TimeoutConfig,
+    pub rate_limit: RateLimitConfig,
+    pub health: HealthConfig,
+    pub capture: Option<CaptureConfig>,
+    pub shutdown_timeout: Duration,
+    pub database: DatabaseConfig,
+    pub main_rpc_url: Option<Url>,
+    pub max_felts_in_calldata: usize,
+    pub white_listed_eip_155_transaction_hashes: Vec<B256>,
+    pub max_logs: Option<u64>,
+    pub max_logs_block_range: Option<u64>,
+    pub provider_cache_size: u32,
+    pub safe_block: SafeBlock,
+    pub syncing_lag_threshold: u64,
+    pub gas_price_oracle: GasPriceOracleConfig,
+}
+
+/// The configuration installed at startup.
+static INSTALLED: OnceLock<Config> = OnceLock::new();
+
+impl Config {
+    /// Loads and validates the configuration, see [`ConfigSources::load`].
+    pub fn load<I, T>(args: I) -> eyre::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Ok(Self::from_sources(&ConfigSources::load(args)?)?)
+    }
+
+    /// Builds the configuration, reporting all the missing and invalid settings at once.
+    #[allow(clippy::too_many_lines)]
+    pub fn from_sources(sources: &ConfigSources) -> Result<Self, ConfigError> {
+        let mut resolver = Resolver { sources, error: ConfigError::default() };
+
+        let network_url = resolver.required("STARKNET_NETWORK", parse);
+        let fallback_network_urls = resolver.optional("STARKNET_NETWORK_FALLBACKS", parse_list).unwrap_or_default();
+        let default_failover = FailoverConfig::default();
+        let failover = FailoverConfig {
+            max_retries: resolver.or("STARKNET_RETRIES", parse, default_failover.max_retries),
+            retry_backoff: resolver
+                .optional("STARKNET_RETRY_BACKOFF_MS", parse)
+                .map_or(default_failover.retry_backoff, Duration::from_millis),
+            failure_threshold: resolver.or(
+                "STARKNET_CIRCUIT_BREAKER_THRESHOLD",
+                parse,
+                default_failover.failure_threshold,
+            ),
+            cooldown: resolver
+                .optional("STARKNET_CIRCUIT_BREAKER_COOLDOWN_MS", parse)
+                .map_or(default_failover.cooldown, Duration::from_millis),
+            hedge_delay: resolver.optional("STARKNET_HEDGE_DELAY_MS", parse).map(Duration::from_millis),
+        };
+        let kakarot_address = resolver.required("KAKAROT_ADDRESS", parse);
+        let uninitialized_account_class_hash = resolver.required("UNINITIALIZED_ACCOUNT_CLASS_HASH", parse);
+        let account_contract_class_hash = resolver.required("ACCOUNT_CONTRACT_CLASS_HASH", parse);
+        let relayers_addresses = resolver.required("RELAYERS_ADDRESSES", parse_list);
+        let relayer_private_key = resolver.required("RELAYER_PRIVATE_KEY", parse);
+        let simulation_account_address = resolver
+            .optional("SIMULATION_ACCOUNT_ADDRESS", parse)
+            .or_else(|| relayers_addresses.as_ref().and_then(|addresses: &Vec<Felt>| addresses.first().copied()));
+
+        let main_listener = resolver.required("KAKAROT_RPC_URL", |socket_addr| Ok(RPCConfig::new(socket_addr.into())));
+        let main_listener = match (main_listener, resolver.optional("KAKAROT_RPC_API", KakarotRpcModule::parse_list)) {
+            (Some(listener), Some(modules)) => Some(listener.with_modules(modules)),
+            (listener, _) => listener,
+        };
+        let additional_listeners = resolver
+            .optional("KAKAROT_RPC_LISTENERS", |listeners| {
+                RPCConfig::parse_listeners(listeners).map_err(|err| err.to_string())
+            })
+            .unwrap_or_default();
+
+        let database = match resolver.or("DATABASE_BACKEND", parse, "mongo".to_string()).as_str() {
+            "mongo" => Some(DatabaseConfig::Mongo {
+                connection_string: resolver.required("MONGO_CONNECTION_STRING", parse).unwrap_or_default(),
+                database_name: resolver.required("MONGO_DATABASE_NAME", parse).unwrap_or_default(),
+                run_migrations: resolver.or("MONGO_RUN_MIGRATIONS", parse, false),
+            }),
+            "postgres" => Some(DatabaseConfig::Postgres {
+                connection_string: resolver.required("POSTGRES_CONNECTION_STRING", parse).unwrap_or_default(),
+                run_migrations: resolver.or("POSTGRES_RUN_MIGRATIONS", parse, false),
+            }),
+            "memory" => Some(DatabaseConfig::Memory),
+            backend => {
+                resolver
+                    .error
+                    .invalid
+                    .push(format!("DATABASE_BACKEND: expected mongo, postgres or memory, got {backend}"));
+                None
+            }
+        };
+
+        let default_oracle = GasPriceOracleConfig::default();
+        let gas_price_oracle = GasPriceOracleConfig {
+            blocks: resolver.or("GAS_PRICE_ORACLE_BLOCKS", parse, default_oracle.blocks),
+            percentile: resolver.or("GAS_PRICE_ORACLE_PERCENTILE", parse, default_oracle.percentile),
+            min_priority_fee: resolver.or("GAS_PRICE_ORACLE_MIN_PRIORITY_FEE", parse, default_oracle.min_priority_fee),
+            max_priority_fee: resolver.or("GAS_PRICE_ORACLE_MAX_PRIORITY_FEE", parse, default_oracle.max_priority_fee),
+        };
+        if let Err(err) = gas_price_oracle.validate() {
+            resolver.error.invalid.push(err.to_string());
+        }
+
+        let rpc_max_connections = resolver.or("RPC_MAX_CONNECTIONS", parse, 100);
+        let prometheus_port = resolver.or("PROMETHEUS_PORT", parse, 9615);
+        let max_request_body_size = resolver.or("RPC_MAX_REQUEST_BODY_SIZE", parse, 10 * 1024 * 1024);
+        let max_response_body_size = resolver.or("RPC_MAX_RESPONSE_BODY_SIZE", parse, 10 * 1024 * 1024);
+        let max_batch_size = resolver.or("RPC_MAX_BATCH_SIZE", parse, 100);
+        let timeouts = TimeoutConfig {
+            default: resolver.optional("RPC_TIMEOUT_MS", parse).map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+            methods: resolver.optional("RPC_METHOD_TIMEOUTS_MS", parse_method_timeouts).unwrap_or_default(),
+        };
+        let rate_limit = RateLimitConfig {
+            api_keys: resolver.optional("RPC_API_KEYS", parse_api_keys).unwrap_or_default(),
+            require_api_key: resolver.or("RPC_REQUIRE_API_KEY", parse, false),
+            api_key_limit: resolver.optional("RPC_API_KEY_RATE_LIMIT", parse),
+            ip_limit: resolver.optional("RPC_IP_RATE_LIMIT", parse),
+            trusted_proxies: resolver.optional("RPC_TRUSTED_PROXIES", parse_list).unwrap_or_default(),
+            method_weights: resolver.optional("RPC_METHOD_WEIGHTS", parse_method_weights).unwrap_or_default(),
+        };
+        let default_health = HealthConfig::default();
+        let health = HealthConfig {
+            max_indexer_lag: resolver.or("HEALTH_MAX_INDEXER_LAG", parse, default_health.max_indexer_lag),
+            max_mempool_transactions: resolver.or(
+                "HEALTH_MAX_MEMPOOL_TRANSACTIONS",
+                parse,
+                default_health.max_mempool_transactions,
+            ),
+            min_funded_relayers: resolver.or("HEALTH_MIN_FUNDED_RELAYERS", parse, default_health.min_funded_relayers),
+            check_timeout: resolver
+                .optional("HEALTH_CHECK_TIMEOUT_MS", parse)
+                .map_or(default_health.check_timeout, Duration::from_millis),
+            report_ttl: resolver
+                .optional("HEALTH_REPORT_TTL_MS", parse)
+                .map_or(default_health.report_ttl, Duration::from_millis),
+        };
+        let capture = resolver.optional("RPC_CAPTURE_PATH", parse).map(|path: PathBuf| CaptureConfig {
+            path,
+            sample_rate: resolver.or("RPC_CAPTURE_SAMPLE_RATE", parse_sample_rate, 1.),
+            max_file_size: resolver.or("RPC_CAPTURE_MAX_FILE_SIZE", parse, DEFAULT_CAPTURE_MAX_FILE_SIZE),
+            max_files: resolver.or("RPC_CAPTURE_MAX_FILES", parse, DEFAULT_CAPTURE_MAX_FILES),
+            omit_methods: resolver.optional("RPC_CAPTURE_OMIT_METHODS", parse_list).unwrap_or_default(),
+        });
+        let shutdown_timeout =
+            resolver.optional("SHUTDOWN_TIMEOUT_MS", parse).map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_millis);
+        let main_rpc_url = if cfg!(feature = "forwarding") {
+            resolver.required("MAIN_RPC_URL", parse)
+        } else {
+            resolver.optional("MAIN_RPC_URL", parse)
+        };
+        let max_felts_in_calldata = resolver.required("MAX_FELTS_IN_CALLDATA", parse);
+        let white_listed_eip_155_transaction_hashes =
+            resolver.optional("WHITE_LISTED_EIP_155_TRANSACTION_HASHES", parse_list).unwrap_or_default();
+        let max_logs = resolver.optional("MAX_LOGS", parse);
+        let max_logs_block_range = resolver.optional::<u64>("MAX_LOGS_BLOCK_RANGE", parse).filter(|range| *range > 0);
+        let provider_cache_size = resolver.or("PROVIDER_CACHE_SIZE", parse, 1_000);
+        let safe_block = resolver.or("SAFE_BLOCK_TAG", parse, SafeBlock::default());
+        let syncing_lag_threshold = resolver.or("SYNCING_LAG_THRESHOLD", parse, 10);
+
+        let (
+            Some(network_url),
+            Some(kakarot_address),
+            Some(uninitialized_account_class_hash),
+            Some(account_contract_class_hash),
+            Some(relayers_addresses),
+            Some(relayer_private_key),
+            Some(main_listener),
+            Some(database),
+            Some(max_felts_in_calldata),
+        ) = (
+            network_url,
+            kakarot_address,
+            uninitialized_account_class_hash,
+            account_contract_class_hash,
+            relayers_addresses,
+            relayer_private_key,
+            main_listener,
+            database,
+            max_felts_in_calldata,
+        )
+        else {
+            return Err(resolver.error);
+        };
+        if !resolver.error.is_empty() {
+            return Err(resolver.error);
+        }
+
         Ok(Self {
-            network_url: Url::parse(&var("STARKNET_NETWORK")?)?,
-            kakarot_address: env_var_to_field_element("KAKAROT_ADDRESS")?,
-            uninitialized_account_class_hash: env_var_to_field_element("UNINITIALIZED_ACCOUNT_CLASS_HASH")?,
-            account_contract_class_hash: env_var_to_field_element("ACCOUNT_CONTRACT_CLASS_HASH")?,
+            starknet: KakarotRpcConfig {
+                network_url,
+                fallback_network_urls,
+                failover,
+                kakarot_address,
+                uninitialized_account_class_hash,
+                account_contract_class_hash,
+            },
+            relayers_addresses,
+            relayer_private_key,
+            simulation_account_address,
+            rpc_listeners: std::iter::once(main_listener).chain(additional_listeners).collect(),
+            rpc_max_connections,
+            prometheus_port,
+            max_request_body_size,
+            max_response_body_size,
+            max_batch_size,
+            timeouts,
+            rate_limit,
+            health,
+            capture,
+            shutdown_timeout,
+            database,
+            main_rpc_url,
+            max_felts_in_calldata,
+            white_listed_eip_155_transaction_hashes,
+            max_logs,
+            max_logs_block_range,
+            provider_cache_size,
+            safe_block,
+            syncing_lag_threshold,
+            gas_price_oracle,
         })
     }
+
+    /// Installs the configuration for the whole process and returns it. Only the first
+    /// installed configuration is kept.
+    pub fn install(self) -> &'static Self {
+        INSTALLED.get_or_init(|| self)
+    }
+
+    /// Loads the configuration of the tests from the environment and the `.env` file, completed
+    /// by [`TEST_DEFAULTS`].
+    #[cfg(any(test, feature = "testing"))]
+    pub fn from_test_env() -> Self {
+        dotenvy::dotenv().ok();
+        let mut sources = ConfigSources::from_env();
+        // The deployment scripts name the network instead, e.g. `katana`.
+        if sources.get("STARKNET_NETWORK").is_some_and(|network| Url::parse(network).is_err()) {
+            sources.0.remove("STARKNET_NETWORK");
+        }
+        let mut defaults = ConfigSources::default();
+        for (env, value) in TEST_DEFAULTS {
+            defaults.insert(env, value);
+        }
+        Self::from_sources(&sources.or(defaults)).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Replaces the configuration of the tests, so that the settings changed by a test setup are
+    /// taken into account. Meant to be called on setup only, each configuration being leaked.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn install_for_tests(self) -> &'static Self {
+        let config: &'static Self = Box::leak(Box::new(self));
+        *TEST_CONFIG.write().unwrap_or_else(PoisonError::into_inner) = Some(config);
+        config
+    }
+}
+
+/// The configuration of the tests, see [`Config::install_for_tests`].
+#[cfg(any(test, feature = "testing"))]
+static TEST_CONFIG: RwLock<Option<&'static Config>> = RwLock::new(None);
+
+/// The values of the settings not set in the environment of the tests. The addresses and
+/// class hashes of the Kakarot contracts are loaded with `make load-env`.
+#[cfg(any(test, feature = "testing"))]
+const TEST_DEFAULTS: [(&str, &str); 8] = [
+    ("STARKNET_NETWORK", "http://0.0.0.0:5050"),
+    ("RELAYERS_ADDRESSES", "0x1"),
+    ("RELAYER_PRIVATE_KEY", "0x1"),
+    ("KAKAROT_RPC_URL", "127.0.0.1:3030"),
+    ("MONGO_CONNECTION_STRING", "mongodb://localhost:27017"),
+    ("MONGO_DATABASE_NAME", "kakarot-local"),
+    ("MAIN_RPC_URL", "http://0.0.0.0:8545"),
+    ("MAX_FELTS_IN_CALLDATA", "22500"),
+];
+
+/// Returns the configuration installed at startup.
+///
+/// In the tests, when no configuration was installed, the one of [`Config::install_for_tests`]
+/// is returned, loaded from the environment on the first call if none was installed.
+pub fn config() -> &'static Config {
+    #[cfg(any(test, feature = "testing"))]
+    if INSTALLED.get().is_none() {
+        if let Some(config) = *TEST_CONFIG.read().unwrap_or_else(PoisonError::into_inner) {
+            return config;
+        }
+        let mut test_config = TEST_CONFIG.write().unwrap_or_else(PoisonError::into_inner);
+        return *test_config.get_or_insert_with(|| Box::leak(Box::new(Config::from_test_env())));
+    }
+    INSTALLED.get().expect("the configuration is installed at startup")
+}
+
+/// The missing and invalid settings of a configuration.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub missing: Vec<Setting>,
+    pub invalid: Vec<String>,
+}
+
+impl ConfigError {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "invalid configuration")?;
+        if !self.missing.is_empty() {
+            writeln!(f, "missing values:")?;
+            for setting in &self.missing {
+                writeln!(f, "  - {setting}")?;
+            }
+        }
+        if !self.invalid.is_empty() {
+            writeln!(f, "invalid values:")?;
+            for error in &self.invalid {
+                writeln!(f, "  - {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Parses the settings, recording the missing and invalid ones.
+struct Resolver<'a> {
+    sources: &'a ConfigSources,
+    error: ConfigError,
+}
+
+impl Resolver<'_> {
+    fn optional<T>(&mut self, env: &'static str, parse: impl FnOnce(&str) -> Result<T, String>) -> Option<T> {
+        let value = self.sources.get(env)?;
+        parse(value).map_err(|err| self.error.invalid.push(format!("{env}: {err}"))).ok()
+    }
+
+    fn required<T>(&mut self, env: &'static str, parse: impl FnOnce(&str) -> Result<T, String>) -> Option<T> {
+        if self.sources.get(env).is_none() {
+            if let Some(setting) = SETTINGS.iter().find(|setting| setting.env == env) {
+                self.error.missing.push(*setting);
+            }
+        }
+        self.optional(env, parse)
+    }
+
+    fn or<T>(&mut self, env: &'static str, parse: impl FnOnce(&str) -> Result<T, String>, default: T) -> T {
+        self.optional(env, parse).unwrap_or(default)
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| err.to_string())
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, String>
+where
+    T::Err: fmt::Display,
+{
+    value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(parse).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_FILE: &str = r#"
+        starknet-network = "http://0.0.0.0:5050"
+        starknet-network-fallbacks = ["http://0.0.0.0:5051", "http://0.0.0.0:5052"]
+        kakarot-address = "0x1"
+        uninitialized-account-class-hash = "0x2"
+        account-contract-class-hash = "0x3"
+        relayers-addresses = ["0x4", "0x5"]
+        relayer-private-key = "0x6"
+        kakarot-rpc-url = "0.0.0.0:3030"
+        database-backend = "memory"
+        max-felts-in-calldata = 22500
+        max-logs = 10000
+    "#;
+
+    #[test]
+    fn test_config_from_toml() {
+        // Given
+        let sources = ConfigSources::from_toml(CONFIG_FILE).unwrap();
+
+        // When
+        let config = Config::from_sources(&sources).unwrap();
+
+        // Then
+        assert_eq!(config.starknet.kakarot_address, Felt::ONE);
+        assert_eq!(config.starknet.fallback_network_urls.len(), 2);
+        assert_eq!(config.starknet.failover, FailoverConfig::default());
+        assert_eq!(config.relayers_addresses, vec![Felt::from(4), Felt::from(5)]);
+        assert_eq!(config.simulation_account_address, Some(Felt::from(4)));
+        assert_eq!(config.rpc_listeners.len(), 1);
+        assert_eq!(config.database, DatabaseConfig::Memory);
+        assert_eq!(config.max_logs, Some(10_000));
+        assert_eq!(config.provider_cache_size, 1_000);
+    }
+
+    #[test]
+    fn test_config_flags_take_precedence() {
+        // Given
+        let (flags, config_file) =
+            ConfigSources::from_args(["kakarot-rpc", "--config", "rpc.toml", "--max-logs", "500"]);
+
+        // When
+        let sources = flags.or(ConfigSources::from_toml(CONFIG_FILE).unwrap());
+
+        // Then
+        assert_eq!(config_file, Some(PathBuf::from("rpc.toml")));
+        assert_eq!(sources.get("MAX_LOGS"), Some("500"));
+        assert_eq!(sources.get("KAKAROT_ADDRESS"), Some("0x1"));
+    }
+
+    #[test]
+    fn test_config_reports_missing_and_invalid_values() {
+        // Given
+        let mut sources = ConfigSources::default();
+        sources.insert("DATABASE_BACKEND", "postgres");
+        sources.insert("MAX_LOGS", "many");
+
+        // When
+        let error = Config::from_sources(&sources).unwrap_err();
+
+        // Then
+        let missing = error.missing.iter().map(|setting| setting.env).collect::<Vec<_>>();
+        assert!(missing.contains(&"KAKAROT_ADDRESS"));
+        assert!(missing.contains(&"POSTGRES_CONNECTION_STRING"));
+        assert!(!missing.contains(&"MONGO_CONNECTION_STRING"));
+        assert_eq!(error.invalid, vec!["MAX_LOGS: invalid digit found in string".to_string()]);
+    }
 }
@@ -1,8 +1,8 @@
-use crate::{config::KakarotRpcConfig, eth_rpc::config::RPCConfig};
+use crate::config::config;
 use num_traits::ToPrimitive;
 use starknet::{
     core::types::{Felt, NonZeroFelt},
-    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+    providers::Provider,
 };
 use std::sync::LazyLock;
 
@@ -12,7 +12,7 @@ pub static MAX_CHAIN_ID: u64 = (2u64.pow(53) - 39) / 2;
 pub static STARKNET_CHAIN_ID: LazyLock<Felt> = LazyLock::new(|| {
     tokio::task::block_in_place(|| {
         tokio::runtime::Handle::current().block_on(async {
-            let provider = JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone()));
+            let provider = config().starknet.starknet_provider();
             provider.chain_id().await.expect("failed to get chain for chain")
         })
     })
@@ -22,13 +22,5 @@ pub static ETH_CHAIN_ID: LazyLock<u64> = LazyLock::new(|| {
     STARKNET_CHAIN_ID.div_rem(&NonZeroFelt::from_felt_unchecked(Felt::from(MAX_CHAIN_ID))).1.to_u64().expect("modulo")
 });
 
-/// The Kakarot RPC configuration.
-pub static KAKAROT_RPC_CONFIG: LazyLock<KakarotRpcConfig> =
-    LazyLock::new(|| KakarotRpcConfig::from_env().expect("failed to load Kakarot RPC config"));
-
-/// The RPC configuration.
-pub static RPC_CONFIG: LazyLock<RPCConfig> =
-    LazyLock::new(|| RPCConfig::from_env().expect("failed to load RPC config"));
-
 /// The gas limit for Kakarot blocks.
 pub const KKRT_BLOCK_GAS_LIMIT: u64 = 7_000_000;
@@ -1,4 +1,8 @@
-use crate::providers::eth_provider::constant::Constant;
+use crate::providers::eth_provider::{
+    constant::Constant, starknet::simulation::StarknetResources, LogsCursor, LogsPage,
+};
+use alloy_primitives::Bytes;
+use alloy_rpc_types::Filter;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[rpc(server, namespace = "kakarot")]
@@ -6,4 +10,16 @@ use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 pub trait KakarotApi {
     #[method(name = "getConfig")]
     async fn get_config(&self) -> RpcResult<Constant>;
+
+    /// Returns the Starknet resources (fee, steps, builtins, L1 data gas) consumed by the
+    /// relaying of the given raw signed transaction, by simulating it on the pending block.
+    #[method(name = "estimateResources")]
+    async fn estimate_resources(&self, bytes: Bytes) -> RpcResult<StarknetResources>;
+
+    /// Returns a page of the logs matching the filter, along with the cursor to pass to
+    /// retrieve the next page. The pages of a block range are cut on block boundaries, the
+    /// logs of a block hash or of a block exceeding the page size are paged by their position
+    /// in the block.
+    #[method(name = "getLogsPaged")]
+    async fn get_logs_paged(&self, filter: Filter, cursor: Option<LogsCursor>) -> RpcResult<LogsPage>;
 }
@@ -1,29 +1,52 @@
+use super::rpc::KakarotRpcModule;
 use eyre::{eyre, Result};
 
 #[derive(Debug, Clone)]
 pub struct RPCConfig {
     pub socket_addr: String,
+    /// The RPC namespaces served on the socket address.
+    pub modules: Vec<KakarotRpcModule>,
 }
 
 impl RPCConfig {
-    pub const fn new(socket_addr: String) -> Self {
-        Self { socket_addr }
+    /// Returns the configuration of a listener serving every namespace.
+    pub fn new(socket_addr: String) -> Self {
+        Self { socket_addr, modules: KakarotRpcModule::ALL.to_vec() }
     }
 
-    pub fn from_env() -> Result<Self> {
-        let socket_addr = std::env::var("KAKAROT_RPC_URL")
-            .map_err(|_| eyre!("Missing mandatory environment variable: KAKAROT_RPC_URL"))?;
-        Ok(Self::new(socket_addr))
+    /// Restricts the namespaces served by the listener.
+    #[must_use]
+    pub fn with_modules(mut self, modules: Vec<KakarotRpcModule>) -> Self {
+        self.modules = modules;
+        self
     }
 
-    pub fn from_port(port: u16) -> Result<Self> {
-        let mut config = Self::from_env()?;
+    /// Parses a semicolon separated list of listeners, each of them being a socket address
+    /// followed by `=` and the comma separated namespaces it serves.
+    pub fn parse_listeners(listeners: &str) -> Result<Vec<Self>> {
+        listeners
+            .split(';')
+            .map(str::trim)
+            .filter(|listener| !listener.is_empty())
+            .map(|listener| {
+                let (socket_addr, api) = listener
+                    .split_once('=')
+                    .ok_or_else(|| eyre!("invalid RPC listener {listener}, expected <address>=<namespaces>"))?;
+                let modules = KakarotRpcModule::parse_list(api).map_err(|err| eyre!(err))?;
+                Ok(Self::new(socket_addr.trim().to_string()).with_modules(modules))
+            })
+            .collect()
+    }
+
+    /// Returns the configuration of the main listener of the installed configuration, on the given port.
+    pub fn from_port(port: u16) -> Self {
+        let mut config = crate::config::config().rpc_listeners[0].clone();
         // Remove port from socket address and replace it with provided port
         let parts: Vec<&str> = config.socket_addr.split(':').collect();
         if let Some(addr) = parts.first() {
             config.socket_addr = format!("{addr}:{port}");
         }
-        Ok(config)
+        config
     }
 }
 
@@ -44,3 +67,26 @@ impl RPCConfig {
         config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listeners() {
+        // When
+        let listeners =
+            RPCConfig::parse_listeners("0.0.0.0:3030=eth,net,web3; 127.0.0.1:3031=debug,trace,txpool;").unwrap();
+
+        // Then
+        assert_eq!(listeners.len(), 2);
+        assert_eq!(listeners[0].socket_addr, "0.0.0.0:3030");
+        assert_eq!(listeners[0].modules, vec![KakarotRpcModule::Eth, KakarotRpcModule::Net, KakarotRpcModule::Web3]);
+        assert_eq!(listeners[1].socket_addr, "127.0.0.1:3031");
+        assert_eq!(
+            listeners[1].modules,
+            vec![KakarotRpcModule::Debug, KakarotRpcModule::Trace, KakarotRpcModule::Txpool]
+        );
+        assert!(RPCConfig::parse_listeners("127.0.0.1:3031").is_err());
+    }
+}
@@ -0,0 +1,392 @@
+//! RPC middleware capturing a sample of the calls and their responses to a JSONL file, one
+//! [`CapturedCall`] per line. The file is rotated once it reaches its maximum size, keeping the
+//! previous files as `<path>.1`, `<path>.2`... up to the configured number of files.
+//!
+//! The captures can be replayed against another node with the `rpc_replay` binary. The parameters
+//! and responses of the omitted methods, e.g. `eth_sendRawTransaction`, aren't captured.
+
+use futures::future::Either;
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request, MethodResponse};
+use pin_project_lite::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Number of captured calls waiting to be written, the calls captured beyond are dropped.
+const CAPTURE_QUEUE_SIZE: usize = 10_000;
+
+/// Default maximum size of a capture file in bytes.
+pub const DEFAULT_CAPTURE_MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Default number of rotated capture files kept.
+pub const DEFAULT_CAPTURE_MAX_FILES: usize = 5;
+
+/// Configuration of the capture of the calls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureConfig {
+    /// Path of the capture file.
+    pub path: PathBuf,
+    /// Fraction of the calls captured, between 0 and 1.
+    pub sample_rate: f64,
+    /// Size in bytes after which the capture file is rotated.
+    pub max_file_size: u64,
+    /// Number of rotated capture files kept.
+    pub max_files: usize,
+    /// Methods captured without their parameters and responses.
+    pub omit_methods: Vec<String>,
+}
+
+/// Parses a sample rate, between 0 and 1.
+pub fn parse_sample_rate(rate: &str) -> Result<f64, String> {
+    rate.trim()
+        .parse()
+        .ok()
+        .filter(|rate: &f64| (0. ..=1.).contains(rate))
+        .ok_or_else(|| format!("invalid sample rate {rate}, expected a number between 0 and 1"))
+}
+
+/// A call captured by the middleware.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedCall {
+    /// Time the call was received, in milliseconds since the epoch.
+    pub timestamp_ms: u64,
+    pub method: String,
+    /// Parameters of the call, null if none.
+    pub params: Value,
+    /// Execution time of the call in microseconds.
+    pub latency_us: u64,
+    /// Code of the error returned, if any.
+    pub error_code: Option<i32>,
+    /// The JSON-RPC response returned.
+    pub response: Value,
+    /// True if the parameters and the response of the call were omitted.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub omitted: bool,
+}
+
+/// A sampled call, queued with its raw parameters and response which are parsed by the writer
+/// thread rather than on the RPC task.
+#[derive(Debug)]
+struct SampledCall {
+    timestamp_ms: u64,
+    method: String,
+    params: Option<String>,
+    latency_us: u64,
+    error_code: Option<i32>,
+    response: Option<String>,
+    omitted: bool,
+}
+
+impl From<SampledCall> for CapturedCall {
+    fn from(call: SampledCall) -> Self {
+        let parse =
+            |value: Option<String>| value.and_then(|value| serde_json::from_str(&value).ok()).unwrap_or_default();
+        Self {
+            timestamp_ms: call.timestamp_ms,
+            method: call.method,
+            params: parse(call.params),
+            latency_us: call.latency_us,
+            error_code: call.error_code,
+            response: parse(call.response),
+            omitted: call.omitted,
+        }
+    }
+}
+
+/// A capture file, rotated once it reaches its maximum size.
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    file: BufWriter<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_file_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, max_file_size, max_files, file: BufWriter::new(file), size })
+    }
+
+    /// Appends the line to the file, rotating it first if the line doesn't fit.
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Shifts the rotated files, dropping the oldest one, and starts a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        *self = Self::open(self.path.clone(), self.max_file_size, self.max_files)?;
+        Ok(())
+    }
+}
+
+/// Returns the path of the rotated capture file with the given index.
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{index}"));
+    path.into()
+}
+
+/// Writes the captured calls to the file until all the senders are dropped.
+fn write_captures(mut file: RotatingFile, receiver: &Receiver<SampledCall>) {
+    while let Ok(call) = receiver.recv() {
+        // Write all the queued calls before flushing.
+        for call in std::iter::once(call).chain(receiver.try_iter()) {
+            let result = serde_json::to_vec(&CapturedCall::from(call))
+                .map_err(io::Error::from)
+                .and_then(|line| file.write_line(&line));
+            if let Err(err) = result {
+                tracing::error!(%err, "failed to write the captured call");
+            }
+        }
+        if let Err(err) = file.flush() {
+            tracing::error!(%err, "failed to flush the capture file");
+        }
+    }
+}
+
+/// The sampling of the calls and the queue of the captured calls, written by a dedicated thread.
+#[derive(Debug)]
+struct Capture {
+    sender: SyncSender<SampledCall>,
+    sample_rate: f64,
+    omit_methods: Vec<String>,
+    calls: AtomicU64,
+}
+
+impl Capture {
+    /// Returns true if the next call should be captured. The captured calls are evenly spread:
+    /// with a rate of 0.25, one call out of four is captured.
+    fn sample(&self) -> bool {
+        let calls = self.calls.fetch_add(1, Ordering::Relaxed) as f64;
+        ((calls + 1.) * self.sample_rate).floor() > (calls * self.sample_rate).floor()
+    }
+
+    /// Returns true if the parameters and the response of the method shouldn't be captured.
+    fn omits(&self, method: &str) -> bool {
+        self.omit_methods.iter().any(|omitted| omitted == method)
+    }
+
+    fn send(&self, call: SampledCall) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(call) {
+            tracing::debug!("capture queue full, dropping the captured call");
+        }
+    }
+}
+
+/// RPC layer capturing the calls, see [`CaptureCalls`].
+#[derive(Debug, Clone)]
+pub struct CaptureLayer {
+    capture: Arc<Capture>,
+}
+
+impl CaptureLayer {
+    /// Opens the capture file and starts the thread writing the captured calls to it.
+    pub fn new(config: CaptureConfig) -> io::Result<Self> {
+        let file = RotatingFile::open(config.path, config.max_file_size, config.max_files)?;
+        let (sender, receiver) = sync_channel(CAPTURE_QUEUE_SIZE);
+        std::thread::Builder::new().name("rpc-capture".into()).spawn(move || write_captures(file, &receiver))?;
+        Ok(Self {
+            capture: Arc::new(Capture {
+                sender,
+                sample_rate: config.sample_rate,
+                omit_methods: config.omit_methods,
+                calls: AtomicU64::new(0),
+            }),
+        })
+    }
+}
+
+impl<S> tower::Layer<S> for CaptureLayer {
+    type Service = CaptureCalls<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        CaptureCalls { service, capture: self.capture.clone() }
+    }
+}
+
+/// RPC middleware capturing a sample of the calls along with their responses and latency.
+#[derive(Debug, Clone)]
+pub struct CaptureCalls<S> {
+    service: S,
+    capture: Arc<Capture>,
+}
+
+impl<'a, S> RpcServiceT<'a> for CaptureCalls<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = Either<S::Future, CaptureFuture<S::Future>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        if !self.capture.sample() {
+            return Either::Left(self.service.call(request));
+        }
+
+        let omitted = self.capture.omits(request.method_name());
+        let call = SampledCall {
+            timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            method: request.method_name().to_string(),
+            params: if omitted { None } else { request.params().as_str().map(ToOwned::to_owned) },
+            latency_us: 0,
+            error_code: None,
+            response: None,
+            omitted,
+        };
+        Either::Right(CaptureFuture {
+            fut: self.service.call(request),
+            call: Some(call),
+            now: Instant::now(),
+            capture: self.capture.clone(),
+        })
+    }
+}
+
+pin_project! {
+    /// Response future of the captured calls.
+    pub struct CaptureFuture<F> {
+        #[pin]
+        fut: F,
+        call: Option<SampledCall>,
+        now: Instant,
+        capture: Arc<Capture>,
+    }
+}
+
+impl<F> std::fmt::Debug for CaptureFuture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CaptureFuture")
+    }
+}
+
+impl<F: Future<Output = MethodResponse>> Future for CaptureFuture<F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = std::task::ready!(this.fut.poll(cx));
+
+        if let Some(mut call) = this.call.take() {
+            call.latency_us = this.now.elapsed().as_micros() as u64;
+            call.error_code = response.as_error_code();
+            if !call.omitted {
+                call.response = Some(response.as_result().to_owned());
+            }
+            this.capture.send(call);
+        }
+        Poll::Ready(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_sample() {
+        // Given
+        let (sender, _receiver) = sync_channel(1);
+        let capture = Capture { sender, sample_rate: 0.25, omit_methods: vec![], calls: AtomicU64::new(0) };
+
+        // When
+        let sampled = (0..100).filter(|_| capture.sample()).count();
+
+        // Then
+        assert_eq!(sampled, 25);
+        assert!(parse_sample_rate("1.5").is_err());
+    }
+
+    #[test]
+    fn test_captured_call_omitted() {
+        // Given
+        let (sender, _receiver) = sync_channel(1);
+        let capture = Capture {
+            sender,
+            sample_rate: 1.,
+            omit_methods: vec!["eth_sendRawTransaction".to_string()],
+            calls: AtomicU64::new(0),
+        };
+        let sampled = |method: &str| SampledCall {
+            timestamp_ms: 1,
+            method: method.to_string(),
+            params: Some(r#"["0x1"]"#.to_string()),
+            latency_us: 2,
+            error_code: None,
+            response: Some(r#""0x2""#.to_string()),
+            omitted: capture.omits(method),
+        };
+
+        // When
+        let captured = CapturedCall::from(sampled("eth_getBalance"));
+        let omitted =
+            CapturedCall::from(SampledCall { params: None, response: None, ..sampled("eth_sendRawTransaction") });
+
+        // Then
+        assert_eq!(captured.params, serde_json::json!(["0x1"]));
+        assert_eq!(captured.response, serde_json::json!("0x2"));
+        assert!(!captured.omitted);
+        assert!(!serde_json::to_string(&captured).unwrap().contains("omitted"));
+        assert_eq!(omitted.params, Value::Null);
+        assert_eq!(omitted.response, Value::Null);
+        assert!(omitted.omitted);
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        // Given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.jsonl");
+        let mut file = RotatingFile::open(path.clone(), 10, 2).unwrap();
+
+        // When
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        // Then
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+}
@@ -0,0 +1,509 @@
+//! Liveness and readiness HTTP endpoints.
+//!
+//! `GET /health/live` answers as long as the process serves requests. `GET /health/ready`
+//! checks each dependency of the RPC and reports them as JSON, with a `503` status when one of
+//! them isn't usable, or once the shutdown started, so that the orchestrator stops routing
+//! traffic to the instance. The mempool backlog and the funds of the relayers are shared by all
+//! the replicas, which routing the traffic elsewhere doesn't help, they are reported as
+//! `degraded` without failing the readiness:
+//!
+//! ```json
+//! {
+//!   "ready": false,
+//!   "checks": {
+//!     "database": { "status": "up", "latestBlock": 1200 },
+//!     "indexer": { "status": "down", "lag": 500, "maxLag": 50 },
+//!     "mempool": { "status": "degraded", "transactions": 6000, "maxTransactions": 5000 },
+//!     "relayers": { "status": "up", "funded": 4, "minFunded": 1 },
+//!     "starknet": { "status": "up", "blockNumber": 1700 },
+//!     "transactions": { "status": "up", "accepting": true }
+//!   }
+//! }
+//! ```
+
+use crate::{
+    client::EthClient,
+    pool::mempool::AccountManager,
+    providers::eth_provider::chain::{latest_indexed_block_number, record_indexer_lag},
+};
+use async_trait::async_trait;
+use hyper::{Method, StatusCode};
+use jsonrpsee::server::{HttpBody, HttpRequest, HttpResponse};
+use reth_transaction_pool::TransactionPool;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use starknet::providers::Provider;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// Path of the liveness endpoint.
+pub const LIVENESS_PATH: &str = "/health/live";
+
+/// Path of the readiness endpoint.
+pub const READINESS_PATH: &str = "/health/ready";
+
+/// Thresholds of the readiness checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthConfig {
+    /// Number of blocks the indexer can lag behind the Starknet head.
+    pub max_indexer_lag: u64,
+    /// Number of transactions waiting in the mempool.
+    pub max_mempool_transactions: usize,
+    /// Number of relayers holding enough funds to relay transactions.
+    pub min_funded_relayers: usize,
+    /// Time given to each dependency to answer.
+    pub check_timeout: Duration,
+    /// Time during which a readiness report is served before the dependencies are checked again.
+    pub report_ttl: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_indexer_lag: 50,
+            max_mempool_transactions: 5_000,
+            min_funded_relayers: 1,
+            check_timeout: Duration::from_secs(5),
+            report_ttl: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Status of a dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Up,
+    /// The dependency is shared by all the replicas, the instance stays ready.
+    Degraded,
+    Down,
+}
+
+/// Result of the check of a dependency, along with the values it was decided on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub status: CheckStatus,
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+impl Check {
+    fn new(up: bool, details: Value) -> Self {
+        Self::with_status(if up { CheckStatus::Up } else { CheckStatus::Down }, details)
+    }
+
+    /// Returns the check of a dependency shared by all the replicas, degraded instead of down.
+    fn shared(up: bool, details: Value) -> Self {
+        Self::with_status(if up { CheckStatus::Up } else { CheckStatus::Degraded }, details)
+    }
+
+    fn with_status(status: CheckStatus, details: Value) -> Self {
+        let details = match details {
+            Value::Object(details) => details,
+            _ => Map::new(),
+        };
+        Self { status, details }
+    }
+
+    fn error(error: impl ToString) -> Self {
+        Self::new(false, json!({ "error": error.to_string() }))
+    }
+}
+
+/// Report of the readiness endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadinessReport {
+    /// True if no dependency is down.
+    pub ready: bool,
+    /// Checks of the dependencies, by name.
+    pub checks: BTreeMap<&'static str, Check>,
+}
+
+/// Values fetched from the dependencies, or the reason they couldn't be fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyStates {
+    /// Number of the latest block indexed in the database.
+    pub latest_indexed_block: Result<u64, String>,
+    /// Number of the Starknet block of the latest indexed block.
+    pub indexed_starknet_block: Result<u64, String>,
+    /// Number of the latest Starknet block.
+    pub starknet_block: Result<u64, String>,
+    /// Number of funded relayers, `None` when the relayers aren't managed by this instance.
+    pub funded_relayers: Option<Result<usize, String>>,
+    /// Number of transactions in the mempool.
+    pub mempool_transactions: usize,
+    /// False once the shutdown started and the sent transactions are rejected.
+    pub accepting_transactions: bool,
+}
+
+impl ReadinessReport {
+    /// Checks the states of the dependencies against the thresholds of the configuration.
+    pub fn new(states: DependencyStates, config: &HealthConfig) -> Self {
+        let mut checks = BTreeMap::new();
+
+        checks.insert(
+            "indexer",
+            match (&states.indexed_starknet_block, &states.starknet_block) {
+                (Ok(indexed), Ok(head)) => {
+                    let lag = head.saturating_sub(*indexed);
+                    Check::new(lag <= config.max_indexer_lag, json!({ "lag": lag, "maxLag": config.max_indexer_lag }))
+                }
+                _ => Check::error("lag unknown, the database or Starknet is unreachable"),
+            },
+        );
+        checks.insert(
+            "database",
+            states
+                .latest_indexed_block
+                .map_or_else(Check::error, |block| Check::new(true, json!({ "latestBlock": block }))),
+        );
+        checks.insert(
+            "starknet",
+            states.starknet_block.map_or_else(Check::error, |block| Check::new(true, json!({ "blockNumber": block }))),
+        );
+        if let Some(funded_relayers) = states.funded_relayers {
+            checks.insert(
+                "relayers",
+                funded_relayers.map_or_else(
+                    |error| Check::shared(false, json!({ "error": error })),
+                    |funded| {
+                        Check::shared(
+                            funded >= config.min_funded_relayers,
+                            json!({ "funded": funded, "minFunded": config.min_funded_relayers }),
+                        )
+                    },
+                ),
+            );
+        }
+        checks.insert(
+            "mempool",
+            Check::shared(
+                states.mempool_transactions <= config.max_mempool_transactions,
+                json!({
+                    "transactions": states.mempool_transactions,
+                    "maxTransactions": config.max_mempool_transactions
+                }),
+            ),
+        );
+        checks.insert(
+            "transactions",
+            Check::new(states.accepting_transactions, json!({ "accepting": states.accepting_transactions })),
+        );
+
+        let ready = checks.values().all(|check| check.status != CheckStatus::Down);
+        Self { ready, checks }
+    }
+}
+
+/// Source of the readiness report, type erased so that the HTTP middleware doesn't depend on
+/// the Starknet provider.
+#[async_trait]
+pub trait ReadinessCheck: std::fmt::Debug + Send + Sync {
+    async fn readiness(&self) -> ReadinessReport;
+}
+
+/// Checks the dependencies of an [`EthClient`] and of the relayers of its [`AccountManager`].
+#[derive(Debug)]
+pub struct HealthChecker<SP: Provider + Send + Sync + Clone + 'static> {
+    eth_client: Arc<EthClient<SP>>,
+    relayers: Option<Arc<AccountManager<SP>>>,
+    config: HealthConfig,
+}
+
+impl<SP: Provider + Send + Sync + Clone + 'static> HealthChecker<SP> {
+    pub const fn new(
+        eth_client: Arc<EthClient<SP>>,
+        relayers: Option<Arc<AccountManager<SP>>>,
+        config: HealthConfig,
+    ) -> Self {
+        Self { eth_client, relayers, config }
+    }
+
+    /// Runs the future, failing if it doesn't complete within the check timeout.
+    async fn timed<T, E: ToString>(&self, future: impl Future<Output = Result<T, E>>) -> Result<T, String> {
+        tokio::time::timeout(self.config.check_timeout, future)
+            .await
+            .map_err(|_| format!("no answer after {}ms", self.config.check_timeout.as_millis()))?
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[async_trait]
+impl<SP: Provider + Send + Sync + Clone + 'static> ReadinessCheck for HealthChecker<SP> {
+    async fn readiness(&self) -> ReadinessReport {
+        let eth_provider = self.eth_client.eth_provider();
+        let (latest_indexed_block, starknet_block, funded_relayers) = tokio::join!(
+            self.timed(async { eth_provider.database().latest_header().await.map(latest_indexed_block_number) }),
+            self.timed(self.eth_client.starknet_provider().block_number()),
+            async {
+                match &self.relayers {
+                    Some(relayers) => Some(self.timed(relayers.funded_relayers()).await),
+                    None => None,
+                }
+            }
+        );
+        let indexed_starknet_block = match &latest_indexed_block {
+            Ok(indexed) => self.timed(eth_provider.indexed_starknet_block_number(*indexed)).await,
+            Err(err) => Err(err.clone()),
+        };
+        if let (Ok(indexed), Ok(head)) = (&indexed_starknet_block, &starknet_block) {
+            record_indexer_lag(*indexed, *head);
+        }
+        let mempool_transactions = self.eth_client.mempool().pool_size().total;
+        let accepting_transactions = self.eth_client.is_accepting_transactions();
+
+        ReadinessReport::new(
+            DependencyStates {
+                latest_indexed_block,
+                indexed_starknet_block,
+                starknet_block,
+                funded_relayers,
+                mempool_transactions,
+                accepting_transactions,
+            },
+            &self.config,
+        )
+    }
+}
+
+/// Serves the report of the inner check for [`HealthConfig::report_ttl`], so that the readiness
+/// endpoint, answered before the authentication and the rate limits, doesn't query the
+/// dependencies on each request.
+#[derive(Debug)]
+pub struct CachedReadiness<C> {
+    inner: C,
+    ttl: Duration,
+    last_report: Mutex<Option<(Instant, ReadinessReport)>>,
+}
+
+impl<C> CachedReadiness<C> {
+    pub fn new(inner: C, ttl: Duration) -> Self {
+        Self { inner, ttl, last_report: Mutex::new(None) }
+    }
+}
+
+#[async_trait]
+impl<C: ReadinessCheck> ReadinessCheck for CachedReadiness<C> {
+    async fn readiness(&self) -> ReadinessReport {
+        // The lock is held during the check, so that the concurrent requests wait for its report.
+        let mut last_report = self.last_report.lock().await;
+        if let Some((checked_at, report)) = last_report.as_ref() {
+            if checked_at.elapsed() < self.ttl {
+                return report.clone();
+            }
+        }
+        let report = self.inner.readiness().await;
+        *last_report = Some((Instant::now(), report.clone()));
+        report
+    }
+}
+
+/// HTTP layer serving the health endpoints, see [`Health`].
+#[derive(Debug, Clone)]
+pub struct HealthLayer {
+    readiness: Arc<dyn ReadinessCheck>,
+}
+
+impl HealthLayer {
+    pub fn new(readiness: Arc<dyn ReadinessCheck>) -> Self {
+        Self { readiness }
+    }
+}
+
+impl<S> tower::Layer<S> for HealthLayer {
+    type Service = Health<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Health { inner, readiness: self.readiness.clone() }
+    }
+}
+
+/// HTTP middleware answering the `GET` requests to [`LIVENESS_PATH`] and [`READINESS_PATH`]
+/// and forwarding the other requests.
+#[derive(Debug, Clone)]
+pub struct Health<S> {
+    inner: S,
+    readiness: Arc<dyn ReadinessCheck>,
+}
+
+impl<S, B> tower::Service<HttpRequest<B>> for Health<S>
+where
+    S: tower::Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+        if request.method() != Method::GET {
+            return Box::pin(self.inner.call(request));
+        }
+
+        match request.uri().path() {
+            LIVENESS_PATH => Box::pin(async { Ok(json_response(StatusCode::OK, &json!({ "status": "alive" }))) }),
+            READINESS_PATH => {
+                let readiness = self.readiness.clone();
+                Box::pin(async move {
+                    let report = readiness.readiness().await;
+                    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+                    Ok(json_response(status, &report))
+                })
+            }
+            _ => Box::pin(self.inner.call(request)),
+        }
+    }
+}
+
+/// Returns a JSON response with the given status.
+fn json_response(status: StatusCode, body: &impl Serialize) -> HttpResponse {
+    HttpResponse::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(HttpBody::from(serde_json::to_string(body).unwrap_or_default()))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states() -> DependencyStates {
+        DependencyStates {
+            latest_indexed_block: Ok(90),
+            indexed_starknet_block: Ok(100),
+            starknet_block: Ok(110),
+            funded_relayers: Some(Ok(2)),
+            mempool_transactions: 10,
+            accepting_transactions: true,
+        }
+    }
+
+    #[test]
+    fn test_readiness_report_ready() {
+        // Given
+        let config = HealthConfig { max_indexer_lag: 10, ..Default::default() };
+
+        // When
+        let report = ReadinessReport::new(states(), &config);
+
+        // Then
+        assert!(report.ready);
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "ready": true,
+                "checks": {
+                    "database": { "status": "up", "latestBlock": 90 },
+                    "indexer": { "status": "up", "lag": 10, "maxLag": 10 },
+                    "mempool": { "status": "up", "transactions": 10, "maxTransactions": 5000 },
+                    "relayers": { "status": "up", "funded": 2, "minFunded": 1 },
+                    "starknet": { "status": "up", "blockNumber": 110 },
+                    "transactions": { "status": "up", "accepting": true }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_readiness_report_not_ready() {
+        // Given
+        let config = HealthConfig { max_indexer_lag: 5, min_funded_relayers: 3, ..Default::default() };
+        let unreachable = DependencyStates {
+            starknet_block: Err("connection refused".to_string()),
+            funded_relayers: None,
+            ..states()
+        };
+
+        // When
+        let lagging = ReadinessReport::new(states(), &config);
+        let unreachable = ReadinessReport::new(unreachable, &config);
+
+        // Then
+        assert!(!lagging.ready);
+        assert_eq!(lagging.checks["indexer"].status, CheckStatus::Down);
+        assert_eq!(lagging.checks["relayers"].status, CheckStatus::Degraded);
+        assert_eq!(lagging.checks["database"].status, CheckStatus::Up);
+
+        assert!(!unreachable.ready);
+        assert_eq!(unreachable.checks["starknet"].details["error"], "connection refused");
+        assert_eq!(unreachable.checks["indexer"].status, CheckStatus::Down);
+        assert!(!unreachable.checks.contains_key("relayers"));
+    }
+
+    #[test]
+    fn test_readiness_report_degraded() {
+        // Given
+        let config = HealthConfig { max_mempool_transactions: 5, min_funded_relayers: 3, ..Default::default() };
+        let unfunded = DependencyStates { funded_relayers: Some(Err("connection refused".to_string())), ..states() };
+
+        // When
+        let backlogged = ReadinessReport::new(states(), &config);
+        let unfunded = ReadinessReport::new(unfunded, &config);
+
+        // Then
+        assert!(backlogged.ready);
+        assert_eq!(backlogged.checks["mempool"].status, CheckStatus::Degraded);
+        assert_eq!(backlogged.checks["relayers"].status, CheckStatus::Degraded);
+        assert_eq!(backlogged.checks["indexer"].status, CheckStatus::Up);
+
+        assert!(unfunded.ready);
+        assert_eq!(unfunded.checks["relayers"].status, CheckStatus::Degraded);
+        assert_eq!(unfunded.checks["relayers"].details["error"], "connection refused");
+    }
+
+    #[test]
+    fn test_readiness_report_shutting_down() {
+        // Given
+        let shutting_down = DependencyStates { accepting_transactions: false, ..states() };
+
+        // When
+        let report = ReadinessReport::new(shutting_down, &HealthConfig::default());
+
+        // Then
+        assert!(!report.ready);
+        assert_eq!(report.checks["transactions"].status, CheckStatus::Down);
+        assert_eq!(report.checks["transactions"].details["accepting"], false);
+        assert_eq!(report.checks["database"].status, CheckStatus::Up);
+    }
+
+    /// Counts the checks of the dependencies.
+    #[derive(Debug, Default)]
+    struct CountingCheck(std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl ReadinessCheck for CountingCheck {
+        async fn readiness(&self) -> ReadinessReport {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            ReadinessReport::new(states(), &HealthConfig::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_readiness() {
+        // Given
+        let cached = CachedReadiness::new(CountingCheck::default(), Duration::from_secs(60));
+        let expired = CachedReadiness::new(CountingCheck::default(), Duration::ZERO);
+
+        // When
+        for _ in 0..3 {
+            assert_eq!(cached.readiness().await, ReadinessReport::new(states(), &HealthConfig::default()));
+            expired.readiness().await;
+        }
+
+        // Then
+        assert_eq!(cached.inner.0.into_inner(), 1);
+        assert_eq!(expired.inner.0.into_inner(), 3);
+    }
+}
@@ -19,7 +19,7 @@
 //! RPC middleware to collect prometheus metrics on RPC calls.
 
 use crate::prometheus_handler::{
-    register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, U64,
+    register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, HISTOGRAM_BUCKETS, U64,
 };
 use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request, MethodResponse};
 use pin_project_lite::pin_project;
@@ -30,23 +30,6 @@ use std::{
     time::Instant,
 };
 
-/// Histogram time buckets in microseconds.
-const HISTOGRAM_BUCKETS: [f64; 13] = [
-    5.0,
-    25.0,
-    100.0,
-    500.0,
-    1_000.0,
-    2_500.0,
-    10_000.0,
-    25_000.0,
-    100_000.0,
-    1_000_000.0,
-    2_000_000.0,
-    5_000_000.0,
-    10_000_000.0,
-];
-
 /// Metrics for RPC middleware storing information about the number of requests started/completed,
 /// calls started/completed and their timings.
 #[derive(Debug, Clone)]
@@ -22,3 +22,11 @@
 pub mod metrics;
 /// Rate limit middleware.
 pub use metrics::*;
+/// Capture of the calls for replay.
+pub mod capture;
+/// Liveness and readiness endpoints.
+pub mod health;
+/// API keys and rate limits middleware.
+pub mod rate_limit;
+/// Timeout middleware.
+pub mod timeout;
@@ -0,0 +1,501 @@
+//! API keys authentication and per client rate limits, weighted by method.
+//!
+//! The clients are identified at the HTTP level by their API key, given in the `x-api-key`
+//! header or as the path of the URL (`https://rpc.example/<key>`), or by their IP address
+//! otherwise. The IP address is the peer address of the connection, unless the peer is one of
+//! the trusted reverse proxies, in which case it is read from the `x-forwarded-for` and
+//! `x-real-ip` headers they set.
+//!
+//! Each call then consumes a number of units from the budget of its client, depending on the
+//! cost of the method: tracing a block costs more than returning the block number.
+
+use crate::prometheus_handler::{register, CounterVec, Opts, PrometheusError, Registry, U64};
+use futures::future::Either;
+use hyper::{Method, Uri};
+use jsonrpsee::{
+    server::{http::response::denied, middleware::rpc::RpcServiceT, HttpRequest, HttpResponse},
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    net::IpAddr,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+/// Error code returned to the rate limited calls.
+pub const RATE_LIMIT_ERROR_CODE: i32 = -32005;
+
+/// Maximum number of clients whose budget is tracked, the least recently seen are forgotten.
+const MAX_TRACKED_CLIENTS: u32 = 100_000;
+
+/// Header holding the API key.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Label of the clients without API key in the metrics.
+const ANONYMOUS: &str = "anonymous";
+
+/// An API key, given as `<name>:<key>[:<units per second>]`. The name identifies the key in the
+/// metrics, so that the key itself is never exported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    pub name: String,
+    pub key: String,
+    /// Units per second of the key, the default API key limit if not set.
+    pub limit: Option<u32>,
+}
+
+impl FromStr for ApiKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let (Some(name), Some(key)) = (parts.next(), parts.next()) else {
+            return Err(format!("invalid API key {s}, expected <name>:<key>[:<units per second>]"));
+        };
+        let limit = parts.next().map(|limit| limit.parse().map_err(|_| format!("invalid limit for API key {name}")));
+        if parts.next().is_some() || name.is_empty() || key.is_empty() {
+            return Err(format!("invalid API key {name}, expected <name>:<key>[:<units per second>]"));
+        }
+        Ok(Self { name: name.to_string(), key: key.to_string(), limit: limit.transpose()? })
+    }
+}
+
+/// Configuration of the authentication and rate limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// The accepted API keys.
+    pub api_keys: Vec<ApiKey>,
+    /// Rejects the requests without a valid API key.
+    pub require_api_key: bool,
+    /// Units per second of the API keys without their own limit, unlimited if not set.
+    pub api_key_limit: Option<u32>,
+    /// Units per second of each IP address without API key, unlimited if not set.
+    pub ip_limit: Option<u32>,
+    /// The reverse proxies whose forwarded IP address headers are trusted.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Units consumed by the methods, overriding [`default_method_weight`].
+    pub method_weights: HashMap<String, u32>,
+}
+
+impl RateLimitConfig {
+    /// Returns the units consumed by a call to the method.
+    pub fn method_weight(&self, method: &str) -> u32 {
+        self.method_weights.get(method).copied().unwrap_or_else(|| default_method_weight(method))
+    }
+}
+
+/// Parses a comma separated list of API keys, see [`ApiKey`].
+pub fn parse_api_keys(keys: &str) -> Result<Vec<ApiKey>, String> {
+    keys.split(',').filter(|key| !key.trim().is_empty()).map(ApiKey::from_str).collect()
+}
+
+/// Parses a comma separated list of `<method>=<units>`, e.g. `debug_traceBlockByNumber=50`.
+pub fn parse_method_weights(weights: &str) -> Result<HashMap<String, u32>, String> {
+    weights
+        .split(',')
+        .map(str::trim)
+        .filter(|weight| !weight.is_empty())
+        .map(|weight| {
+            let (method, units) = weight
+                .split_once('=')
+                .ok_or_else(|| format!("invalid method weight {weight}, expected <method>=<units>"))?;
+            let units = units.trim().parse().map_err(|_| format!("invalid units for method {method}"))?;
+            Ok((method.trim().to_string(), units))
+        })
+        .collect()
+}
+
+/// Returns the default units consumed by a call to the method, depending on the load it puts
+/// on Starknet and the database.
+pub fn default_method_weight(method: &str) -> u32 {
+    match method {
+        method if method.starts_with("debug_") || method.starts_with("trace_") => 20,
+        "eth_call"
+        | "eth_estimateGas"
+        | "eth_getLogs"
+        | "eth_feeHistory"
+        | "eth_sendRawTransaction"
+        | "kakarot_getLogsPaged"
+        | "kakarot_estimateResources" => 5,
+        _ => 1,
+    }
+}
+
+/// The client of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Client {
+    /// A client authenticated by its API key, identified by the name of the key.
+    ApiKey(Arc<str>),
+    /// A client without API key.
+    Ip(IpAddr),
+    /// A client without API key whose IP address is unknown.
+    Anonymous,
+}
+
+impl Client {
+    fn label(&self) -> &str {
+        match self {
+            Self::ApiKey(name) => name,
+            Self::Ip(_) | Self::Anonymous => ANONYMOUS,
+        }
+    }
+}
+
+/// The IP address of the peer of the connection, attached to the HTTP requests by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerAddr(pub IpAddr);
+
+/// Metrics of the usage of the RPC by client.
+#[derive(Debug, Clone)]
+struct RateLimitMetrics {
+    /// Units consumed by the clients.
+    units: CounterVec<U64>,
+    /// Calls rejected because the client exceeded its limit.
+    limited: CounterVec<U64>,
+}
+
+impl RateLimitMetrics {
+    fn new(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            units: register(
+                CounterVec::new(Opts::new("rpc_client_units", "Units consumed by the RPC clients"), &["client"])?,
+                registry,
+            )?,
+            limited: register(
+                CounterVec::new(
+                    Opts::new("rpc_client_rate_limited_calls", "Number of calls rejected by the rate limits"),
+                    &["client", "method"],
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+/// The budget of a client, refilled continuously up to one second worth of units.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    units: f64,
+    updated: Instant,
+}
+
+/// Authenticates the clients and tracks their budget.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    /// The API keys names, by key.
+    keys: HashMap<String, Arc<str>>,
+    /// The limits of the API keys, by name.
+    key_limits: HashMap<Arc<str>, Option<u32>>,
+    buckets: Mutex<LruMap<Client, Bucket>>,
+    metrics: RateLimitMetrics,
+}
+
+impl RateLimiter {
+    /// Returns a new rate limiter, whose metrics are registered in the given registry.
+    pub fn new(config: RateLimitConfig, registry: &Registry) -> Result<Self, PrometheusError> {
+        let mut keys = HashMap::new();
+        let mut key_limits = HashMap::new();
+        for api_key in &config.api_keys {
+            let name: Arc<str> = Arc::from(api_key.name.as_str());
+            keys.insert(api_key.key.clone(), name.clone());
+            key_limits.insert(name, api_key.limit.or(config.api_key_limit));
+        }
+
+        Ok(Self {
+            config,
+            keys,
+            key_limits,
+            buckets: Mutex::new(LruMap::new(ByLength::new(MAX_TRACKED_CLIENTS))),
+            metrics: RateLimitMetrics::new(registry)?,
+        })
+    }
+
+    /// Identifies the client of the request, returns `None` if the request must be rejected.
+    /// A path other than the root must be a valid API key.
+    fn authenticate<B>(&self, request: &HttpRequest<B>) -> Option<Client> {
+        let header_key = request.headers().get(API_KEY_HEADER).and_then(|key| key.to_str().ok());
+        let path_key = Some(request.uri().path().trim_matches('/')).filter(|path| !path.is_empty());
+        if path_key.is_some_and(|key| !self.keys.contains_key(key)) {
+            return None;
+        }
+
+        match header_key.or(path_key) {
+            Some(key) => self.keys.get(key).map(|name| Client::ApiKey(name.clone())),
+            None if self.config.require_api_key => None,
+            None => Some(self.client_ip(request).map_or(Client::Anonymous, Client::Ip)),
+        }
+    }
+
+    /// Returns the IP address of the client: the peer address of the connection or, if the peer
+    /// is a trusted proxy, the address it forwarded.
+    fn client_ip<B>(&self, request: &HttpRequest<B>) -> Option<IpAddr> {
+        let PeerAddr(peer) = *request.extensions().get::<PeerAddr>()?;
+        if !self.config.trusted_proxies.contains(&peer) {
+            return Some(peer);
+        }
+        forwarded_ip(request, &self.config.trusted_proxies).or(Some(peer))
+    }
+
+    /// Consumes the units of the call from the budget of the client, returns false if the
+    /// budget is exhausted. Calls weighing more than the limit of the client consume its whole
+    /// budget.
+    fn try_acquire(&self, client: &Client, weight: u32) -> bool {
+        let limit = match client {
+            Client::ApiKey(name) => self.key_limits.get(name).copied().flatten(),
+            Client::Ip(_) | Client::Anonymous => self.config.ip_limit,
+        };
+        let Some(limit) = limit else {
+            self.metrics.units.with_label_values(&[client.label()]).inc_by(u64::from(weight));
+            return true;
+        };
+
+        let weight = weight.min(limit);
+        let (limit, units) = (f64::from(limit), f64::from(weight));
+        let now = Instant::now();
+        let acquired = {
+            // The buckets are never left in an inconsistent state, hence a poisoned lock is safe to use.
+            let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+            let Some(bucket) = buckets.get_or_insert(client.clone(), || Bucket { units: limit, updated: now }) else {
+                return true;
+            };
+            bucket.units = now.duration_since(bucket.updated).as_secs_f64().mul_add(limit, bucket.units).min(limit);
+            bucket.updated = now;
+            let acquired = bucket.units >= units;
+            if acquired {
+                bucket.units -= units;
+            }
+            acquired
+        };
+
+        if acquired {
+            self.metrics.units.with_label_values(&[client.label()]).inc_by(u64::from(weight));
+        }
+        acquired
+    }
+}
+
+/// Returns the IP address of the client forwarded by the trusted proxies. The addresses of
+/// `x-forwarded-for` are appended by each proxy, hence the client is the last one which isn't
+/// a trusted proxy, the preceding ones being set by the client itself.
+fn forwarded_ip<B>(request: &HttpRequest<B>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let headers = request.headers();
+    let forwarded = headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()).unwrap_or_default();
+    let mut client = None;
+    for ip in forwarded.rsplit(',').map_while(|ip| ip.trim().parse::<IpAddr>().ok()) {
+        client = Some(ip);
+        if !trusted_proxies.contains(&ip) {
+            break;
+        }
+    }
+    client.or_else(|| {
+        headers.get("x-real-ip").and_then(|value| value.to_str().ok()).and_then(|ip| ip.trim().parse().ok())
+    })
+}
+
+/// HTTP layer authenticating the clients, see [`ApiKeyAuth`].
+#[derive(Debug, Clone)]
+pub struct ApiKeyLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl ApiKeyLayer {
+    pub const fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> tower::Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyAuth { inner, limiter: self.limiter.clone() }
+    }
+}
+
+/// HTTP middleware rejecting the requests with an unknown API key, or without API key if one
+/// is required, and attaching the [`Client`] to the accepted requests. The health check is
+/// always accepted.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> tower::Service<HttpRequest<B>> for ApiKeyAuth<S>
+where
+    S: tower::Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        if request.method() == Method::GET && request.uri().path() == "/health" {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let Some(client) = self.limiter.authenticate(&request) else {
+            return Box::pin(ready(Ok(denied())));
+        };
+        if matches!(client, Client::ApiKey(_)) && request.uri().path() != "/" {
+            // The API key was given as the path of the URL.
+            *request.uri_mut() = Uri::from_static("/");
+        }
+        request.extensions_mut().insert(client);
+
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// RPC layer enforcing the rate limits, see [`RateLimit`].
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub const fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> tower::Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RateLimit { service, limiter: self.limiter.clone() }
+    }
+}
+
+/// RPC middleware consuming the units of each call from the budget of its client, and
+/// rejecting the calls exceeding it.
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    service: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<'a, S> RpcServiceT<'a> for RateLimit<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = Either<S::Future, Ready<MethodResponse>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let client = request.extensions().get::<Client>().cloned().unwrap_or(Client::Anonymous);
+        let weight = self.limiter.config.method_weight(request.method_name());
+
+        if self.limiter.try_acquire(&client, weight) {
+            return Either::Left(self.service.call(request));
+        }
+
+        self.limiter.metrics.limited.with_label_values(&[client.label(), request.method_name()]).inc();
+        Either::Right(ready(MethodResponse::error(
+            request.id(),
+            ErrorObject::owned(RATE_LIMIT_ERROR_CODE, "rate limit exceeded", None::<()>),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(config: RateLimitConfig) -> RateLimiter {
+        RateLimiter::new(config, &Registry::new()).unwrap()
+    }
+
+    fn request(uri: &str, api_key: Option<&str>) -> HttpRequest<()> {
+        let mut builder = hyper::Request::post(uri);
+        if let Some(api_key) = api_key {
+            builder = builder.header(API_KEY_HEADER, api_key);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_api_keys_and_weights() {
+        // When
+        let keys = parse_api_keys("alice:secret:100, bob:other").unwrap();
+        let weights = parse_method_weights("debug_traceBlockByNumber=50,eth_blockNumber=1").unwrap();
+
+        // Then
+        assert_eq!(keys[0], ApiKey { name: "alice".into(), key: "secret".into(), limit: Some(100) });
+        assert_eq!(keys[1], ApiKey { name: "bob".into(), key: "other".into(), limit: None });
+        assert_eq!(weights.get("debug_traceBlockByNumber"), Some(&50));
+        assert!(parse_api_keys("alice").is_err());
+        assert!(parse_method_weights("eth_call").is_err());
+    }
+
+    #[test]
+    fn test_authenticate() {
+        // Given
+        let limiter = limiter(RateLimitConfig {
+            api_keys: parse_api_keys("alice:secret").unwrap(),
+            require_api_key: true,
+            ..Default::default()
+        });
+
+        // When & Then
+        assert_eq!(limiter.authenticate(&request("/", Some("secret"))), Some(Client::ApiKey("alice".into())));
+        assert_eq!(limiter.authenticate(&request("/secret", None)), Some(Client::ApiKey("alice".into())));
+        assert_eq!(limiter.authenticate(&request("/", Some("unknown"))), None);
+        assert_eq!(limiter.authenticate(&request("/unknown", Some("secret"))), None);
+        assert_eq!(limiter.authenticate(&request("/", None)), None);
+    }
+
+    #[test]
+    fn test_client_ip() {
+        // Given
+        let proxy = IpAddr::from([10, 0, 0, 2]);
+        let limiter = limiter(RateLimitConfig { trusted_proxies: vec![proxy], ..Default::default() });
+        let request = |peer: Option<IpAddr>, forwarded_for: &str| {
+            let mut request = hyper::Request::post("/").header("x-forwarded-for", forwarded_for).body(()).unwrap();
+            if let Some(peer) = peer {
+                request.extensions_mut().insert(PeerAddr(peer));
+            }
+            request
+        };
+        let client = IpAddr::from([1, 2, 3, 4]);
+
+        // When & Then
+        // Only the trusted proxies can forward the IP address of the client.
+        assert_eq!(limiter.client_ip(&request(Some(client), "5.6.7.8")), Some(client));
+        assert_eq!(limiter.client_ip(&request(Some(proxy), "5.6.7.8, 1.2.3.4")), Some(client));
+        assert_eq!(limiter.client_ip(&request(Some(proxy), "5.6.7.8, 1.2.3.4, 10.0.0.2")), Some(client));
+        assert_eq!(limiter.client_ip(&request(Some(proxy), "10.0.0.2")), Some(proxy));
+        assert_eq!(limiter.client_ip(&request(Some(proxy), "")), Some(proxy));
+        assert_eq!(limiter.client_ip(&request(None, "5.6.7.8")), None);
+    }
+
+    #[test]
+    fn test_rate_limit_is_weighted() {
+        // Given
+        let config = RateLimitConfig { ip_limit: Some(10), ..Default::default() };
+        let debug_weight = config.method_weight("debug_traceBlockByNumber");
+        let limiter = limiter(config);
+        let client = Client::Ip(IpAddr::from([127, 0, 0, 1]));
+
+        // When
+        let first = limiter.try_acquire(&client, debug_weight);
+        let second = limiter.try_acquire(&client, 1);
+
+        // Then
+        assert!(first);
+        assert!(!second);
+        assert!(limiter.try_acquire(&Client::Ip(IpAddr::from([127, 0, 0, 2])), 1));
+    }
+}
@@ -0,0 +1,165 @@
+//! RPC middleware bounding the execution time of the calls.
+
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Id, Request},
+    MethodResponse,
+};
+use pin_project_lite::pin_project;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Error code returned to the calls that timed out, same value as geth.
+pub const TIMEOUT_ERROR_CODE: i32 = -32002;
+
+/// Default timeout of the calls.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout of the tracing and debug calls, which replay whole blocks.
+pub const DEFAULT_TRACING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Configuration of the timeouts of the calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Timeout of the calls, except the tracing and debug ones.
+    pub default: Duration,
+    /// Timeouts of the methods, overriding the default ones.
+    pub methods: HashMap<String, Duration>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self { default: DEFAULT_TIMEOUT, methods: HashMap::new() }
+    }
+}
+
+impl TimeoutConfig {
+    /// Returns the timeout of the method.
+    pub fn method_timeout(&self, method: &str) -> Duration {
+        self.methods.get(method).copied().unwrap_or_else(|| {
+            if method.starts_with("debug_") || method.starts_with("trace_") {
+                DEFAULT_TRACING_TIMEOUT.max(self.default)
+            } else {
+                self.default
+            }
+        })
+    }
+}
+
+/// Parses a comma separated list of `<method>=<milliseconds>`, e.g. `eth_call=10000`.
+pub fn parse_method_timeouts(timeouts: &str) -> Result<HashMap<String, Duration>, String> {
+    timeouts
+        .split(',')
+        .map(str::trim)
+        .filter(|timeout| !timeout.is_empty())
+        .map(|timeout| {
+            let (method, millis) = timeout
+                .split_once('=')
+                .ok_or_else(|| format!("invalid method timeout {timeout}, expected <method>=<milliseconds>"))?;
+            let millis = millis.trim().parse().map_err(|_| format!("invalid timeout for method {method}"))?;
+            Ok((method.trim().to_string(), Duration::from_millis(millis)))
+        })
+        .collect()
+}
+
+/// RPC layer bounding the execution time of the calls, see [`Timeout`].
+#[derive(Debug, Clone)]
+pub struct TimeoutLayer {
+    config: Arc<TimeoutConfig>,
+}
+
+impl TimeoutLayer {
+    pub fn new(config: TimeoutConfig) -> Self {
+        Self { config: Arc::new(config) }
+    }
+}
+
+impl<S> tower::Layer<S> for TimeoutLayer {
+    type Service = Timeout<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Timeout { service, config: self.config.clone() }
+    }
+}
+
+/// RPC middleware aborting the calls running for longer than the timeout of their method and
+/// returning a JSON-RPC error instead. Aborting a call drops its pending Starknet requests.
+#[derive(Debug, Clone)]
+pub struct Timeout<S> {
+    service: S,
+    config: Arc<TimeoutConfig>,
+}
+
+impl<'a, S> RpcServiceT<'a> for Timeout<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = TimeoutFuture<'a, S::Future>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let timeout = self.config.method_timeout(request.method_name());
+        TimeoutFuture { id: request.id(), timeout, fut: tokio::time::timeout(timeout, self.service.call(request)) }
+    }
+}
+
+pin_project! {
+    /// Response future for timeouts.
+    pub struct TimeoutFuture<'a, F> {
+        #[pin]
+        fut: tokio::time::Timeout<F>,
+        id: Id<'a>,
+        timeout: Duration,
+    }
+}
+
+impl<'a, F> std::fmt::Debug for TimeoutFuture<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TimeoutFuture")
+    }
+}
+
+impl<'a, F: Future<Output = MethodResponse>> Future for TimeoutFuture<'a, F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        this.fut.poll(cx).map(|response| {
+            response.unwrap_or_else(|_| {
+                MethodResponse::error(
+                    this.id.clone(),
+                    ErrorObject::owned(
+                        TIMEOUT_ERROR_CODE,
+                        format!("request timed out after {}ms", this.timeout.as_millis()),
+                        None::<()>,
+                    ),
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_timeout() {
+        // Given
+        let config = TimeoutConfig {
+            default: Duration::from_secs(10),
+            methods: parse_method_timeouts("eth_getLogs=5000").unwrap(),
+        };
+
+        // When & Then
+        assert_eq!(config.method_timeout("eth_getLogs"), Duration::from_secs(5));
+        assert_eq!(config.method_timeout("eth_call"), Duration::from_secs(10));
+        assert_eq!(config.method_timeout("debug_traceBlockByNumber"), DEFAULT_TRACING_TIMEOUT);
+        assert!(parse_method_timeouts("eth_call=soon").is_err());
+    }
+}
@@ -8,21 +8,41 @@ pub mod rpc;
 pub mod servers;
 
 use crate::{
-    eth_rpc::middleware::{metrics::RpcMetrics, MetricsLayer},
-    prometheus_handler::init_prometheus,
+    eth_rpc::middleware::{
+        capture::CaptureLayer,
+        health::{HealthLayer, ReadinessCheck},
+        metrics::RpcMetrics,
+        rate_limit::{ApiKeyLayer, PeerAddr, RateLimitLayer, RateLimiter},
+        timeout::TimeoutLayer,
+        MetricsLayer,
+    },
+    pool::mempool::MEMPOOL_METRICS,
+    prometheus_handler::{init_prometheus, register},
+    providers::{
+        eth_provider::{cache::CACHE_METRICS, chain::INDEXER_LAG, database::DATABASE_METRICS},
+        sn_provider::STARKNET_METRICS,
+    },
 };
 use config::RPCConfig;
 use eyre::Result;
 use jsonrpsee::{
     server::{
         middleware::http::{InvalidPath, ProxyGetRequestLayer},
+        serve_with_graceful_shutdown, stop_channel, BatchRequestConfig, HttpBody, Methods, RegisterMethodError,
         RpcServiceBuilder, ServerBuilder, ServerHandle,
     },
     RpcModule,
 };
 use prometheus::Registry;
-use std::net::{AddrParseError, Ipv4Addr, SocketAddr};
+use rpc::KakarotRpcModuleBuilder;
+use starknet::providers::Provider;
+use std::{
+    net::{AddrParseError, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 use thiserror::Error;
+use tokio::net::TcpListener;
+use tower::Service;
 use tower_http::cors::{Any, CorsLayer};
 
 #[derive(Error, Debug)]
@@ -37,6 +57,8 @@ pub enum RpcError {
     PrometheusHandlerError(#[from] crate::prometheus_handler::Error),
     #[error(transparent)]
     PrometheusError(#[from] prometheus::Error),
+    #[error(transparent)]
+    RegisterMethodError(#[from] RegisterMethodError),
 }
 
 /// # Errors
@@ -45,47 +67,149 @@ pub enum RpcError {
 pub async fn run_server(
     kakarot_rpc_module: RpcModule<()>,
     rpc_config: RPCConfig,
+    readiness: Arc<dyn ReadinessCheck>,
 ) -> Result<(SocketAddr, ServerHandle), RpcError> {
-    let RPCConfig { socket_addr } = rpc_config;
-
-    let cors = CorsLayer::new().allow_methods(Any).allow_origin(Any).allow_headers(Any);
+    let (metrics, rate_limiter) = start_metrics()?;
+    let capture = start_capture()?;
+    start_server(kakarot_rpc_module, rpc_config, metrics, rate_limiter, readiness, capture).await
+}
 
-    let http_middleware =
-        tower::ServiceBuilder::new().layer(ProxyGetRequestLayer::new("/health", "net_health")?).layer(cors);
+/// Starts a server per listener, each of them serving the namespaces of its configuration.
+/// The listeners share the same prometheus metrics, rate limits, readiness checks and capture file.
+///
+/// # Errors
+///
+/// Will return `Err` if a module can't be built or if a server fails to start.
+pub async fn run_servers<SP>(
+    builder: &KakarotRpcModuleBuilder<SP>,
+    listeners: Vec<RPCConfig>,
+    readiness: Arc<dyn ReadinessCheck>,
+) -> Result<Vec<(SocketAddr, ServerHandle)>, RpcError>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    let (metrics, rate_limiter) = start_metrics()?;
+    let capture = start_capture()?;
+    let mut servers = Vec::with_capacity(listeners.len());
+    for rpc_config in listeners {
+        let kakarot_rpc_module = builder.rpc_module_for(&rpc_config.modules)?;
+        servers.push(
+            start_server(
+                kakarot_rpc_module,
+                rpc_config,
+                metrics.clone(),
+                rate_limiter.clone(),
+                readiness.clone(),
+                capture.clone(),
+            )
+            .await?,
+        );
+    }
+    Ok(servers)
+}
 
+/// Registers the metrics in a new prometheus registry, served on `PROMETHEUS_PORT`, and returns
+/// the RPC metrics middleware and the rate limiter recording the usage of the clients.
+fn start_metrics() -> Result<(Option<MetricsLayer>, Arc<RateLimiter>), RpcError> {
     // Creating the prometheus registry to register the metrics
     let registry = Registry::new();
     // register the metrics
     let metrics = RpcMetrics::new(Some(&registry))?.map(|m| MetricsLayer::new(m, "http"));
+    CACHE_METRICS.register(&registry)?;
+    DATABASE_METRICS.register(&registry)?;
+    STARKNET_METRICS.register(&registry)?;
+    MEMPOOL_METRICS.register(&registry)?;
+    register(INDEXER_LAG.clone(), &registry)?;
+    let config = crate::config::config();
+    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.clone(), &registry)?);
     tokio::spawn(async move {
         // serve the prometheus metrics on the given port so that it can be read
         let _ = init_prometheus(
-            SocketAddr::new(
-                std::net::IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
-                get_env_or_default("PROMETHEUS_PORT", "9615").parse().unwrap(),
-            ),
+            SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), config.prometheus_port),
             registry,
         )
         .await;
     });
+    Ok((metrics, rate_limiter))
+}
+
+/// Opens the capture file if the capture of the calls is enabled.
+fn start_capture() -> Result<Option<CaptureLayer>, RpcError> {
+    Ok(crate::config::config().capture.clone().map(CaptureLayer::new).transpose()?)
+}
+
+async fn start_server(
+    kakarot_rpc_module: RpcModule<()>,
+    rpc_config: RPCConfig,
+    metrics: Option<MetricsLayer>,
+    rate_limiter: Arc<RateLimiter>,
+    readiness: Arc<dyn ReadinessCheck>,
+    capture: Option<CaptureLayer>,
+) -> Result<(SocketAddr, ServerHandle), RpcError> {
+    let RPCConfig { socket_addr, .. } = rpc_config;
+    let config = crate::config::config();
+
+    let cors = CorsLayer::new().allow_methods(Any).allow_origin(Any).allow_headers(Any);
+
+    // The health endpoints are answered before the authentication, for the probes of the orchestrator.
+    let http_middleware = tower::ServiceBuilder::new()
+        .layer(HealthLayer::new(readiness))
+        .layer(ApiKeyLayer::new(rate_limiter.clone()))
+        .layer(ProxyGetRequestLayer::new("/health", "net_health")?)
+        .layer(cors);
+
     // add the metrics as a middleware to the RPC so that every new RPC call fires prometheus metrics
     // upon start, finish etc. we don't need to manually handle each method, it should automatically
     // work for any new method.
-    let rpc_middleware = RpcServiceBuilder::new().option_layer(metrics);
+    let rpc_middleware = RpcServiceBuilder::new()
+        .option_layer(capture)
+        .option_layer(metrics)
+        .layer(RateLimitLayer::new(rate_limiter))
+        .layer(TimeoutLayer::new(config.timeouts.clone()));
+
+    // A batch size of 0 disables the batch requests.
+    let batch_request_config = match config.max_batch_size {
+        0 => BatchRequestConfig::Disabled,
+        max_batch_size => BatchRequestConfig::Limit(max_batch_size),
+    };
 
-    let server = ServerBuilder::default()
-        .max_connections(get_env_or_default("RPC_MAX_CONNECTIONS", "100").parse().unwrap())
+    let service_builder = ServerBuilder::default()
+        .max_connections(config.rpc_max_connections)
+        .max_request_body_size(config.max_request_body_size)
+        .max_response_body_size(config.max_response_body_size)
+        .set_batch_request_config(batch_request_config)
         .set_http_middleware(http_middleware)
         .set_rpc_middleware(rpc_middleware)
-        .build(socket_addr.parse::<SocketAddr>()?)
-        .await?;
+        .to_service_builder();
+    let listener = TcpListener::bind(socket_addr.parse::<SocketAddr>()?).await?;
+    let addr = listener.local_addr()?;
+    let methods = Methods::from(kakarot_rpc_module);
+    let (stop_handle, handle) = stop_channel();
 
-    let addr = server.local_addr()?;
-    let handle = server.start(kakarot_rpc_module);
+    // The connections are accepted here rather than by the jsonrpsee server in order to attach
+    // their peer address to the requests, which identifies the clients without API key.
+    tokio::spawn(async move {
+        loop {
+            let (socket, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        tracing::debug!(%err, "failed to accept a connection");
+                        continue;
+                    }
+                },
+                () = stop_handle.clone().shutdown() => break,
+            };
 
-    Ok((addr, handle))
-}
+            let (service_builder, methods, stop) = (service_builder.clone(), methods.clone(), stop_handle.clone());
+            let service = tower::service_fn(move |request: hyper::Request<hyper::body::Incoming>| {
+                let mut request = request.map(HttpBody::new);
+                request.extensions_mut().insert(PeerAddr(peer.ip()));
+                service_builder.clone().build(methods.clone(), stop.clone()).call(request)
+            });
+            tokio::spawn(serve_with_graceful_shutdown(socket, service, stop_handle.clone().shutdown()));
+        }
+    });
 
-fn get_env_or_default(name: &str, default: &str) -> String {
-    std::env::var(name).unwrap_or_else(|_| default.to_string())
+    Ok((addr, handle))
 }
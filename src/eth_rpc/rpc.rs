@@ -17,7 +17,7 @@ use crate::{
 };
 use jsonrpsee::{server::RegisterMethodError, Methods, RpcModule};
 use starknet::providers::Provider;
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, fmt, marker::PhantomData, str::FromStr, sync::Arc};
 
 /// Represents RPC modules that are supported by reth
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
@@ -32,6 +32,60 @@ pub enum KakarotRpcModule {
     KakarotRpc,
 }
 
+impl KakarotRpcModule {
+    /// All the supported RPC modules.
+    pub const ALL: [Self; 8] =
+        [Self::Eth, Self::Alchemy, Self::Web3, Self::Net, Self::Debug, Self::Trace, Self::Txpool, Self::KakarotRpc];
+
+    /// Returns the namespace of the module, as used in the method names and in the
+    /// configuration of the listeners.
+    pub const fn namespace(self) -> &'static str {
+        match self {
+            Self::Eth => "eth",
+            Self::Alchemy => "alchemy",
+            Self::Web3 => "web3",
+            Self::Net => "net",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+            Self::Txpool => "txpool",
+            Self::KakarotRpc => "kakarot",
+        }
+    }
+
+    /// Parses a comma separated list of namespaces, e.g. `eth,net,web3`. The `all` keyword
+    /// selects every module.
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+        let mut modules = Vec::new();
+        for namespace in list.split(',').map(str::trim).filter(|namespace| !namespace.is_empty()) {
+            if namespace == "all" {
+                return Ok(Self::ALL.to_vec());
+            }
+            let module = namespace.parse()?;
+            if !modules.contains(&module) {
+                modules.push(module);
+            }
+        }
+        Ok(modules)
+    }
+}
+
+impl fmt::Display for KakarotRpcModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.namespace())
+    }
+}
+
+impl FromStr for KakarotRpcModule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|module| module.namespace() == s).ok_or_else(|| {
+            let namespaces = Self::ALL.map(Self::namespace).join(", ");
+            format!("unknown RPC namespace {s}, expected one of {namespaces}")
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct KakarotRpcModuleBuilder<SP> {
     modules: HashMap<KakarotRpcModule, Methods>,
@@ -54,8 +108,8 @@ where
         let web3_rpc_module = Web3Rpc::default().into_rpc();
         let net_rpc_module = NetRpc::new(eth_provider.clone()).into_rpc();
         let debug_rpc_module = DebugRpc::new(debug_provider).into_rpc();
-        let trace_rpc_module = TraceRpc::new(eth_provider).into_rpc();
-        let kakarot_rpc_module = KakarotRpc.into_rpc();
+        let trace_rpc_module = TraceRpc::new(eth_provider.clone()).into_rpc();
+        let kakarot_rpc_module = KakarotRpc::new(eth_provider).into_rpc();
         let txpool_rpc_module = TxpoolRpc::new(pool_provider).into_rpc();
 
         let mut modules = HashMap::new();
@@ -72,13 +126,36 @@ where
         Self { modules, _phantom: PhantomData }
     }
 
+    /// Returns the RPC module serving every namespace.
     pub fn rpc_module(&self) -> Result<RpcModule<()>, RegisterMethodError> {
+        self.rpc_module_for(&KakarotRpcModule::ALL)
+    }
+
+    /// Returns the RPC module serving only the given namespaces.
+    pub fn rpc_module_for(&self, modules: &[KakarotRpcModule]) -> Result<RpcModule<()>, RegisterMethodError> {
         let mut rpc_module = RpcModule::new(());
 
-        for methods in self.modules.values().cloned() {
+        for methods in modules.iter().filter_map(|module| self.modules.get(module)).cloned() {
             rpc_module.merge(methods)?;
         }
 
         Ok(rpc_module)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rpc_modules() {
+        // When
+        let modules = KakarotRpcModule::parse_list("eth, net,web3,eth").unwrap();
+
+        // Then
+        assert_eq!(modules, vec![KakarotRpcModule::Eth, KakarotRpcModule::Net, KakarotRpcModule::Web3]);
+        assert_eq!(KakarotRpcModule::parse_list("all").unwrap(), KakarotRpcModule::ALL.to_vec());
+        assert_eq!(KakarotRpcModule::parse_list("kakarot").unwrap(), vec![KakarotRpcModule::KakarotRpc]);
+        assert!(KakarotRpcModule::parse_list("eth,admin").is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    client::{EthClient, TransactionHashProvider},
+    client::{
+        EthClient, GasPriceOracleProvider, PendingBlockProvider, TransactionCountProvider, TransactionHashProvider,
+    },
     eth_rpc::api::eth_api::EthApiServer,
     providers::eth_provider::{
-        constant::MAX_PRIORITY_FEE_PER_GAS,
         database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt, transaction::ExtendedTransaction},
         error::EthApiError,
         BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
@@ -74,6 +75,9 @@ where
 
     #[tracing::instrument(skip(self), err)]
     async fn block_by_number(&self, number: BlockNumberOrTag, full: bool) -> RpcResult<Option<ExtendedBlock>> {
+        if number.is_pending() {
+            return Ok(self.eth_client.pending_block(full).await?);
+        }
         Ok(self.eth_client.eth_provider().block_by_number(number, full).await?)
     }
 
@@ -84,6 +88,10 @@ where
 
     #[tracing::instrument(skip(self), ret, err)]
     async fn block_transaction_count_by_number(&self, number: BlockNumberOrTag) -> RpcResult<Option<U256>> {
+        if number.is_pending() {
+            let block = self.eth_client.pending_block(false).await?;
+            return Ok(block.map(|block| U256::from(block.transactions.len())));
+        }
         Ok(self.eth_client.eth_provider().block_transaction_count_by_number(number).await?)
     }
 
@@ -151,7 +159,7 @@ where
 
     #[tracing::instrument(skip(self), ret, err)]
     async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> RpcResult<U256> {
-        Ok(self.eth_client.eth_provider().transaction_count(address, block_id).await?)
+        Ok(self.eth_client.transaction_count(address, block_id).await?)
     }
 
     #[tracing::instrument(skip(self), err)]
@@ -191,7 +199,7 @@ where
 
     #[tracing::instrument(skip_all, ret, err)]
     async fn gas_price(&self) -> RpcResult<U256> {
-        Ok(self.eth_client.eth_provider().gas_price().await?)
+        Ok(self.eth_client.suggested_gas_price().await?)
     }
 
     #[tracing::instrument(skip(self), ret, err)]
@@ -207,7 +215,7 @@ where
 
     #[tracing::instrument(skip_all, ret, err)]
     async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        Ok(U256::from(*MAX_PRIORITY_FEE_PER_GAS))
+        Ok(self.eth_client.suggested_priority_fee().await?)
     }
 
     async fn blob_base_fee(&self) -> RpcResult<U256> {
@@ -247,11 +255,12 @@ where
 
         #[cfg(feature = "forwarding")]
         {
-            use crate::providers::eth_provider::{constant::forwarding::MAIN_RPC_URL, error::TransactionError};
+            use crate::{config::config, providers::eth_provider::error::TransactionError};
             use alloy_provider::{Provider as _, ProviderBuilder};
-            use url::Url;
 
-            let provider = ProviderBuilder::new().on_http(Url::parse(MAIN_RPC_URL.as_ref()).unwrap());
+            // Required by the configuration with the forwarding feature.
+            let main_rpc_url = config().main_rpc_url.clone().ok_or(EthApiError::Unsupported("MAIN_RPC_URL unset"))?;
+            let provider = ProviderBuilder::new().on_http(main_rpc_url);
             let tx_hash = provider
                 .send_raw_transaction(&bytes)
                 .await
@@ -1,26 +1,64 @@
 use crate::{
-    config::KakarotRpcConfig,
+    config::config,
     eth_rpc::api::kakarot_api::KakarotApiServer,
     providers::eth_provider::{
-        constant::{Constant, MAX_LOGS},
-        starknet::kakarot_core::{get_white_listed_eip_155_transaction_hashes, MAX_FELTS_IN_CALLDATA},
+        constant::Constant,
+        error::{EthApiError, SignatureError},
+        provider::EthDataProvider,
+        starknet::{kakarot_core::get_white_listed_eip_155_transaction_hashes, simulation::StarknetResources},
+        LogProvider, LogsCursor, LogsPage,
     },
 };
+use alloy_primitives::Bytes;
+use alloy_rlp::Decodable;
+use alloy_rpc_types::Filter;
 use jsonrpsee::core::{async_trait, RpcResult};
+use reth_primitives::TransactionSigned;
+use starknet::providers::Provider;
 
 #[derive(Debug)]
-pub struct KakarotRpc;
+pub struct KakarotRpc<SP: Provider + Send + Sync> {
+    eth_provider: EthDataProvider<SP>,
+}
+
+impl<SP> KakarotRpc<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    pub const fn new(eth_provider: EthDataProvider<SP>) -> Self {
+        Self { eth_provider }
+    }
+}
 
 #[async_trait]
-impl KakarotApiServer for KakarotRpc {
+impl<SP> KakarotApiServer for KakarotRpc<SP>
+where
+    SP: Provider + Send + Sync + 'static,
+{
     async fn get_config(&self) -> RpcResult<Constant> {
-        let starknet_config = KakarotRpcConfig::from_env().expect("Failed to load Kakarot RPC config");
+        let config = config();
         Ok(Constant {
-            max_logs: *MAX_LOGS,
-            starknet_network: String::from(starknet_config.network_url),
-            max_felts_in_calldata: *MAX_FELTS_IN_CALLDATA,
+            max_logs: config.max_logs,
+            max_logs_block_range: config.max_logs_block_range,
+            starknet_network: config.starknet.network_url.to_string(),
+            max_felts_in_calldata: config.max_felts_in_calldata,
             white_listed_eip_155_transaction_hashes: get_white_listed_eip_155_transaction_hashes(),
-            kakarot_address: starknet_config.kakarot_address,
+            kakarot_address: config.starknet.kakarot_address,
         })
     }
+
+    #[tracing::instrument(skip_all, err)]
+    async fn estimate_resources(&self, bytes: Bytes) -> RpcResult<StarknetResources> {
+        let transaction = TransactionSigned::decode(&mut bytes.as_ref()).map_err(EthApiError::from)?;
+        if transaction.recover_signer().is_none() {
+            return Err(EthApiError::from(SignatureError::Recovery).into());
+        }
+
+        Ok(self.eth_provider.simulate_transaction(&transaction).await?)
+    }
+
+    #[tracing::instrument(skip_all, err)]
+    async fn get_logs_paged(&self, filter: Filter, cursor: Option<LogsCursor>) -> RpcResult<LogsPage> {
+        Ok(self.eth_provider.get_logs_paged(filter, cursor).await?)
+    }
 }
@@ -2,6 +2,8 @@
 use opentelemetry as _;
 use opentelemetry_otlp as _;
 use opentelemetry_sdk as _;
+#[cfg(feature = "binaries")]
+use reqwest as _;
 use tracing_opentelemetry as _;
 use tracing_subscriber as _;
 
@@ -19,6 +21,8 @@ pub mod eth_rpc;
 pub mod models;
 pub mod pool;
 pub mod prometheus_handler;
+pub mod rpc_diff;
+pub mod shutdown;
 #[cfg(feature = "testing")]
 pub mod test_utils;
 pub mod tracing;
@@ -1,26 +1,33 @@
 use dotenvy::dotenv;
 use eyre::Result;
+use itertools::Itertools;
 use kakarot_rpc::{
     client::EthClient,
-    constants::{KAKAROT_RPC_CONFIG, KKRT_BLOCK_GAS_LIMIT, RPC_CONFIG},
-    eth_rpc::{rpc::KakarotRpcModuleBuilder, run_server},
+    config::{Config, DatabaseConfig},
+    constants::KKRT_BLOCK_GAS_LIMIT,
+    eth_rpc::{
+        middleware::health::{CachedReadiness, HealthChecker},
+        rpc::KakarotRpcModuleBuilder,
+        run_servers,
+    },
     pool::{
         constants::PRUNE_DURATION,
         mempool::{maintain_transaction_pool, AccountManager},
     },
     providers::eth_provider::{
-        database::Database,
-        starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
+        database::{
+            ethereum::EthereumStore, memory::InMemoryDatabase, migrations::migrations, postgres::PostgresDatabase,
+            Database,
+        },
+        starknet::kakarot_core::core::KakarotCoreReader,
     },
+    shutdown::{wait_for_signal, ShutdownSignal},
 };
 use mongodb::options::{DatabaseOptions, ReadConcern, WriteConcern};
 use opentelemetry_sdk::runtime::Tokio;
 use reth_transaction_pool::PoolConfig;
-use starknet::{
-    core::types::{BlockId, BlockTag, Felt},
-    providers::{jsonrpc::HttpTransport, JsonRpcClient},
-};
-use std::{env::var, str::FromStr, sync::Arc};
+use starknet::core::types::{BlockId, BlockTag};
+use std::{env::var, sync::Arc};
 use tracing_opentelemetry::MetricsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
@@ -29,56 +36,143 @@ async fn main() -> Result<()> {
     // Environment variables are safe to use after this
     dotenv().ok();
 
+    // Load the configuration from the flags, the config file and the environment, reporting
+    // all the missing and invalid values at once
+    let config = Config::load(std::env::args_os())?.install();
+
     setup_tracing().expect("failed to start tracing and metrics");
 
-    let starknet_provider = JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone()));
+    // Spread the Starknet calls over the main and the fallback networks
+    let starknet_provider = config.starknet.starknet_provider();
 
     // Setup the database
-    let db_client =
-        mongodb::Client::with_uri_str(var("MONGO_CONNECTION_STRING").expect("Missing MONGO_CONNECTION_STRING .env"))
-            .await?;
-    let db = Database::new(
-        db_client.database_with_options(
-            &var("MONGO_DATABASE_NAME").expect("Missing MONGO_DATABASE_NAME from .env"),
-            DatabaseOptions::builder()
-                .read_concern(ReadConcern::majority())
-                .write_concern(WriteConcern::majority())
-                .build(),
-        ),
-    );
+    let db = setup_database(&config.database).await?;
 
     // Setup the eth provider
     let starknet_provider = Arc::new(starknet_provider);
 
     // Get the pool config
-    let contract_reader = KakarotCoreReader::new(*KAKAROT_ADDRESS, starknet_provider.clone());
+    let contract_reader = KakarotCoreReader::new(config.starknet.kakarot_address, starknet_provider.clone());
     let base_fee = contract_reader.get_base_fee().block_id(BlockId::Tag(BlockTag::Pending)).call().await?.base_fee;
     let base_fee = base_fee.try_into()?;
-    let config =
+    let pool_config =
         PoolConfig { minimal_protocol_basefee: base_fee, gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() };
 
     // Init the Ethereum Client
-    let eth_client = EthClient::new(starknet_provider, config, db.clone());
+    let eth_client = EthClient::new(starknet_provider, pool_config, db);
     let eth_client = Arc::new(eth_client);
 
+    // Stops the background tasks at shutdown
+    let shutdown = ShutdownSignal::new();
+
     // Start the relayer manager
-    let addresses =
-        var("RELAYERS_ADDRESSES")?.split(',').filter_map(|addr| Felt::from_str(addr).ok()).collect::<Vec<_>>();
-    AccountManager::new(addresses, Arc::clone(&eth_client)).start();
+    let account_manager =
+        AccountManager::new(config.relayers_addresses.clone(), Arc::clone(&eth_client)).start(shutdown.subscribe());
 
-    // Start the maintenance of the mempool
-    maintain_transaction_pool(Arc::clone(&eth_client), PRUNE_DURATION);
+    // Check the dependencies for the readiness endpoint
+    let readiness = Arc::new(CachedReadiness::new(
+        HealthChecker::new(Arc::clone(&eth_client), Some(Arc::clone(&account_manager)), config.health),
+        config.health.report_ttl,
+    ));
 
-    // Setup the RPC module
-    let kakarot_rpc_module = KakarotRpcModuleBuilder::new(eth_client).rpc_module()?;
+    // Start the maintenance of the mempool
+    let maintenance = maintain_transaction_pool(Arc::clone(&eth_client), PRUNE_DURATION, shutdown.subscribe());
+
+    // Setup the RPC modules
+    let kakarot_rpc_module_builder = KakarotRpcModuleBuilder::new(Arc::clone(&eth_client));
+
+    // Start a RPC server per listener
+    let listeners = config.rpc_listeners.clone();
+    let apis = listeners.iter().map(|listener| listener.modules.iter().join(",")).collect::<Vec<_>>();
+    let servers = run_servers(&kakarot_rpc_module_builder, listeners, readiness).await?;
+    for ((socket_addr, _), api) in servers.iter().zip(apis) {
+        let url = format!("http://{socket_addr}");
+        tracing::info!(%api, "RPC Server running on {url}...");
+    }
+
+    wait_for_signal().await?;
+
+    // Reject the new transactions and keep relaying the ones of the pool up to the shutdown
+    // timeout, while the servers keep answering the other calls until the relays are stored.
+    // The readiness endpoint answers 503 from now on, so that the traffic is routed elsewhere
+    tracing::info!("shutting down, no longer accepting transactions");
+    eth_client.stop_accepting_transactions();
+    shutdown.trigger();
+    if !account_manager.wait_for_relays(config.shutdown_timeout).await {
+        tracing::warn!(
+            timeout = ?config.shutdown_timeout,
+            left = eth_client.mempool().pool_size().total,
+            "timed out relaying the transactions of the mempool"
+        );
+    }
+
+    for (_, server_handle) in &servers {
+        // The server can only be already stopped, which is what we want
+        let _ = server_handle.stop();
+    }
+    futures::future::join_all(servers.into_iter().map(|(_, server_handle)| server_handle.stopped())).await;
+    maintenance.await?;
+    tracing::info!("shut down");
 
-    // Start the RPC server
-    let (socket_addr, server_handle) = run_server(kakarot_rpc_module, RPC_CONFIG.clone()).await?;
-    let url = format!("http://{socket_addr}");
+    Ok(())
+}
 
-    tracing::info!("RPC Server running on {url}...");
+/// Sets up the storage of the indexed Ethereum data. The in-memory storage starts empty and
+/// isn't persisted.
+async fn setup_database(config: &DatabaseConfig) -> Result<Arc<dyn EthereumStore>> {
+    match config {
+        DatabaseConfig::Mongo { connection_string, database_name, run_migrations } => {
+            let db_client = mongodb::Client::with_uri_str(connection_string).await?;
+            let db = Database::new(
+                db_client.database_with_options(
+                    database_name,
+                    DatabaseOptions::builder()
+                        .read_concern(ReadConcern::majority())
+                        .write_concern(WriteConcern::majority())
+                        .build(),
+                ),
+            );
+
+            // Check the database schema and indexes
+            check_database(&db, *run_migrations).await?;
+
+            Ok(Arc::new(db))
+        }
+        DatabaseConfig::Memory => {
+            tracing::warn!("using the in-memory database, the indexed data isn't persisted");
+            Ok(Arc::new(InMemoryDatabase::new()))
+        }
+        DatabaseConfig::Postgres { connection_string, run_migrations } => {
+            let db = PostgresDatabase::connect(connection_string).await?;
+            if *run_migrations {
+                db.migrate().await?;
+            }
+
+            Ok(Arc::new(db))
+        }
+    }
+}
 
-    server_handle.stopped().await;
+/// Applies the pending database migrations if `MONGO_RUN_MIGRATIONS` is set to true, reports
+/// them otherwise, and reports the indexes missing from the database.
+async fn check_database(db: &Database, run_migrations: bool) -> Result<()> {
+    let migrations = migrations();
+    if run_migrations {
+        let applied = db.migrate(&migrations).await?;
+        tracing::info!(?applied, "applied database migrations");
+    } else {
+        for migration in db.pending_migrations(&migrations).await? {
+            tracing::warn!(
+                version = migration.version(),
+                description = migration.description(),
+                "pending database migration, set MONGO_RUN_MIGRATIONS=true to apply it"
+            );
+        }
+    }
+
+    for index in db.missing_indexes().await? {
+        tracing::warn!(%index, "missing database index, queries filtering on it will scan the collection");
+    }
 
     Ok(())
 }
@@ -1,17 +1,15 @@
-use crate::providers::eth_provider::{
-    provider::EthApiResult,
-    starknet::kakarot_core::{ETH_SEND_TRANSACTION, KAKAROT_ADDRESS},
-    utils::split_u256,
+#[cfg(not(feature = "hive"))]
+use crate::providers::eth_provider::error::EthApiError;
+use crate::{
+    config::config,
+    providers::eth_provider::{
+        provider::EthApiResult, starknet::kakarot_core::ETH_SEND_TRANSACTION, utils::split_u256,
+    },
 };
 use alloy_consensus::transaction::Transaction as _;
 use alloy_rlp::Encodable;
 use reth_primitives::{transaction::legacy_parity, Transaction, TransactionSigned};
 use starknet::core::types::Felt;
-#[cfg(not(feature = "hive"))]
-use {
-    crate::providers::eth_provider::error::EthApiError,
-    crate::providers::eth_provider::starknet::kakarot_core::MAX_FELTS_IN_CALLDATA,
-};
 
 /// Returns the transaction's signature as a [`Vec<Felt>`].
 /// Fields r and s are split into two 16-bytes chunks both converted
@@ -60,11 +58,12 @@ pub fn transaction_data_to_starknet_calldata(
 
     // Check if call data is too large
     #[cfg(not(feature = "hive"))]
-    if capacity > *MAX_FELTS_IN_CALLDATA {
-        return Err(EthApiError::CalldataExceededLimit(*MAX_FELTS_IN_CALLDATA, capacity));
+    if capacity > config().max_felts_in_calldata {
+        return Err(EthApiError::CalldataExceededLimit(config().max_felts_in_calldata, capacity));
     }
 
     let mut execute_from_outside_calldata = Vec::with_capacity(capacity);
+    let kakarot_address = config().starknet.kakarot_address;
 
     // Construct the execute from outside calldata
     // https://github.com/kkrt-labs/kakarot/blob/main/src/kakarot/accounts/account_contract.cairo#L73
@@ -74,7 +73,7 @@ pub fn transaction_data_to_starknet_calldata(
         Felt::ZERO,               // OutsideExecution execute_after
         Felt::from(u32::MAX),     // OutsideExecution execute_before
         Felt::ONE,                // call_array_len
-        *KAKAROT_ADDRESS,         // CallArray to
+        kakarot_address,          // CallArray to
         *ETH_SEND_TRANSACTION,    // CallArray selector
         Felt::ZERO,               // CallArray data_offset
         signed_data.len().into(), // CallArray data_len
@@ -128,7 +127,7 @@ mod tests {
                 Felt::ZERO,                                                   // OutsideExecution execute_after
                 Felt::from(u32::MAX),                                         // OutsideExecution execute_before
                 Felt::ONE,                                                    // call_array_len
-                *KAKAROT_ADDRESS,                                             // CallArray to
+                config().starknet.kakarot_address,                            // CallArray to
                 *ETH_SEND_TRANSACTION,                                        // CallArray selector
                 Felt::ZERO,                                                   // CallArray data_offset
                 Felt::from((transaction.transaction.length() + 30) / 31 + 1), // CallArray data_len
@@ -0,0 +1,264 @@
+use super::mempool::KakarotPool;
+use crate::providers::eth_provider::{
+    database::ethereum::{EthereumBlockStore, EthereumReceiptStore},
+    error::TransactionError,
+    provider::{EthApiResult, EthDataProvider},
+    BlockProvider, GasProvider,
+};
+use eyre::ensure;
+use reth_transaction_pool::TransactionPool;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, PoisonError},
+};
+
+/// Default number of blocks sampled by the gas price oracle.
+pub const DEFAULT_GAS_PRICE_ORACLE_BLOCKS: u64 = 20;
+/// Default percentile of the sampled priority fees suggested by the gas price oracle.
+pub const DEFAULT_GAS_PRICE_ORACLE_PERCENTILE: u32 = 60;
+/// Default maximum priority fee per gas suggested by the gas price oracle (500 gwei).
+pub const DEFAULT_GAS_PRICE_ORACLE_MAX_PRIORITY_FEE: u128 = 500_000_000_000;
+
+/// Configuration of the [`GasPriceOracle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasPriceOracleConfig {
+    /// Number of recent blocks sampled.
+    pub blocks: u64,
+    /// Percentile of the sampled priority fees that is suggested, between 0 and 100.
+    pub percentile: u32,
+    /// Floor of the suggested priority fee per gas.
+    pub min_priority_fee: u128,
+    /// Ceiling of the suggested priority fee per gas.
+    pub max_priority_fee: u128,
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            blocks: DEFAULT_GAS_PRICE_ORACLE_BLOCKS,
+            percentile: DEFAULT_GAS_PRICE_ORACLE_PERCENTILE,
+            min_priority_fee: 0,
+            max_priority_fee: DEFAULT_GAS_PRICE_ORACLE_MAX_PRIORITY_FEE,
+        }
+    }
+}
+
+impl GasPriceOracleConfig {
+    /// Checks that the configuration is consistent.
+    pub fn validate(&self) -> eyre::Result<()> {
+        ensure!(self.blocks > 0, "gas price oracle block window must be positive");
+        ensure!(
+            self.percentile <= 100,
+            "gas price oracle percentile must be between 0 and 100, got {}",
+            self.percentile
+        );
+        ensure!(
+            self.min_priority_fee <= self.max_priority_fee,
+            "gas price oracle floor ({}) is above its ceiling ({})",
+            self.min_priority_fee,
+            self.max_priority_fee
+        );
+        Ok(())
+    }
+}
+
+/// A gas price suggestion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasPriceSuggestion {
+    /// The base fee of the next block.
+    pub base_fee: u128,
+    /// The suggested priority fee per gas.
+    pub priority_fee: u128,
+}
+
+impl GasPriceSuggestion {
+    /// Returns the suggested gas price, i.e. the base fee plus the suggested priority fee.
+    pub const fn gas_price(&self) -> u128 {
+        self.base_fee.saturating_add(self.priority_fee)
+    }
+}
+
+/// Gas price oracle suggesting priority fees based on the effective priority fees paid
+/// in the recent blocks and on the priority fees of the pending transactions of the pool.
+///
+/// The priority fees paid in the recent blocks are cached, only the blocks indexed since the
+/// last suggestion are loaded. The pool is sampled on each call, as it changes within a block.
+#[derive(Debug)]
+pub struct GasPriceOracle {
+    config: GasPriceOracleConfig,
+    /// The sample of the recent blocks, along with the block number it was taken at.
+    cache: Mutex<Option<BlocksSample>>,
+}
+
+/// The priority fees paid in the recent blocks.
+#[derive(Debug, Clone)]
+struct BlocksSample {
+    /// The block number the sample was taken at.
+    block_number: u64,
+    /// The base fee of the next block.
+    base_fee: u128,
+    /// The effective priority fees paid in each sampled block.
+    priority_fees: BTreeMap<u64, Vec<u128>>,
+}
+
+impl GasPriceOracle {
+    pub fn new(config: GasPriceOracleConfig) -> Self {
+        Self { config, cache: Mutex::new(None) }
+    }
+
+    /// Returns the gas price suggestion for the next block.
+    pub async fn suggest<SP>(
+        &self,
+        eth_provider: &EthDataProvider<SP>,
+        pool: &KakarotPool<EthDataProvider<SP>>,
+    ) -> EthApiResult<GasPriceSuggestion>
+    where
+        SP: starknet::providers::Provider + Send + Sync,
+    {
+        let block_number = eth_provider.block_number().await?.to::<u64>();
+        let cached = self.cache.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let sample = match cached {
+            Some(sample) if sample.block_number == block_number => sample,
+            cached => self.sample_blocks(eth_provider, block_number, cached).await?,
+        };
+        let mut priority_fees = sample.priority_fees.values().flatten().copied().collect::<Vec<_>>();
+
+        // Account for the pressure of the pool: the pending transactions compete
+        // for the inclusion in the next block.
+        let pool_base_fee = u64::try_from(sample.base_fee).unwrap_or(u64::MAX);
+        priority_fees
+            .extend(pool.pending_transactions().iter().filter_map(|tx| tx.effective_tip_per_gas(pool_base_fee)));
+
+        let priority_fee = suggest_priority_fee(priority_fees, &self.config);
+        Ok(GasPriceSuggestion { base_fee: sample.base_fee, priority_fee })
+    }
+
+    /// Samples the effective priority fees paid in the recent blocks, reusing the blocks of the
+    /// previous sample, and caches the sample.
+    ///
+    /// The lock is only held to read and update the cache, never across the calls to the
+    /// database and Starknet: concurrent requests on a stale cache compute the same sample.
+    async fn sample_blocks<SP>(
+        &self,
+        eth_provider: &EthDataProvider<SP>,
+        block_number: u64,
+        previous: Option<BlocksSample>,
+    ) -> EthApiResult<BlocksSample>
+    where
+        SP: starknet::providers::Provider + Send + Sync,
+    {
+        let base_fee: u128 = eth_provider.gas_price().await?.try_into().map_err(|_| TransactionError::GasOverflow)?;
+
+        let start_block = block_number.saturating_sub(self.config.blocks - 1);
+        // The head of the previous sample is loaded again, as it may have been sampled while pending.
+        let mut priority_fees = previous
+            .map(|mut sample| {
+                sample.priority_fees.remove(&sample.block_number);
+                sample.priority_fees
+            })
+            .unwrap_or_default();
+        priority_fees.retain(|number, _| (start_block..=block_number).contains(number));
+
+        // Only load the blocks from the first one missing from the previous sample.
+        if let Some(first_missing) = (start_block..=block_number).find(|number| !priority_fees.contains_key(number)) {
+            let headers = eth_provider.database().headers(first_missing, block_number).await?;
+            let base_fees = headers
+                .iter()
+                .map(|header| (header.number, u128::from(header.base_fee_per_gas.unwrap_or_default())))
+                .collect::<HashMap<_, _>>();
+            for number in base_fees.keys() {
+                priority_fees.insert(*number, Vec::new());
+            }
+
+            let receipts = eth_provider.database().receipts_by_block_range(first_missing, block_number).await?;
+            for receipt in receipts {
+                let Some(number) = receipt.block_number else { continue };
+                if let (Some(base_fee), Some(fees)) = (base_fees.get(&number), priority_fees.get_mut(&number)) {
+                    fees.push(receipt.effective_gas_price.saturating_sub(*base_fee));
+                }
+            }
+        }
+
+        let sample = BlocksSample { block_number, base_fee, priority_fees };
+
+        // Don't replace a sample taken meanwhile at a more recent block.
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if cache.as_ref().map_or(true, |cached| cached.block_number <= block_number) {
+            *cache = Some(sample.clone());
+        }
+
+        Ok(sample)
+    }
+}
+
+/// Returns the configured percentile of the sampled priority fees, bounded by the
+/// configured floor and ceiling. Returns the floor if there is no sample.
+fn suggest_priority_fee(mut priority_fees: Vec<u128>, config: &GasPriceOracleConfig) -> u128 {
+    if priority_fees.is_empty() {
+        return config.min_priority_fee;
+    }
+
+    priority_fees.sort_unstable();
+    let index = (priority_fees.len() - 1) * config.percentile as usize / 100;
+
+    priority_fees[index].clamp(config.min_priority_fee, config.max_priority_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_priority_fee_no_sample() {
+        // Given
+        let config = GasPriceOracleConfig { min_priority_fee: 7, ..Default::default() };
+
+        // When
+        let priority_fee = suggest_priority_fee(vec![], &config);
+
+        // Then
+        assert_eq!(priority_fee, 7);
+    }
+
+    #[test]
+    fn test_suggest_priority_fee_percentile() {
+        // Given
+        let config = GasPriceOracleConfig { percentile: 60, ..Default::default() };
+        let priority_fees = vec![50, 10, 40, 20, 30, 0];
+
+        // When
+        let priority_fee = suggest_priority_fee(priority_fees, &config);
+
+        // Then
+        // Sorted priority fees: [0, 10, 20, 30, 40, 50], index = 5 * 60 / 100 = 3.
+        assert_eq!(priority_fee, 30);
+    }
+
+    #[test]
+    fn test_suggest_priority_fee_bounded() {
+        // Given
+        let config = GasPriceOracleConfig { percentile: 100, min_priority_fee: 15, max_priority_fee: 25, blocks: 1 };
+
+        // When
+        let ceiled = suggest_priority_fee(vec![10, 100], &config);
+        let floored = suggest_priority_fee(vec![1, 10], &config);
+
+        // Then
+        assert_eq!(ceiled, 25);
+        assert_eq!(floored, 15);
+    }
+
+    #[test]
+    fn test_gas_price_oracle_config_validate() {
+        // Given
+        let invalid_percentile = GasPriceOracleConfig { percentile: 101, ..Default::default() };
+        let invalid_bounds = GasPriceOracleConfig { min_priority_fee: 2, max_priority_fee: 1, ..Default::default() };
+        let invalid_blocks = GasPriceOracleConfig { blocks: 0, ..Default::default() };
+
+        // When & Then
+        assert!(GasPriceOracleConfig::default().validate().is_ok());
+        assert!(invalid_percentile.validate().is_err());
+        assert!(invalid_bounds.validate().is_err());
+        assert!(invalid_blocks.validate().is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use alloy_primitives::{Address, B256};
+use reth_transaction_pool::{EthPooledTransaction, ValidPoolTransaction};
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+/// A transaction of the pool.
+pub type PooledTransaction = Arc<ValidPoolTransaction<EthPooledTransaction>>;
+
+/// The transactions removed from the pool to be relayed to Starknet, until they are indexed.
+///
+/// Relayed transactions are neither in the pool nor in the database until the indexer picks
+/// them up, they are tracked here so that the pending state accounts for them.
+#[derive(Debug, Default)]
+pub struct InFlightTransactions(RwLock<HashMap<B256, (PooledTransaction, Instant)>>);
+
+impl InFlightTransactions {
+    /// Returns an empty set of in flight transactions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the transaction picked up by a relayer.
+    pub fn insert(&self, transaction: PooledTransaction) {
+        let mut transactions = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        transactions.insert(*transaction.hash(), (transaction, Instant::now()));
+    }
+
+    /// Removes the transaction, once indexed or back in the pool.
+    pub fn remove(&self, hash: &B256) {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).remove(hash);
+    }
+
+    /// Removes the transactions in flight for longer than the given duration, which were
+    /// dropped by Starknet or indexed without being noticed.
+    pub fn prune(&self, older_than: Duration) {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).retain(|_, (_, since)| since.elapsed() <= older_than);
+    }
+
+    /// Returns the transactions in flight, ordered by sender and nonce.
+    pub fn transactions(&self) -> Vec<PooledTransaction> {
+        let mut transactions = self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|(transaction, _)| transaction.clone())
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|transaction| (transaction.sender(), transaction.nonce()));
+        transactions
+    }
+
+    /// Returns the nonces of the transactions in flight sent by the address.
+    pub fn nonces(&self, sender: Address) -> Vec<u64> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .filter(|(transaction, _)| transaction.sender() == sender)
+            .map(|(transaction, _)| transaction.nonce())
+            .collect()
+    }
+
+    /// Returns the number of transactions in flight.
+    pub fn len(&self) -> usize {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Returns true if no transaction is in flight.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
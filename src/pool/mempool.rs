@@ -3,10 +3,16 @@
 use super::validate::KakarotTransactionValidator;
 use crate::{
     client::EthClient,
-    constants::{KAKAROT_RPC_CONFIG, KKRT_BLOCK_GAS_LIMIT},
+    constants::KKRT_BLOCK_GAS_LIMIT,
     into_via_try_wrapper,
     pool::constants::ONE_TENTH_ETH,
-    providers::eth_provider::{database::state::EthDatabase, starknet::relayer::Relayer, BlockProvider},
+    prometheus_handler::{register, CounterVec, GaugeVec, Opts, PrometheusError, Registry, F64, I64, U64},
+    providers::eth_provider::{
+        database::{ethereum::EthereumTransactionStore, state::EthDatabase},
+        starknet::relayer::Relayer,
+        BlockProvider,
+    },
+    shutdown::Shutdown,
 };
 use alloy_eips::BlockNumberOrTag;
 use alloy_primitives::{Address, U256};
@@ -18,12 +24,13 @@ use reth_transaction_pool::{
     blobstore::NoopBlobStore, BlockInfo, CanonicalStateUpdate, CoinbaseTipOrdering, EthPooledTransaction, Pool,
     TransactionOrigin, TransactionPool, TransactionPoolExt,
 };
-use starknet::{
-    core::types::{BlockTag, Felt},
-    providers::{jsonrpc::HttpTransport, JsonRpcClient},
+use starknet::core::types::{BlockTag, Felt};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+    time::Duration,
 };
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use tokio::time::Instant;
+use tokio::{sync::watch, task::JoinHandle, time::Instant};
 use tracing::instrument;
 
 /// A type alias for the Kakarot Transaction Validator.
@@ -37,6 +44,78 @@ pub type TransactionOrdering = CoinbaseTipOrdering<EthPooledTransaction>;
 /// A type alias for the Kakarot Sequencer Mempool.
 pub type KakarotPool<Client> = Pool<Validator<Client>, TransactionOrdering, NoopBlobStore>;
 
+/// Time during which a balance of a relayer is reused by [`AccountManager::funded_relayers`].
+const RELAYER_BALANCE_TTL: Duration = Duration::from_secs(30);
+
+/// The metrics of the mempool and of the relayers.
+pub static MEMPOOL_METRICS: LazyLock<MempoolMetrics> =
+    LazyLock::new(|| MempoolMetrics::new().expect("failed to create the mempool metrics"));
+
+/// Metrics of the mempool and of the relayers.
+#[derive(Debug, Clone)]
+pub struct MempoolMetrics {
+    /// Number of transactions pending or queued in the mempool, or in flight.
+    transactions: GaugeVec<I64>,
+    /// Number of transactions relayed, labelled by outcome.
+    relayed_transactions: CounterVec<U64>,
+    /// Balance of the relayers in ether.
+    relayer_balances: GaugeVec<F64>,
+}
+
+impl MempoolMetrics {
+    fn new() -> Result<Self, PrometheusError> {
+        Ok(Self {
+            transactions: GaugeVec::new(
+                Opts::new(
+                    "mempool_transactions",
+                    "Number of transactions pending or queued in the mempool, or in flight",
+                ),
+                &["state"],
+            )?,
+            relayed_transactions: CounterVec::new(
+                Opts::new("mempool_relayed_transactions", "Number of transactions relayed to Starknet, by outcome"),
+                &["outcome"],
+            )?,
+            relayer_balances: GaugeVec::new(
+                Opts::new("mempool_relayer_balance", "Balance of the relayers in ether"),
+                &["relayer"],
+            )?,
+        })
+    }
+
+    /// Registers the metrics in the given registry.
+    pub fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+        register(self.transactions.clone(), registry)?;
+        register(self.relayed_transactions.clone(), registry)?;
+        register(self.relayer_balances.clone(), registry)?;
+        Ok(())
+    }
+
+    /// Records the number of transactions of the mempool and in flight.
+    fn record_transactions<SP>(&self, eth_client: &EthClient<SP>)
+    where
+        SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+    {
+        let size = eth_client.mempool().pool_size();
+        for (state, count) in
+            [("pending", size.pending), ("queued", size.queued), ("in_flight", eth_client.in_flight().len())]
+        {
+            self.transactions.with_label_values(&[state]).set(i64::try_from(count).unwrap_or(i64::MAX));
+        }
+    }
+
+    /// Records the outcome of a relayed transaction: `success`, `failure` or `no_relayer`.
+    fn record_relay(&self, outcome: &str) {
+        self.relayed_transactions.with_label_values(&[outcome]).inc();
+    }
+
+    /// Records the balance of the relayer.
+    fn record_balance(&self, relayer: Felt, balance: U256) {
+        let balance = u128::try_from(balance).map_or(f64::MAX, |balance| balance as f64) / 1e18;
+        self.relayer_balances.with_label_values(&[&format!("{relayer:#x}")]).set(balance);
+    }
+}
+
 /// Manages a collection of accounts addresses, interfacing with an Ethereum client.
 ///
 /// This struct provides functionality to initialize account data from a file, monitor account balances,
@@ -47,20 +126,75 @@ pub struct AccountManager<SP: starknet::providers::Provider + Send + Sync + Clon
     accounts: Vec<Felt>,
     /// The Ethereum client used to interact with the blockchain.
     eth_client: Arc<EthClient<SP>>,
+    /// The relays in progress, waited for at shutdown.
+    relays: RelayTracker,
+    /// The latest balance of each account, along with the time it was fetched.
+    balances: Mutex<HashMap<Felt, (U256, Instant)>>,
+}
+
+/// Counts the relays in progress.
+#[derive(Debug, Clone)]
+struct RelayTracker(Arc<watch::Sender<usize>>);
+
+impl RelayTracker {
+    fn new() -> Self {
+        Self(Arc::new(watch::channel(0).0))
+    }
+
+    /// Tracks a relay in progress until the returned guard is dropped.
+    fn track(&self) -> RelayGuard {
+        RelayGuard::new(&self.0)
+    }
+
+    /// Returns the number of relays in progress.
+    fn in_progress(&self) -> usize {
+        *self.0.borrow()
+    }
+
+    /// Waits for the relays in progress to complete, up to the timeout. Returns false if some
+    /// relays didn't complete in time.
+    async fn wait(&self, timeout: Duration) -> bool {
+        let mut relays = self.0.subscribe();
+        tokio::time::timeout(timeout, relays.wait_for(|relays| *relays == 0)).await.is_ok()
+    }
+}
+
+/// Tracks a relay in progress until dropped.
+#[derive(Debug)]
+struct RelayGuard(Arc<watch::Sender<usize>>);
+
+impl RelayGuard {
+    fn new(relays: &Arc<watch::Sender<usize>>) -> Self {
+        relays.send_modify(|relays| *relays += 1);
+        Self(Arc::clone(relays))
+    }
+}
+
+impl Drop for RelayGuard {
+    fn drop(&mut self) {
+        self.0.send_modify(|relays| *relays -= 1);
+    }
 }
 
 impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountManager<SP> {
     /// Initialize the account manager with a set of passed accounts.
-    pub const fn new(accounts: Vec<Felt>, eth_client: Arc<EthClient<SP>>) -> Self {
-        Self { accounts, eth_client }
+    pub fn new(accounts: Vec<Felt>, eth_client: Arc<EthClient<SP>>) -> Self {
+        Self { accounts, eth_client, relays: RelayTracker::new(), balances: Mutex::default() }
     }
 
     /// Starts the account manager task that periodically checks account balances and processes transactions.
+    /// After the shutdown, the task keeps relaying the transactions of the pool until it is drained, which
+    /// is waited for with [`AccountManager::wait_for_relays`]. Returns the manager shared with the task.
     #[instrument(skip_all, name = "mempool")]
-    pub fn start(self) {
+    pub fn start(self, shutdown: Shutdown) -> Arc<Self> {
         let this = Arc::new(self);
+        let manager = Arc::clone(&this);
+        // The task is tracked as a relay until it stops, so that the shutdown waits for the pool
+        // to be drained.
+        let running = this.relays.track();
 
         tokio::spawn(async move {
+            let _running = running;
             loop {
                 // TODO: add a listener on the pool and only try to call [`best_transaction`]
                 // TODO: when we are sure there is a transaction in the pool. This avoids an
@@ -68,6 +202,12 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
                 // TODO: sleep which could sleep for a while before handling transactions.
                 let best_hashes =
                     this.eth_client.mempool().as_ref().best_transactions().map(|x| *x.hash()).collect::<Vec<_>>();
+                // The failed relays put their transaction back in the pool, hence the task only
+                // stops once it is the only relay left.
+                if shutdown.is_triggered() && best_hashes.is_empty() && this.relays.in_progress() == 1 {
+                    tracing::info!(target: "account_manager", "stopped relaying transactions, the pool is drained");
+                    break;
+                }
                 if let Some(best_hash) = best_hashes.first() {
                     let transaction = this.eth_client.mempool().get(best_hash);
                     if transaction.is_none() {
@@ -77,17 +217,24 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
                     let transaction = transaction.expect("not None");
 
                     // We remove the transaction to avoid another relayer from picking it up.
+                    // It is tracked as in flight until indexed, to be accounted for in the pending state.
                     this.eth_client.mempool().as_ref().remove_transactions(vec![*best_hash]);
+                    this.eth_client.in_flight().insert(transaction.clone());
 
                     // Spawn a task for the transaction to be sent
                     let manager = this.clone();
+                    let relay = this.relays.track();
                     tokio::spawn(async move {
+                        let _relay = relay;
+
                         // Lock the relayer account
                         let hash = transaction.hash();
                         let maybe_relayer = manager.get_relayer().await;
                         if maybe_relayer.is_err() {
                             // If we fail to fetch a relayer, we need to re-insert the transaction in the pool
                             tracing::error!(target: "account_manager", err = ?maybe_relayer.unwrap_err(), ?hash, "failed to fetch relayer");
+                            MEMPOOL_METRICS.record_relay("no_relayer");
+                            manager.eth_client.in_flight().remove(hash);
                             let _ = manager
                                 .eth_client
                                 .mempool()
@@ -104,6 +251,8 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
                         if res.is_err() {
                             // If the relayer failed to relay the transaction, we need to reposition it in the mempool
                             tracing::error!(target: "account_manager", err = ?res.unwrap_err(), ?hash, "failed to relay transaction");
+                            MEMPOOL_METRICS.record_relay("failure");
+                            manager.eth_client.in_flight().remove(hash);
                             let _ = manager
                                 .eth_client
                                 .mempool()
@@ -112,17 +261,35 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
                             return;
                         }
 
+                        MEMPOOL_METRICS.record_relay("success");
                         tracing::info!(target: "account_manager", starknet_hash = ?res.expect("not error"), ethereum_hash = ?transaction_signed.hash());
                     });
                 }
 
-                tokio::time::sleep(Duration::from_secs(1)).await;
+                if shutdown.is_triggered() {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+                tokio::select! {
+                    () = tokio::time::sleep(Duration::from_secs(1)) => {}
+                    () = shutdown.wait() => {}
+                }
             }
         });
+
+        manager
+    }
+
+    /// Waits for the transactions of the pool to be relayed and for the relays in progress to
+    /// get their Starknet hash and store it, up to the timeout. Returns false if some relays
+    /// didn't complete in time.
+    pub async fn wait_for_relays(&self, timeout: Duration) -> bool {
+        self.relays.wait(timeout).await
     }
 
-    /// Returns the next available account from the manager.
-    pub async fn get_relayer(&self) -> eyre::Result<Relayer<JsonRpcClient<HttpTransport>>>
+    /// Returns the next available account from the manager, sharing the Starknet provider of
+    /// the client.
+    pub async fn get_relayer(&self) -> eyre::Result<Relayer<SP>>
     where
         SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
     {
@@ -151,8 +318,8 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
             let account = Relayer::new(
                 account_address,
                 balance,
-                JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone())),
-                Some(Arc::new(self.eth_client.eth_provider().database().clone())),
+                SP::clone(self.eth_client.starknet_provider()),
+                Some(self.eth_client.eth_provider().database().clone()),
             );
 
             // Return the locked relayer instance
@@ -162,14 +329,40 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
         Err(eyre::eyre!("failed to fetch funded account"))
     }
 
+    /// Returns the number of accounts holding enough funds to relay transactions. The balances
+    /// fetched less than [`RELAYER_BALANCE_TTL`] ago, e.g. by the relays, are reused.
+    pub async fn funded_relayers(&self) -> eyre::Result<usize> {
+        let balances = futures::future::try_join_all(self.accounts.iter().map(|account| async move {
+            match self.recent_balance(*account) {
+                Some(balance) => Ok(balance),
+                None => self.get_balance(*account).await,
+            }
+        }))
+        .await?;
+        Ok(balances.into_iter().filter(|balance| *balance >= U256::from(ONE_TENTH_ETH)).count())
+    }
+
+    /// Returns the balance of the account fetched less than [`RELAYER_BALANCE_TTL`] ago, if any.
+    fn recent_balance(&self, account_address: Felt) -> Option<U256> {
+        self.balances
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&account_address)
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < RELAYER_BALANCE_TTL)
+            .map(|(balance, _)| *balance)
+    }
+
     /// Retrieves the balance of the specified account address for the [`BlockTag::Pending`]
     async fn get_balance(&self, account_address: Felt) -> eyre::Result<U256> {
         // Get the balance of the address for the Pending block.
-        self.eth_client
+        let balance = self
+            .eth_client
             .starknet_provider()
             .balance_at(account_address, starknet::core::types::BlockId::Tag(BlockTag::Pending))
-            .await
-            .map_err(Into::into)
+            .await?;
+        MEMPOOL_METRICS.record_balance(account_address, balance);
+        self.balances.lock().unwrap_or_else(PoisonError::into_inner).insert(account_address, (balance, Instant::now()));
+        Ok(balance)
     }
 }
 
@@ -210,8 +403,13 @@ where
 }
 
 /// Maintains the transaction pool by periodically polling the database in order to
-/// fetch the latest block and mark the block's transactions as mined by the node.
-pub fn maintain_transaction_pool<SP>(eth_client: Arc<EthClient<SP>>, prune_duration: Duration)
+/// fetch the latest block and mark the block's transactions as mined by the node,
+/// until the shutdown.
+pub fn maintain_transaction_pool<SP>(
+    eth_client: Arc<EthClient<SP>>,
+    prune_duration: Duration,
+    shutdown: Shutdown,
+) -> JoinHandle<()>
 where
     SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
 {
@@ -221,7 +419,9 @@ where
         // Mapping to store the transactions in the mempool with a timestamp to potentially prune them
         let mut mempool_transactions = HashMap::new();
 
-        loop {
+        while !shutdown.is_triggered() {
+            MEMPOOL_METRICS.record_transactions(&eth_client);
+
             // Adding the transactions to the mempool mapping with a timestamp
             for tx in eth_client
                 .mempool()
@@ -232,6 +432,18 @@ where
                 mempool_transactions.entry(*tx.hash()).or_insert_with(Instant::now);
             }
 
+            // Remove the transactions in flight which were indexed, including the ones of the
+            // blocks indexed between two polls.
+            let in_flight = eth_client.in_flight().transactions().iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+            if !in_flight.is_empty() {
+                match eth_client.eth_provider().database().transactions_by_hashes(&in_flight).await {
+                    Ok(indexed) => indexed.iter().for_each(|tx| eth_client.in_flight().remove(&tx.hash)),
+                    Err(err) => {
+                        tracing::error!(target: "maintain_transaction_pool", ?err, "failed to fetch the transactions in flight");
+                    }
+                }
+            }
+
             // Fetch the latest block number
             let Ok(current_block_number) = eth_client.eth_provider().block_number().await else {
                 tracing::error!(target: "maintain_transaction_pool", "failed to fetch current block number");
@@ -287,10 +499,12 @@ where
                         let mut mined_transactions: Vec<_> =
                             sealed_block.body.transactions.iter().map(|tx| tx.hash).collect();
 
-                        // Prune mined transactions from the mempool mapping
+                        // Prune mined transactions from the mempool mapping and the transactions in flight
                         for tx_hash in &mined_transactions {
                             mempool_transactions.remove(tx_hash);
+                            eth_client.in_flight().remove(tx_hash);
                         }
+                        eth_client.in_flight().prune(prune_duration);
 
                         // Prune transactions that have been in the mempool for more than 5 minutes
                         let now = Instant::now();
@@ -328,7 +542,51 @@ where
                     tracing::error!(target: "maintain_transaction_pool", "failed to fetch latest block");
                 }
             }
-            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            tokio::select! {
+                () = tokio::time::sleep(Duration::from_secs(1)) => {}
+                () = shutdown.wait() => {}
+            }
         }
-    });
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_relays_times_out() {
+        // Given
+        let relays = RelayTracker::new();
+        let _relay = relays.track();
+
+        // When
+        let completed = relays.wait(Duration::from_millis(50)).await;
+
+        // Then
+        assert!(!completed);
+        assert_eq!(relays.in_progress(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_relays_drained() {
+        // Given
+        let relays = RelayTracker::new();
+        let first = relays.track();
+        let second = relays.track();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(first);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(second);
+        });
+
+        // When
+        let completed = relays.wait(Duration::from_secs(5)).await;
+
+        // Then
+        assert!(completed);
+        assert_eq!(relays.in_progress(), 0);
+    }
 }
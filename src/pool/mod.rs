@@ -1,3 +1,5 @@
 pub mod constants;
+pub mod gas_oracle;
+pub mod in_flight;
 pub mod mempool;
 pub mod validate;
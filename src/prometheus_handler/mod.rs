@@ -34,6 +34,23 @@ pub use prometheus::{
     exponential_buckets, Error as PrometheusError, Histogram, HistogramOpts, HistogramVec, Opts, Registry,
 };
 
+/// Histogram time buckets in microseconds.
+pub const HISTOGRAM_BUCKETS: [f64; 13] = [
+    5.0,
+    25.0,
+    100.0,
+    500.0,
+    1_000.0,
+    2_500.0,
+    10_000.0,
+    25_000.0,
+    100_000.0,
+    1_000_000.0,
+    2_000_000.0,
+    5_000_000.0,
+    10_000_000.0,
+];
+
 pub fn register<T: Clone + Collector + 'static>(metric: T, registry: &Registry) -> Result<T, PrometheusError> {
     registry.register(Box::new(metric.clone()))?;
     Ok(metric)
@@ -5,13 +5,16 @@ use super::{
     },
     error::KakarotError,
 };
-use crate::providers::eth_provider::{
-    database::ethereum::EthereumTransactionStore,
-    provider::{EthApiResult, EthDataProvider},
+use crate::providers::{
+    eth_provider::{
+        database::ethereum::EthereumTransactionStore,
+        provider::{EthApiResult, EthDataProvider},
+    },
+    sn_provider::STARKNET_METRICS,
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{B256, U256, U64};
-use alloy_rpc_types::Header;
+use alloy_rpc_types::{BlockHashOrNumber, Header};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use mongodb::bson::doc;
@@ -50,7 +53,15 @@ where
 {
     async fn header(&self, block_id: &BlockId) -> EthApiResult<Option<Header>> {
         let block_hash_or_number = self.block_id_into_block_number_or_hash(*block_id).await?;
-        Ok(self.database().header(block_hash_or_number).await?)
+        if let Some(header) = self.cache().header(block_hash_or_number) {
+            return Ok(Some(header));
+        }
+
+        let header = self.database().header(block_hash_or_number).await?;
+        if let Some(header) = &header {
+            self.cache().insert_header(block_hash_or_number, header);
+        }
+        Ok(header)
     }
 
     async fn block_number(&self) -> EthApiResult<U64> {
@@ -59,10 +70,14 @@ where
             None => {
                 let span = tracing::span!(tracing::Level::INFO, "sn::block_number");
                 U64::from(
-                    self.starknet_provider_inner().block_number().instrument(span).await.map_err(KakarotError::from)?,
+                    STARKNET_METRICS
+                        .observe("block_number", self.starknet_provider_inner().block_number().instrument(span))
+                        .await
+                        .map_err(KakarotError::from)?,
                 )
             }
             Some(header) => {
+                self.cache().on_latest_header(&header);
                 let is_pending_block = header.hash.is_zero();
                 U64::from(if is_pending_block { header.number - 1 } else { header.number })
             }
@@ -71,7 +86,7 @@ where
     }
 
     async fn block_by_hash(&self, hash: B256, full: bool) -> EthApiResult<Option<ExtendedBlock>> {
-        Ok(self.database().block(hash.into(), full).await?)
+        self.cached_block(hash.into(), full).await
     }
 
     async fn block_by_number(
@@ -80,7 +95,7 @@ where
         full: bool,
     ) -> EthApiResult<Option<ExtendedBlock>> {
         let block_number = self.tag_into_block_number(number_or_tag).await?;
-        Ok(self.database().block(block_number.into(), full).await?)
+        self.cached_block(block_number.into(), full).await
     }
 
     async fn block_transaction_count_by_hash(&self, hash: B256) -> EthApiResult<Option<U256>> {
@@ -103,3 +118,25 @@ where
         Ok(Some(self.database().transactions(block_hash_or_number).await?))
     }
 }
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the block from the cache, or from the database if it isn't cached yet.
+    async fn cached_block(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        full: bool,
+    ) -> EthApiResult<Option<ExtendedBlock>> {
+        if let Some(block) = self.cache().block(block_hash_or_number, full) {
+            return Ok(Some(block));
+        }
+
+        let block = self.database().block(block_hash_or_number, full).await?;
+        if let Some(block) = &block {
+            self.cache().insert_block(block_hash_or_number, full, block);
+        }
+        Ok(block)
+    }
+}
@@ -0,0 +1,284 @@
+use super::database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt, transaction::ExtendedTransaction};
+use crate::prometheus_handler::{register, CounterVec, GaugeVec, Opts, PrometheusError, Registry, I64, U64};
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_rpc_types::{BlockHashOrNumber, Header};
+use schnellru::{ByLength, LruMap};
+use std::{
+    hash::Hash,
+    sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+/// The metrics of the provider caches, shared by all the providers of the process.
+pub static CACHE_METRICS: LazyLock<CacheMetrics> =
+    LazyLock::new(|| CacheMetrics::new().expect("failed to create the provider cache metrics"));
+
+/// Metrics of the provider caches, labelled by cache.
+#[derive(Debug, Clone)]
+pub struct CacheMetrics {
+    /// Number of lookups served from a cache.
+    hits: CounterVec<U64>,
+    /// Number of lookups missing from a cache.
+    misses: CounterVec<U64>,
+    /// Number of entries held by a cache.
+    entries: GaugeVec<I64>,
+}
+
+impl CacheMetrics {
+    fn new() -> Result<Self, PrometheusError> {
+        Ok(Self {
+            hits: CounterVec::new(
+                Opts::new("eth_provider_cache_hits", "Number of lookups served from the provider caches"),
+                &["cache"],
+            )?,
+            misses: CounterVec::new(
+                Opts::new("eth_provider_cache_misses", "Number of lookups missing from the provider caches"),
+                &["cache"],
+            )?,
+            entries: GaugeVec::new(
+                Opts::new("eth_provider_cache_entries", "Number of entries held by the provider caches"),
+                &["cache"],
+            )?,
+        })
+    }
+
+    /// Registers the metrics in the given registry.
+    pub fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+        register(self.hits.clone(), registry)?;
+        register(self.misses.clone(), registry)?;
+        register(self.entries.clone(), registry)?;
+        Ok(())
+    }
+}
+
+/// A named LRU cache, safe to share between tasks.
+#[derive(Debug)]
+struct Cache<K: Hash + PartialEq, V> {
+    name: &'static str,
+    map: Mutex<LruMap<K, V>>,
+}
+
+impl<K: Hash + PartialEq + Clone, V: Clone> Cache<K, V> {
+    fn new(name: &'static str, size: u32) -> Self {
+        Self { name, map: Mutex::new(LruMap::new(ByLength::new(size))) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruMap<K, V>> {
+        // The map is never left in an inconsistent state, hence a poisoned lock is safe to use.
+        self.map.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the cached value, marking it as the most recently used.
+    fn get(&self, key: &K) -> Option<V> {
+        let value = self.lock().get(key).cloned();
+        let counter = if value.is_some() { &CACHE_METRICS.hits } else { &CACHE_METRICS.misses };
+        counter.with_label_values(&[self.name]).inc();
+        value
+    }
+
+    /// Inserts the value, evicting the least recently used entry if the cache is full.
+    fn insert(&self, key: K, value: V) {
+        let mut map = self.lock();
+        map.insert(key, value);
+        self.record_len(map.len());
+    }
+
+    /// Removes the entries for which the predicate returns false.
+    fn retain(&self, predicate: impl Fn(&K, &V) -> bool) {
+        let mut map = self.lock();
+        let evicted =
+            map.iter().filter(|(key, value)| !predicate(key, value)).map(|(key, _)| key.clone()).collect::<Vec<_>>();
+        for key in evicted {
+            map.remove(&key);
+        }
+        self.record_len(map.len());
+    }
+
+    fn record_len(&self, len: usize) {
+        CACHE_METRICS.entries.with_label_values(&[self.name]).set(i64::try_from(len).unwrap_or(i64::MAX));
+    }
+}
+
+/// In-process LRU caches of the data served by the provider.
+///
+/// Only the data of sealed blocks, which doesn't change once indexed, is cached. The entries at
+/// or above the pending block (zero-hash header) are invalidated when the pending block changes,
+/// so that a rewrite of the tip of the chain by the indexer isn't hidden by the caches. The
+/// storages written without a pending block aren't cached, see `EthereumBlockStore::is_cacheable`.
+#[derive(Debug)]
+pub struct ProviderCache {
+    headers: Cache<BlockHashOrNumber, Header>,
+    blocks: Cache<(BlockHashOrNumber, bool), ExtendedBlock>,
+    receipts: Cache<B256, ExtendedTxReceipt>,
+    transactions: Cache<B256, ExtendedTransaction>,
+    code: Cache<(Address, u64), Bytes>,
+    /// The last pending header seen by the provider.
+    pending: Mutex<Option<Header>>,
+}
+
+impl ProviderCache {
+    /// Returns new empty caches, each holding at most `size` entries.
+    pub fn new(size: u32) -> Self {
+        Self {
+            headers: Cache::new("headers", size),
+            blocks: Cache::new("blocks", size),
+            receipts: Cache::new("receipts", size),
+            transactions: Cache::new("transactions", size),
+            code: Cache::new("code", size),
+            pending: Mutex::new(None),
+        }
+    }
+
+    pub fn header(&self, block_hash_or_number: BlockHashOrNumber) -> Option<Header> {
+        self.headers.get(&block_hash_or_number)
+    }
+
+    pub fn insert_header(&self, block_hash_or_number: BlockHashOrNumber, header: &Header) {
+        if !header.hash.is_zero() {
+            self.headers.insert(block_hash_or_number, header.clone());
+        }
+    }
+
+    pub fn block(&self, block_hash_or_number: BlockHashOrNumber, full: bool) -> Option<ExtendedBlock> {
+        self.blocks.get(&(block_hash_or_number, full))
+    }
+
+    pub fn insert_block(&self, block_hash_or_number: BlockHashOrNumber, full: bool, block: &ExtendedBlock) {
+        if !block.header.hash.is_zero() {
+            self.blocks.insert((block_hash_or_number, full), block.clone());
+        }
+    }
+
+    pub fn receipt(&self, hash: &B256) -> Option<ExtendedTxReceipt> {
+        self.receipts.get(hash)
+    }
+
+    pub fn insert_receipt(&self, receipt: &ExtendedTxReceipt) {
+        if is_sealed(receipt.block_hash) {
+            self.receipts.insert(receipt.transaction_hash, receipt.clone());
+        }
+    }
+
+    pub fn transaction(&self, hash: &B256) -> Option<ExtendedTransaction> {
+        self.transactions.get(hash)
+    }
+
+    pub fn insert_transaction(&self, transaction: &ExtendedTransaction) {
+        if is_sealed(transaction.block_hash) {
+            self.transactions.insert(transaction.hash, transaction.clone());
+        }
+    }
+
+    /// Returns the code of the address at the given sealed block number.
+    pub fn code(&self, address: Address, block_number: u64) -> Option<Bytes> {
+        self.code.get(&(address, block_number))
+    }
+
+    /// Inserts the code of the address at the given sealed block number.
+    pub fn insert_code(&self, address: Address, block_number: u64, code: &Bytes) {
+        self.code.insert((address, block_number), code.clone());
+    }
+
+    /// Records the latest header of the database. If the pending block changed since the last
+    /// call, the entries at or above the block number of the previous pending block are evicted.
+    pub fn on_latest_header(&self, header: &Header) {
+        let latest_pending = header.hash.is_zero().then(|| header.clone());
+        let previous = {
+            let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            if *pending == latest_pending {
+                return;
+            }
+            std::mem::replace(&mut *pending, latest_pending)
+        };
+
+        if let Some(previous) = previous {
+            self.invalidate_from(previous.number.min(header.number));
+        }
+    }
+
+    /// Evicts the entries at or above the given block number.
+    fn invalidate_from(&self, number: u64) {
+        let below = |block_number: Option<u64>| block_number.is_some_and(|block_number| block_number < number);
+        self.headers.retain(|_, header| header.number < number);
+        self.blocks.retain(|_, block| block.header.number < number);
+        self.receipts.retain(|_, receipt| below(receipt.block_number));
+        self.transactions.retain(|_, transaction| below(transaction.block_number));
+        self.code.retain(|(_, block_number), _| *block_number < number);
+    }
+}
+
+/// Returns true if the block hash is the one of a sealed block.
+fn is_sealed(block_hash: Option<B256>) -> bool {
+    block_hash.is_some_and(|hash| !hash.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(number: u64, hash: B256) -> Header {
+        Header { number, hash, ..Default::default() }
+    }
+
+    #[test]
+    fn test_cache_skips_pending_headers() {
+        // Given
+        let cache = ProviderCache::new(10);
+
+        // When
+        cache.insert_header(1.into(), &header(1, B256::with_last_byte(1)));
+        cache.insert_header(2.into(), &header(2, B256::ZERO));
+
+        // Then
+        assert_eq!(cache.header(1.into()), Some(header(1, B256::with_last_byte(1))));
+        assert_eq!(cache.header(2.into()), None);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        // Given
+        let cache = ProviderCache::new(2);
+
+        // When
+        for number in 1..=3 {
+            cache.insert_header(number.into(), &header(number, B256::with_last_byte(1)));
+        }
+
+        // Then
+        assert_eq!(cache.header(1.into()), None);
+        assert!(cache.header(2.into()).is_some());
+        assert!(cache.header(3.into()).is_some());
+    }
+
+    #[test]
+    fn test_cache_disabled_without_size() {
+        // Given
+        let cache = ProviderCache::new(0);
+
+        // When
+        cache.insert_header(1.into(), &header(1, B256::with_last_byte(1)));
+
+        // Then
+        assert_eq!(cache.header(1.into()), None);
+    }
+
+    #[test]
+    fn test_cache_invalidated_on_pending_change() {
+        // Given
+        let cache = ProviderCache::new(10);
+        for number in 1..=3 {
+            cache.insert_header(number.into(), &header(number, B256::with_last_byte(1)));
+            cache.insert_code(Address::ZERO, number, &Bytes::from_static(&[1]));
+        }
+        cache.on_latest_header(&header(4, B256::ZERO));
+
+        // When
+        cache.on_latest_header(&header(2, B256::ZERO));
+
+        // Then
+        assert!(cache.header(1.into()).is_some());
+        assert!(cache.code(Address::ZERO, 1).is_some());
+        assert_eq!(cache.header(2.into()), None);
+        assert_eq!(cache.header(3.into()), None);
+        assert_eq!(cache.code(Address::ZERO, 3), None);
+    }
+}
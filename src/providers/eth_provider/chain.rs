@@ -1,14 +1,33 @@
-use crate::providers::eth_provider::{
-    error::KakarotError,
-    provider::{EthApiResult, EthDataProvider},
+use crate::{
+    config::config,
+    prometheus_handler::{Gauge, I64},
+    providers::{
+        eth_provider::{
+            database::ethereum::EthereumBlockStore,
+            error::KakarotError,
+            provider::{EthApiResult, EthDataProvider},
+        },
+        sn_provider::STARKNET_METRICS,
+    },
 };
 use alloy_primitives::{U256, U64};
-use alloy_rpc_types::{SyncInfo, SyncStatus};
+use alloy_rpc_types::{Header, SyncInfo, SyncStatus};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use starknet::core::types::SyncStatusType;
+use starknet::{
+    core::types::{MaybePendingBlockWithTxHashes, StarknetError, SyncStatusType},
+    providers::ProviderError,
+};
+use std::sync::LazyLock;
 use tracing::Instrument;
 
+/// Number of Starknet blocks not indexed yet, updated on each `eth_syncing` call and readiness
+/// check.
+pub static INDEXER_LAG: LazyLock<Gauge<I64>> = LazyLock::new(|| {
+    Gauge::new("eth_provider_indexer_lag", "Number of Starknet blocks not indexed yet")
+        .expect("failed to create the indexer lag metric")
+});
+
 #[async_trait]
 #[auto_impl(Arc, &)]
 pub trait ChainProvider {
@@ -26,18 +45,123 @@ where
 {
     async fn syncing(&self) -> EthApiResult<SyncStatus> {
         let span = tracing::span!(tracing::Level::INFO, "sn::syncing");
-        Ok(match self.starknet_provider_inner().syncing().instrument(span).await.map_err(KakarotError::from)? {
-            SyncStatusType::NotSyncing => SyncStatus::None,
-            SyncStatusType::Syncing(data) => SyncStatus::Info(Box::new(SyncInfo {
+        if let SyncStatusType::Syncing(data) = STARKNET_METRICS
+            .observe("syncing", self.starknet_provider_inner().syncing().instrument(span))
+            .await
+            .map_err(KakarotError::from)?
+        {
+            return Ok(SyncStatus::Info(Box::new(SyncInfo {
                 starting_block: U256::from(data.starting_block_num),
                 current_block: U256::from(data.current_block_num),
                 highest_block: U256::from(data.highest_block_num),
                 ..Default::default()
-            })),
-        })
+            })));
+        }
+
+        // Starknet is in sync, check that the indexer caught up with the Starknet head.
+        let span = tracing::span!(tracing::Level::INFO, "sn::block_number");
+        let head = STARKNET_METRICS
+            .observe("block_number", self.starknet_provider_inner().block_number().instrument(span))
+            .await
+            .map_err(KakarotError::from)?;
+        let indexed = latest_indexed_block_number(self.database().latest_header().await?);
+        let indexed = self.indexed_starknet_block_number(indexed).await?;
+
+        record_indexer_lag(indexed, head);
+        Ok(indexer_sync_status(indexed, head, config().syncing_lag_threshold))
     }
 
     async fn chain_id(&self) -> EthApiResult<Option<U64>> {
         Ok(Some(U64::from(self.chain_id)))
     }
 }
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the number of the Starknet block of the indexed EVM block with the given number,
+    /// found by the hash of the block as for [`EthDataProvider::starknet_block_id`], so that the
+    /// indexer lag doesn't assume the numbers of both chains match. Falls back to the EVM number
+    /// when the block isn't indexed or is unknown to Starknet, e.g. reorganized.
+    pub(crate) async fn indexed_starknet_block_number(&self, indexed: u64) -> EthApiResult<u64> {
+        let Some(block_id) = self.starknet_block_id(indexed).await? else {
+            return Ok(indexed);
+        };
+
+        let span = tracing::span!(tracing::Level::INFO, "sn::block_number_by_hash");
+        match STARKNET_METRICS
+            .observe(
+                "block_number_by_hash",
+                self.starknet_provider_inner().get_block_with_tx_hashes(block_id).instrument(span),
+            )
+            .await
+        {
+            Ok(MaybePendingBlockWithTxHashes::Block(block)) => Ok(block.block_number),
+            Ok(MaybePendingBlockWithTxHashes::PendingBlock(_))
+            | Err(ProviderError::StarknetError(StarknetError::BlockNotFound)) => Ok(indexed),
+            Err(err) => Err(KakarotError::from(err).into()),
+        }
+    }
+}
+
+/// Records the number of Starknet blocks the indexer lags behind the head in [`INDEXER_LAG`].
+pub(crate) fn record_indexer_lag(indexed: u64, head: u64) {
+    INDEXER_LAG.set(i64::try_from(head.saturating_sub(indexed)).unwrap_or(i64::MAX));
+}
+
+/// Returns the number of the latest indexed block given the latest header of the database. A
+/// pending header is stored with a zero hash and doesn't count as indexed.
+pub(crate) fn latest_indexed_block_number(latest_header: Option<Header>) -> u64 {
+    latest_header
+        .map(|header| if header.hash.is_zero() { header.number.saturating_sub(1) } else { header.number })
+        .unwrap_or_default()
+}
+
+/// Returns the syncing status of the indexer, which is syncing while the indexed block is
+/// more than `threshold` blocks behind the Starknet head.
+fn indexer_sync_status(indexed: u64, head: u64, threshold: u64) -> SyncStatus {
+    if head.saturating_sub(indexed) <= threshold {
+        return SyncStatus::None;
+    }
+    SyncStatus::Info(Box::new(SyncInfo {
+        starting_block: U256::from(indexed),
+        current_block: U256::from(indexed),
+        highest_block: U256::from(head),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    #[test]
+    fn test_latest_indexed_block_number() {
+        // Given
+        let header = |number, hash| Header { number, hash, ..Default::default() };
+
+        // When & Then
+        assert_eq!(latest_indexed_block_number(None), 0);
+        assert_eq!(latest_indexed_block_number(Some(header(10, B256::repeat_byte(1)))), 10);
+        assert_eq!(latest_indexed_block_number(Some(header(10, B256::ZERO))), 9);
+        assert_eq!(latest_indexed_block_number(Some(header(0, B256::ZERO))), 0);
+    }
+
+    #[test]
+    fn test_indexer_sync_status() {
+        // When & Then
+        assert_eq!(indexer_sync_status(100, 105, 5), SyncStatus::None);
+        assert_eq!(indexer_sync_status(110, 105, 5), SyncStatus::None);
+        assert_eq!(
+            indexer_sync_status(100, 106, 5),
+            SyncStatus::Info(Box::new(SyncInfo {
+                starting_block: U256::from(100),
+                current_block: U256::from(100),
+                highest_block: U256::from(106),
+                ..Default::default()
+            }))
+        );
+    }
+}
@@ -1,17 +1,38 @@
 use alloy_primitives::{B256, U256};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
-use std::{str::FromStr, sync::LazyLock};
 
-/// Maximum priority fee per gas
-pub static MAX_PRIORITY_FEE_PER_GAS: LazyLock<u64> = LazyLock::new(|| 0);
+/// Number of logs per page of `kakarot_getLogsPaged` when `MAX_LOGS` isn't set
+pub const DEFAULT_LOGS_PAGE_SIZE: u64 = 10_000;
 
-/// Maximum number of logs that can be fetched in a single request
-pub static MAX_LOGS: LazyLock<Option<u64>> =
-    LazyLock::new(|| std::env::var("MAX_LOGS").ok().and_then(|val| u64::from_str(&val).ok()));
+/// Number of headers whose logs blooms are scanned at once by `eth_getLogs`
+pub const LOGS_BLOOM_SCAN_BATCH_SIZE: u64 = 10_000;
+
+/// Maximum number of blocks returned by `eth_feeHistory`. Same value as geth.
+pub const MAX_FEE_HISTORY_BLOCK_COUNT: u64 = 1024;
+
+/// Number of Starknet execution costs of the receipts of a block fetched at once
+pub const STARKNET_EXECUTION_COST_CONCURRENCY: usize = 16;
 
 /// Gas limit for estimate gas and call
 pub const CALL_REQUEST_GAS_LIMIT: u64 = 50_000_000;
+/// Gas charged for every transaction, without any calldata or contract creation
+pub const TX_GAS: u64 = 21_000;
+/// Gas charged for a contract creation transaction, without any calldata
+pub const TX_GAS_CONTRACT_CREATION: u64 = 53_000;
+/// Gas charged per zero byte of calldata
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas charged per non zero byte of calldata (EIP-2028)
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Gas charged per address in the access list (EIP-2930)
+pub const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+/// Gas charged per storage key in the access list (EIP-2930)
+pub const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+/// Gas charged per 32 bytes word of init code (EIP-3860)
+pub const TX_INIT_CODE_WORD_GAS: u64 = 2;
+/// Maximum relative error allowed between the estimated gas and the exact
+/// required gas before the binary search of `eth_estimateGas` stops. Same value as geth.
+pub const ESTIMATE_GAS_ERROR_RATIO: f64 = 0.015;
 /// Number of characters for representing a U256 in a hex string form. Used for padding hashes
 pub const HASH_HEX_STRING_LEN: usize = 64;
 /// Number of characters for representing logs topics in a hex string form. Used for padding logs topics
@@ -30,6 +51,8 @@ pub const STARKNET_MODULUS: U256 = U256::from_limbs([0x1, 0, 0, 0x0800_0000_0000
 pub struct Constant {
     /// Maximum number of logs to output for `eth_getLogs` RPC Method
     pub max_logs: Option<u64>,
+    /// Maximum number of blocks queried by the `eth_getLogs` RPC Method
+    pub max_logs_block_range: Option<u64>,
     /// Name of the `StarkNet` network.
     pub starknet_network: String,
     /// Maximum number of Felts in calldata.
@@ -51,27 +74,25 @@ pub mod hive {
     use starknet::{
         accounts::{ConnectedAccount, ExecutionEncoding, SingleOwnerAccount},
         core::types::Felt,
-        providers::{jsonrpc::HttpTransport, JsonRpcClient},
         signers::{LocalWallet, SigningKey},
     };
     use tokio::sync::Mutex;
 
-    use crate::constants::{KAKAROT_RPC_CONFIG, STARKNET_CHAIN_ID};
+    use crate::{config::config, constants::STARKNET_CHAIN_ID, providers::sn_provider::FailoverProvider};
 
-    pub static DEPLOY_WALLET: LazyLock<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>> =
-        LazyLock::new(|| {
-            SingleOwnerAccount::new(
-                JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone())),
-                LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
-                    Felt::from_str(&var("KATANA_PRIVATE_KEY").expect("Missing deployer private key"))
-                        .expect("Failed to parse deployer private key"),
-                )),
-                Felt::from_str(&var("KATANA_ACCOUNT_ADDRESS").expect("Missing deployer address"))
-                    .expect("Failed to parse deployer address"),
-                *STARKNET_CHAIN_ID,
-                ExecutionEncoding::New,
-            )
-        });
+    pub static DEPLOY_WALLET: LazyLock<SingleOwnerAccount<FailoverProvider, LocalWallet>> = LazyLock::new(|| {
+        SingleOwnerAccount::new(
+            config().starknet.starknet_provider(),
+            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
+                Felt::from_str(&var("KATANA_PRIVATE_KEY").expect("Missing deployer private key"))
+                    .expect("Failed to parse deployer private key"),
+            )),
+            Felt::from_str(&var("KATANA_ACCOUNT_ADDRESS").expect("Missing deployer address"))
+                .expect("Failed to parse deployer address"),
+            *STARKNET_CHAIN_ID,
+            ExecutionEncoding::New,
+        )
+    });
     pub static DEPLOY_WALLET_NONCE: LazyLock<Arc<Mutex<Felt>>> = LazyLock::new(|| {
         tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
@@ -80,14 +101,3 @@ pub mod hive {
         })
     });
 }
-
-#[cfg(feature = "forwarding")]
-pub mod forwarding {
-    use super::LazyLock;
-    use url::Url;
-
-    pub static MAIN_RPC_URL: LazyLock<Url> = LazyLock::new(|| {
-        Url::parse(&std::env::var("MAIN_RPC_URL").expect("Missing MAIN_RPC_URL environment variable"))
-            .expect("Invalid MAIN_RPC_URL environment variable")
-    });
-}
@@ -2,22 +2,24 @@ use super::{
     filter,
     filter::EthDatabaseFilterBuilder,
     types::{
-        header::{ExtendedBlock, StoredHeader},
+        header::{ExtendedBlock, HeaderBloom, StoredHeader, StoredHeaderBloom},
+        log::StoredLog,
+        receipt::{ExtendedTxReceipt, StoredTransactionReceipt},
         transaction::{ExtendedTransaction, StoredTransaction},
     },
-    Database,
+    Database, FindOpts,
 };
 use crate::providers::eth_provider::{
-    database::types::transaction::{EthStarknetHashes, StoredEthStarknetTransactionHash},
-    error::EthApiError,
+    database::types::transaction::{EthStarknetHashes, StarknetExecutionCost, StoredEthStarknetTransactionHash},
+    error::{EthApiError, KakarotError},
 };
 use alloy_consensus::constants::EMPTY_ROOT_HASH;
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{Address, B256, U256};
 use alloy_rlp::Encodable;
-use alloy_rpc_types::{Block, BlockHashOrNumber, BlockTransactions, Header};
+use alloy_rpc_types::{Block, BlockHashOrNumber, BlockTransactions, Filter, Header, Index, Log, Topic};
 use alloy_serde::WithOtherFields;
 use async_trait::async_trait;
-use mongodb::bson::doc;
+use mongodb::bson::{doc, Document};
 use reth_primitives::BlockBody;
 use tracing::instrument;
 
@@ -28,15 +30,37 @@ pub trait EthereumTransactionStore {
     /// Returns the transaction with the given hash. Returns None if the
     /// transaction is not found.
     async fn transaction(&self, hash: &B256) -> Result<Option<ExtendedTransaction>, EthApiError>;
+    /// Returns the transactions with the given hashes, skipping the ones not found.
+    async fn transactions_by_hashes(&self, hashes: &[B256]) -> Result<Vec<ExtendedTransaction>, EthApiError>;
     /// Returns all transactions for the given block hash or number.
     async fn transactions(
         &self,
         block_hash_or_number: BlockHashOrNumber,
     ) -> Result<Vec<ExtendedTransaction>, EthApiError>;
+    /// Returns the transaction at the given index of the given block hash or number. Returns
+    /// None if the transaction is not found.
+    async fn transaction_by_block_and_index(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        index: Index,
+    ) -> Result<Option<ExtendedTransaction>, EthApiError>;
     /// Upserts the given transaction.
     async fn upsert_transaction(&self, transaction: ExtendedTransaction) -> Result<(), EthApiError>;
     /// Upserts the given transaction hash mapping (Ethereum -> Starknet).
     async fn upsert_transaction_hashes(&self, transaction_hashes: EthStarknetHashes) -> Result<(), EthApiError>;
+    /// Returns the transaction hash mapping (Ethereum -> Starknet) for the given Ethereum
+    /// transaction hash. Returns None if the mapping is not found.
+    async fn transaction_hashes(
+        &self,
+        eth_hash: &B256,
+    ) -> Result<Option<StoredEthStarknetTransactionHash>, EthApiError>;
+    /// Caches the Starknet execution cost in the transaction hash mapping of the given
+    /// Ethereum transaction hash.
+    async fn update_starknet_execution_cost(
+        &self,
+        eth_hash: &B256,
+        execution_cost: StarknetExecutionCost,
+    ) -> Result<(), EthApiError>;
 }
 
 #[async_trait]
@@ -47,6 +71,15 @@ impl EthereumTransactionStore for Database {
         Ok(self.get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
     }
 
+    #[instrument(skip_all, name = "db::transactions_by_hashes", err)]
+    async fn transactions_by_hashes(&self, hashes: &[B256]) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        if hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hashes(hashes).build();
+        Ok(self.get::<StoredTransaction>(filter, None).await?.into_iter().map(Into::into).collect())
+    }
+
     #[instrument(skip_all, name = "db::transactions", err)]
     async fn transactions(
         &self,
@@ -59,6 +92,19 @@ impl EthereumTransactionStore for Database {
         Ok(self.get::<StoredTransaction>(filter, None).await?.into_iter().map(Into::into).collect())
     }
 
+    #[instrument(skip_all, name = "db::transaction_by_block_and_index", err)]
+    async fn transaction_by_block_and_index(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        index: Index,
+    ) -> Result<Option<ExtendedTransaction>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
+            .with_block_hash_or_number(block_hash_or_number)
+            .with_tx_index(&index)
+            .build();
+        Ok(self.get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+    }
+
     #[instrument(skip_all, name = "db::upsert_transaction", err)]
     async fn upsert_transaction(&self, transaction: ExtendedTransaction) -> Result<(), EthApiError> {
         let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hash(&transaction.hash).build();
@@ -72,6 +118,29 @@ impl EthereumTransactionStore for Database {
             .build();
         Ok(self.update_one(StoredEthStarknetTransactionHash::from(transaction_hashes), filter, true).await?)
     }
+
+    #[instrument(skip_all, name = "db::transaction_hashes", err)]
+    async fn transaction_hashes(
+        &self,
+        eth_hash: &B256,
+    ) -> Result<Option<StoredEthStarknetTransactionHash>, EthApiError> {
+        let filter =
+            EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(eth_hash).build();
+        Ok(self.get_one(filter, None).await?)
+    }
+
+    #[instrument(skip_all, name = "db::update_starknet_execution_cost", err)]
+    async fn update_starknet_execution_cost(
+        &self,
+        eth_hash: &B256,
+        execution_cost: StarknetExecutionCost,
+    ) -> Result<(), EthApiError> {
+        let filter =
+            EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(eth_hash).build();
+        let execution_cost = mongodb::bson::to_bson(&execution_cost)
+            .map_err(|err| KakarotError::from(mongodb::error::Error::custom(err)))?;
+        Ok(self.set_fields::<StoredEthStarknetTransactionHash>(doc! {"execution_cost": execution_cost}, filter).await?)
+    }
 }
 
 /// Trait for interacting with a database that stores Ethereum typed
@@ -83,6 +152,8 @@ pub trait EthereumBlockStore {
     /// Returns the header for the given hash or number. Returns None if the
     /// header is not found.
     async fn header(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<Header>, EthApiError>;
+    /// Returns the headers within the given block range, bounds included, sorted by block number.
+    async fn headers(&self, from: u64, to: u64) -> Result<Vec<Header>, EthApiError>;
     /// Returns the block for the given hash or number. Returns None if the
     /// block is not found.
     async fn block(
@@ -98,6 +169,14 @@ pub trait EthereumBlockStore {
     /// Returns the transaction count for the given block hash or number. Returns None if the
     /// block is not found.
     async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError>;
+    /// Returns the block number and logs bloom of the first headers within the given block
+    /// range, bounds included, sorted by block number and up to the limit.
+    async fn logs_blooms(&self, from: u64, to: u64, limit: u64) -> Result<Vec<HeaderBloom>, EthApiError>;
+    /// Returns true if the data of the sealed blocks only changes along with the pending block,
+    /// as written by the indexer, hence can be cached by the provider (see `ProviderCache`).
+    fn is_cacheable(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait]
@@ -122,53 +201,25 @@ impl EthereumBlockStore for Database {
             .map(Into::into))
     }
 
+    #[instrument(skip_all, name = "db::headers", err)]
+    async fn headers(&self, from: u64, to: u64) -> Result<Vec<Header>, EthApiError> {
+        let builder = EthDatabaseFilterBuilder::<filter::Header>::default();
+        let find_options = FindOpts::default().with_sort(builder.block_number_sort());
+        let filter = builder.with_block_number_range(from, to).build();
+        Ok(self.get_and_map_to::<_, StoredHeader>(filter, Some(find_options)).await?)
+    }
+
     #[instrument(skip_all, name = "db::block", err)]
     async fn block(
         &self,
         block_hash_or_number: BlockHashOrNumber,
         full: bool,
     ) -> Result<Option<ExtendedBlock>, EthApiError> {
-        let maybe_header = self.header(block_hash_or_number).await?;
-        if maybe_header.is_none() {
+        let Some(header) = self.header(block_hash_or_number).await? else {
             return Ok(None);
-        }
-        let header = maybe_header.unwrap();
-
-        // The withdrawals are not supported, hence the withdrawals_root should always be empty.
-        if let Some(withdrawals_root) = header.withdrawals_root {
-            if withdrawals_root != EMPTY_ROOT_HASH {
-                return Err(EthApiError::Unsupported("withdrawals"));
-            }
-        }
-
-        let transactions = self.transactions(block_hash_or_number).await?;
-
-        let block_transactions = if full {
-            BlockTransactions::Full(transactions.clone())
-        } else {
-            BlockTransactions::Hashes(transactions.iter().map(|tx| tx.hash).collect())
         };
-
-        let block = reth_primitives::Block {
-            body: BlockBody {
-                transactions: transactions.into_iter().map(TryFrom::try_from).collect::<Result<_, _>>()?,
-                withdrawals: Some(Default::default()),
-                ..Default::default()
-            },
-            header: header.clone().try_into()?,
-        };
-
-        // This is how Reth computes the block size.
-        // `https://github.com/paradigmxyz/reth/blob/v0.2.0-beta.5/crates/rpc/rpc-types-compat/src/block.rs#L66`
-        let size = block.length();
-
-        Ok(Some(WithOtherFields::new(Block {
-            header,
-            transactions: block_transactions,
-            size: Some(U256::from(size)),
-            withdrawals: Some(Default::default()),
-            ..Default::default()
-        })))
+        let transactions = self.transactions(block_hash_or_number).await?;
+        build_block(header, transactions, full).map(Some)
     }
 
     #[instrument(skip_all, name = "db::transaction_count", err)]
@@ -183,48 +234,271 @@ impl EthereumBlockStore for Database {
         let count = self.count::<StoredTransaction>(filter).await?;
         Ok(Some(U256::from(count)))
     }
+
+    #[instrument(skip_all, name = "db::logs_blooms", err)]
+    async fn logs_blooms(&self, from: u64, to: u64, limit: u64) -> Result<Vec<HeaderBloom>, EthApiError> {
+        let builder = EthDatabaseFilterBuilder::<filter::Header>::default();
+        let find_options = FindOpts::default()
+            .with_projection(doc! {"_id": 0, "header.number": 1, "header.logsBloom": 1})
+            .with_sort(builder.block_number_sort())
+            .with_limit(limit);
+        let filter = builder.with_block_number_range(from, to).build();
+        let blooms: Vec<StoredHeaderBloom> = self.get(filter, find_options).await?;
+        Ok(blooms.into_iter().map(|bloom| bloom.header).collect())
+    }
+}
+
+/// Builds the block from its header and transactions.
+pub(crate) fn build_block(
+    header: Header,
+    transactions: Vec<ExtendedTransaction>,
+    full: bool,
+) -> Result<ExtendedBlock, EthApiError> {
+    // The withdrawals are not supported, hence the withdrawals_root should always be empty.
+    if let Some(withdrawals_root) = header.withdrawals_root {
+        if withdrawals_root != EMPTY_ROOT_HASH {
+            return Err(EthApiError::Unsupported("withdrawals"));
+        }
+    }
+
+    let block_transactions = if full {
+        BlockTransactions::Full(transactions.clone())
+    } else {
+        BlockTransactions::Hashes(transactions.iter().map(|tx| tx.hash).collect())
+    };
+
+    let block = reth_primitives::Block {
+        body: BlockBody {
+            transactions: transactions.into_iter().map(TryFrom::try_from).collect::<Result<_, _>>()?,
+            withdrawals: Some(Default::default()),
+            ..Default::default()
+        },
+        header: header.clone().try_into()?,
+    };
+
+    // This is how Reth computes the block size.
+    // `https://github.com/paradigmxyz/reth/blob/v0.2.0-beta.5/crates/rpc/rpc-types-compat/src/block.rs#L66`
+    let size = block.length();
+
+    Ok(WithOtherFields::new(Block {
+        header,
+        transactions: block_transactions,
+        size: Some(U256::from(size)),
+        withdrawals: Some(Default::default()),
+        ..Default::default()
+    }))
+}
+
+/// Trait for interacting with a database that stores Ethereum typed
+/// transaction receipts.
+#[async_trait]
+pub trait EthereumReceiptStore {
+    /// Returns the receipt of the transaction with the given hash. Returns None if the
+    /// receipt is not found.
+    async fn receipt(&self, hash: &B256) -> Result<Option<ExtendedTxReceipt>, EthApiError>;
+    /// Returns all receipts for the given block hash or number.
+    async fn receipts(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Vec<ExtendedTxReceipt>, EthApiError>;
+    /// Returns all receipts within the given block range, bounds included.
+    async fn receipts_by_block_range(&self, from: u64, to: u64) -> Result<Vec<ExtendedTxReceipt>, EthApiError>;
+}
+
+#[async_trait]
+impl EthereumReceiptStore for Database {
+    #[instrument(skip_all, name = "db::receipt", err)]
+    async fn receipt(&self, hash: &B256) -> Result<Option<ExtendedTxReceipt>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(hash).build();
+        Ok(self.get_one::<StoredTransactionReceipt>(filter, None).await?.map(Into::into))
+    }
+
+    #[instrument(skip_all, name = "db::receipts", err)]
+    async fn receipts(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default()
+            .with_block_hash_or_number(block_hash_or_number)
+            .build();
+        Ok(self.get_and_map_to::<_, StoredTransactionReceipt>(filter, None).await?)
+    }
+
+    #[instrument(skip_all, name = "db::receipts_by_block_range", err)]
+    async fn receipts_by_block_range(&self, from: u64, to: u64) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number_range(from, to).build();
+        Ok(self.get_and_map_to::<_, StoredTransactionReceipt>(filter, None).await?)
+    }
+}
+
+/// The blocks whose logs are queried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogBlocks {
+    /// The block with the given hash.
+    Hash(B256),
+    /// The blocks within the given block range, bounds included.
+    Range(u64, u64),
+    /// The blocks within the given disjoint block ranges, sorted and bounds included.
+    Ranges(Vec<(u64, u64)>),
+}
+
+/// A query on the stored logs, matching the logs of the queried blocks which match the
+/// addresses and the topics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogQuery {
+    /// The queried blocks.
+    pub blocks: LogBlocks,
+    /// The addresses of the logs. Any address matches if empty.
+    pub addresses: Vec<Address>,
+    /// The topics of the logs, by position. Any topic matches an empty position.
+    pub topics: [Topic; 4],
+    /// The number of matching logs skipped, in block order.
+    pub skip: u64,
+}
+
+impl LogQuery {
+    /// Returns the query of the logs of the blocks matching the addresses and topics of the filter.
+    pub fn new(blocks: LogBlocks, filter: &Filter) -> Self {
+        Self { blocks, addresses: filter.address.iter().copied().collect(), topics: filter.topics.clone(), skip: 0 }
+    }
+
+    /// Skips the given number of matching logs.
+    #[must_use]
+    pub const fn with_skip(mut self, skip: u64) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// Returns true if the log matches the query.
+    pub fn matches(&self, log: &Log) -> bool {
+        let in_blocks = match &self.blocks {
+            LogBlocks::Hash(hash) => log.block_hash == Some(*hash),
+            LogBlocks::Range(from, to) => log.block_number.is_some_and(|number| (*from..=*to).contains(&number)),
+            LogBlocks::Ranges(ranges) => {
+                log.block_number.is_some_and(|number| ranges.iter().any(|(from, to)| (*from..=*to).contains(&number)))
+            }
+        };
+        let topics = log.topics();
+
+        in_blocks
+            && (self.addresses.is_empty() || self.addresses.contains(&log.address()))
+            && self.topics.iter().enumerate().all(|(index, topic)| {
+                topic.is_empty() || topics.get(index).is_some_and(|log_topic| topic.matches(log_topic))
+            })
+    }
+
+    /// Returns the database filter of the query.
+    fn to_document(&self) -> Document {
+        let builder = EthDatabaseFilterBuilder::<filter::Log>::default();
+        let builder = match &self.blocks {
+            LogBlocks::Hash(hash) => builder.with_block_hash(hash),
+            LogBlocks::Range(from, to) => builder.with_block_number_range(*from, *to),
+            LogBlocks::Ranges(ranges) => builder.with_block_number_ranges(ranges),
+        };
+        builder.with_topics(&self.topics).with_addresses(&self.addresses).build()
+    }
+}
+
+/// Trait for interacting with a database that stores Ethereum typed logs.
+#[async_trait]
+pub trait EthereumLogStore {
+    /// Returns the logs matching the query, after the skipped ones and up to the limit.
+    async fn logs(&self, query: &LogQuery, limit: Option<u64>) -> Result<Vec<Log>, EthApiError>;
+    /// Returns the block number of the n-th log matching the query, in block order and
+    /// starting from 0. Returns None if less logs match the query.
+    async fn nth_log_block_number(&self, query: &LogQuery, n: u64) -> Result<Option<u64>, EthApiError>;
+}
+
+#[async_trait]
+impl EthereumLogStore for Database {
+    #[instrument(skip_all, name = "db::logs", err)]
+    async fn logs(&self, query: &LogQuery, limit: Option<u64>) -> Result<Vec<Log>, EthApiError> {
+        let sort = EthDatabaseFilterBuilder::<filter::Log>::default().block_order_sort();
+        let mut find_options = FindOpts::default().with_sort(sort).with_skip(query.skip);
+        if let Some(limit) = limit {
+            find_options = find_options.with_limit(limit);
+        }
+        Ok(self.get_and_map_to::<_, StoredLog>(query.to_document(), Some(find_options)).await?)
+    }
+
+    #[instrument(skip_all, name = "db::nth_log_block_number", err)]
+    async fn nth_log_block_number(&self, query: &LogQuery, n: u64) -> Result<Option<u64>, EthApiError> {
+        let sort = EthDatabaseFilterBuilder::<filter::Log>::default().block_order_sort();
+        let find_options = FindOpts::default().with_sort(sort).with_skip(n).with_limit(1);
+        let logs: Vec<StoredLog> = self.get(query.to_document(), find_options).await?;
+        Ok(logs.first().and_then(|log| log.block_number))
+    }
+}
+
+/// The storage of the Ethereum data indexed from Starknet, queried by the providers.
+pub trait EthereumStore:
+    EthereumBlockStore + EthereumTransactionStore + EthereumReceiptStore + EthereumLogStore + std::fmt::Debug + Send + Sync
+{
+}
+
+impl<T> EthereumStore for T where
+    T: EthereumBlockStore
+        + EthereumTransactionStore
+        + EthereumReceiptStore
+        + EthereumLogStore
+        + std::fmt::Debug
+        + Send
+        + Sync
+{
+}
+
+/// Trait for writing the Ethereum data indexed from Starknet. `MongoDB` and `PostgreSQL` are
+/// written by the Apibara indexer of the `indexer` directory, the in-memory storage by its
+/// owner, e.g. the tests.
+/// Transactions are written through [`EthereumTransactionStore::upsert_transaction`].
+#[async_trait]
+pub trait EthereumIndexWriter: Send + Sync {
+    /// Upserts the header, replacing the header with the same block number.
+    async fn upsert_header(&self, header: Header) -> Result<(), EthApiError>;
+    /// Upserts the receipt along with its logs, replacing the receipt of the same transaction
+    /// and its logs.
+    async fn upsert_receipt(&self, receipt: ExtendedTxReceipt) -> Result<(), EthApiError>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+    use crate::{
+        providers::eth_provider::database::memory::InMemoryDatabase,
+        test_utils::mongo::{StoreFuzzer, RANDOM_BYTES_SIZE},
+    };
     use arbitrary::Arbitrary;
     use rand::{self, Rng};
-    use starknet::core::types::Felt;
+    use starknet::core::types::{
+        ComputationResources, DataAvailabilityResources, DataResources, ExecutionResources, FeePayment, Felt, PriceUnit,
+    };
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_ethereum_transaction_store() {
-        // Initialize MongoDB fuzzer
-        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        // Initialize the fuzzer
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
 
         // Mock a database with 100 transactions, receipts, and headers
-        let database = mongo_fuzzer.mock_database(100).await;
+        let database = fuzzer.mock_in_memory_database(100).await;
 
         // Generate random bytes for test data
         let bytes: Vec<u8> = (0..RANDOM_BYTES_SIZE).map(|_| rand::random()).collect();
         let mut unstructured = arbitrary::Unstructured::new(&bytes);
 
         // Test fetching existing and non existing transactions by their hash
-        test_get_transaction(&database, &mongo_fuzzer, &mut unstructured).await;
+        test_get_transaction(&database, &fuzzer, &mut unstructured).await;
 
         // Test fetching transactions by their block hash
-        test_get_transactions_by_block_hash(&database, &mongo_fuzzer).await;
+        test_get_transactions_by_block_hash(&database, &fuzzer).await;
 
         // Test fetching transactions by their block number
-        test_get_transactions_by_block_number(&database, &mongo_fuzzer).await;
+        test_get_transactions_by_block_number(&database, &fuzzer).await;
 
         // Test upserting transactions into the database
         test_upsert_transactions(&mut unstructured, &database).await;
     }
 
     async fn test_get_transaction(
-        database: &Database,
-        mongo_fuzzer: &MongoFuzzer,
+        database: &InMemoryDatabase,
+        fuzzer: &StoreFuzzer,
         unstructured: &mut arbitrary::Unstructured<'_>,
     ) {
         // Fetch the first transaction from the mock database
-        let first_transaction = mongo_fuzzer.transactions.first().unwrap();
+        let first_transaction = fuzzer.transactions.first().unwrap();
 
         // Test retrieving an existing transaction by its hash
         assert_eq!(database.transaction(&first_transaction.hash).await.unwrap(), Some(first_transaction.into()));
@@ -236,12 +510,12 @@ mod tests {
         assert_eq!(database.transaction(&unstored_transaction.hash).await.unwrap(), None);
     }
 
-    async fn test_get_transactions_by_block_hash(database: &Database, mongo_fuzzer: &MongoFuzzer) {
+    async fn test_get_transactions_by_block_hash(database: &InMemoryDatabase, fuzzer: &StoreFuzzer) {
         // Fetch the first block hash from the mock database
-        let first_block_hash = mongo_fuzzer.headers.first().unwrap().hash;
+        let first_block_hash = fuzzer.headers.first().unwrap().hash;
 
         // Fetch transactions belonging to the first block hash
-        let transactions_first_block_hash = mongo_fuzzer
+        let transactions_first_block_hash = fuzzer
             .transactions
             .iter()
             .filter(|tx| tx.block_hash.unwrap() == first_block_hash)
@@ -252,12 +526,12 @@ mod tests {
         assert_eq!(database.transactions(first_block_hash.into()).await.unwrap(), transactions_first_block_hash);
     }
 
-    async fn test_get_transactions_by_block_number(database: &Database, mongo_fuzzer: &MongoFuzzer) {
+    async fn test_get_transactions_by_block_number(database: &InMemoryDatabase, fuzzer: &StoreFuzzer) {
         // Fetch the first block number from the mock database
-        let first_block_number = mongo_fuzzer.headers.first().unwrap().number;
+        let first_block_number = fuzzer.headers.first().unwrap().number;
 
         // Fetch transactions belonging to the first block number
-        let transactions_first_block_number = mongo_fuzzer
+        let transactions_first_block_number = fuzzer
             .transactions
             .iter()
             .filter(|tx| tx.block_number.unwrap() == first_block_number)
@@ -268,7 +542,7 @@ mod tests {
         assert_eq!(database.transactions(first_block_number.into()).await.unwrap(), transactions_first_block_number);
     }
 
-    async fn test_upsert_transactions(unstructured: &mut arbitrary::Unstructured<'_>, database: &Database) {
+    async fn test_upsert_transactions(unstructured: &mut arbitrary::Unstructured<'_>, database: &InMemoryDatabase) {
         // Generate and upsert a mock transaction into the database
         let mock_transaction = StoredTransaction::arbitrary(unstructured).unwrap();
         database.upsert_transaction(mock_transaction.clone().tx).await.unwrap();
@@ -279,28 +553,31 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_ethereum_block_store() {
-        // Initialize MongoDB fuzzer
-        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        // Initialize the fuzzer
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
 
         // Mock a database with 100 transactions, receipts, and headers
-        let database = mongo_fuzzer.mock_database(100).await;
+        let database = fuzzer.mock_in_memory_database(100).await;
 
         // Generate random bytes for test data
         let bytes: Vec<u8> = (0..RANDOM_BYTES_SIZE).map(|_| rand::random()).collect();
         let mut unstructured = arbitrary::Unstructured::new(&bytes);
 
         // Test fetching existing and none existing header via blockhash and blocknumber from database
-        test_get_header(&database, &mongo_fuzzer).await;
+        test_get_header(&database, &fuzzer).await;
 
         // Test fetching existing and none existing block via blockhash and blocknumber from database
-        test_get_blocks(&database, &mongo_fuzzer, &mut unstructured).await;
+        test_get_blocks(&database, &fuzzer, &mut unstructured).await;
 
         // Test fetching existing and none existing transaction counts via blockhash and blocknumber from database
-        test_get_transaction_count(&database, &mongo_fuzzer).await;
+        test_get_transaction_count(&database, &fuzzer).await;
+
+        // Test fetching the logs blooms of a block range from database
+        test_get_logs_blooms(&database, &fuzzer).await;
     }
 
-    async fn test_get_header(database: &Database, mongo_fuzzer: &MongoFuzzer) {
-        let header_block_hash = &mongo_fuzzer.headers.first().unwrap().header;
+    async fn test_get_header(database: &InMemoryDatabase, fuzzer: &StoreFuzzer) {
+        let header_block_hash = &fuzzer.headers.first().unwrap().header;
 
         // Test retrieving header by block hash
         assert_eq!(database.header(header_block_hash.hash.into()).await.unwrap().unwrap(), *header_block_hash);
@@ -316,13 +593,13 @@ mod tests {
         assert_eq!(database.header(rng.gen::<u64>().into()).await.unwrap(), None);
     }
 
-    async fn test_get_blocks(database: &Database, mongo_fuzzer: &MongoFuzzer, u: &mut arbitrary::Unstructured<'_>) {
-        let header = &mongo_fuzzer.headers.first().unwrap().header;
+    async fn test_get_blocks(database: &InMemoryDatabase, fuzzer: &StoreFuzzer, u: &mut arbitrary::Unstructured<'_>) {
+        let header = &fuzzer.headers.first().unwrap().header;
 
         let block_hash = header.hash;
 
         let block: ExtendedBlock = {
-            let transactions: Vec<ExtendedTransaction> = mongo_fuzzer
+            let transactions: Vec<ExtendedTransaction> = fuzzer
                 .transactions
                 .iter()
                 .filter_map(|stored_transaction| {
@@ -377,20 +654,37 @@ mod tests {
         let mut faulty_header = StoredHeader::arbitrary(u).unwrap();
         faulty_header.header.withdrawals_root = Some(rng.gen::<B256>());
 
-        let filter = EthDatabaseFilterBuilder::<filter::Header>::default().with_block_hash(&faulty_header.hash).build();
-
-        database.update_one(faulty_header.clone(), filter, true).await.expect("Failed to update header in database");
+        database.upsert_header(faulty_header.header.clone()).await.expect("Failed to upsert header");
 
         assert!(database.block(faulty_header.hash.into(), true).await.is_err());
     }
 
-    async fn test_get_transaction_count(database: &Database, mongo_fuzzer: &MongoFuzzer) {
-        let header_block_hash = &mongo_fuzzer.headers.first().unwrap().header;
+    async fn test_get_logs_blooms(database: &InMemoryDatabase, fuzzer: &StoreFuzzer) {
+        let header = &fuzzer.headers.last().unwrap().header;
+
+        // Test retrieving the logs bloom of a single block
+        assert_eq!(
+            database.logs_blooms(header.number, header.number, 10).await.unwrap(),
+            vec![HeaderBloom { number: header.number, logs_bloom: header.logs_bloom }]
+        );
+
+        // Test retrieving the logs blooms of the first blocks, sorted by block number
+        let max_block_number = fuzzer.max_block_number();
+        let blooms = database.logs_blooms(0, max_block_number, 2).await.unwrap();
+        assert_eq!(blooms.len(), 2);
+        assert!(blooms[0].number < blooms[1].number);
+
+        // Test retrieving the logs blooms of an empty range
+        assert!(database.logs_blooms(max_block_number + 1, max_block_number, 10).await.unwrap().is_empty());
+    }
+
+    async fn test_get_transaction_count(database: &InMemoryDatabase, fuzzer: &StoreFuzzer) {
+        let header_block_hash = &fuzzer.headers.first().unwrap().header;
 
         let first_block_hash = header_block_hash.hash;
 
         let transaction_count: U256 = U256::from(
-            mongo_fuzzer
+            fuzzer
                 .transactions
                 .iter()
                 .filter(|transaction| transaction.tx.block_hash.unwrap() == first_block_hash)
@@ -410,11 +704,11 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_upsert_transaction_hashes() {
-        // Initialize MongoDB fuzzer
-        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        // Initialize the fuzzer
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
 
         // Mock a database with sample data
-        let database = mongo_fuzzer.mock_database(1).await;
+        let database = fuzzer.mock_in_memory_database(1).await;
 
         // Generate random Ethereum and Starknet hashes
         let eth_hash = B256::random();
@@ -431,10 +725,8 @@ mod tests {
             .expect("Failed to upsert transaction hash mapping");
 
         // Retrieve the inserted transaction hash mapping and verify it matches the inserted values
-        let filter =
-            EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(&eth_hash).build();
-        let stored_mapping: Option<StoredEthStarknetTransactionHash> =
-            database.get_one(filter.clone(), None).await.expect("Failed to retrieve transaction hash mapping");
+        let stored_mapping =
+            database.transaction_hashes(&eth_hash).await.expect("Failed to retrieve transaction hash mapping");
 
         assert_eq!(
             stored_mapping,
@@ -453,8 +745,8 @@ mod tests {
             .expect("Failed to update transaction hash mapping");
 
         // Retrieve the updated transaction hash mapping and verify it matches the updated values
-        let updated_mapping: Option<StoredEthStarknetTransactionHash> =
-            database.get_one(filter, None).await.expect("Failed to retrieve updated transaction hash mapping");
+        let updated_mapping =
+            database.transaction_hashes(&eth_hash).await.expect("Failed to retrieve updated transaction hash mapping");
 
         assert_eq!(
             updated_mapping,
@@ -462,4 +754,58 @@ mod tests {
             "The transaction hash mapping was not updated correctly"
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_update_starknet_execution_cost() {
+        // Given
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
+        let database = fuzzer.mock_in_memory_database(1).await;
+
+        let eth_hash = B256::random();
+        let starknet_hash =
+            Felt::from_hex("0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb").unwrap();
+        database
+            .upsert_transaction_hashes(EthStarknetHashes { eth_hash, starknet_hash })
+            .await
+            .expect("Failed to upsert transaction hash mapping");
+
+        let execution_cost = StarknetExecutionCost {
+            starknet_hash,
+            actual_fee: FeePayment { amount: Felt::from(1_000_000u64), unit: PriceUnit::Wei },
+            execution_resources: ExecutionResources {
+                computation_resources: ComputationResources {
+                    steps: 50_000,
+                    memory_holes: Some(10),
+                    range_check_builtin_applications: Some(1_000),
+                    pedersen_builtin_applications: Some(20),
+                    poseidon_builtin_applications: Some(5),
+                    ec_op_builtin_applications: None,
+                    ecdsa_builtin_applications: None,
+                    bitwise_builtin_applications: Some(100),
+                    keccak_builtin_applications: Some(2),
+                    segment_arena_builtin: None,
+                },
+                data_resources: DataResources {
+                    data_availability: DataAvailabilityResources { l1_gas: 0, l1_data_gas: 128 },
+                },
+            },
+        };
+
+        // When
+        database
+            .update_starknet_execution_cost(&eth_hash, execution_cost.clone())
+            .await
+            .expect("Failed to update the Starknet execution cost");
+
+        // Then
+        let stored_mapping =
+            database.transaction_hashes(&eth_hash).await.expect("Failed to retrieve transaction hash mapping");
+        assert_eq!(
+            stored_mapping,
+            Some(StoredEthStarknetTransactionHash {
+                hashes: EthStarknetHashes { eth_hash, starknet_hash },
+                execution_cost: Some(execution_cost),
+            })
+        );
+    }
 }
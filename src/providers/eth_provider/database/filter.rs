@@ -194,6 +194,55 @@ impl<T: BlockFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
             BlockHashOrNumber::Number(number) => self.with_block_number(number),
         }
     }
+
+    /// Adds a filter on the block number range.
+    #[must_use]
+    pub fn with_block_number_range(mut self, from: u64, to: u64) -> Self {
+        let key = format!("{}.{}", self.target, self.target.block_number());
+        self.filter.insert(
+            key,
+            doc! {"$gte": format_hex(from, BLOCK_NUMBER_HEX_STRING_LEN), "$lte": format_hex(to, BLOCK_NUMBER_HEX_STRING_LEN)},
+        );
+        self
+    }
+
+    /// Returns the sort of the documents of the target by increasing block number.
+    pub fn block_number_sort(&self) -> Document {
+        doc! {format!("{}.{}", self.target, self.target.block_number()): 1}
+    }
+
+    /// Returns the sort of the documents of the target in block order, i.e. by increasing block
+    /// number, then in the order the indexer inserted them, which is the execution order of the
+    /// block. The transaction and log indexes can't be sorted on, the indexer stores them as
+    /// unpadded hex and decimal strings.
+    pub fn block_order_sort(&self) -> Document {
+        let mut sort = self.block_number_sort();
+        sort.insert("_id", 1);
+        sort
+    }
+
+    /// Adds a filter on any of the sorted and disjoint block number ranges, bounds included.
+    #[must_use]
+    pub fn with_block_number_ranges(mut self, ranges: &[(u64, u64)]) -> Self {
+        let key = format!("{}.{}", self.target, self.target.block_number());
+        let range = |(from, to): &(u64, u64)| {
+            doc! {"$gte": format_hex(from, BLOCK_NUMBER_HEX_STRING_LEN), "$lte": format_hex(to, BLOCK_NUMBER_HEX_STRING_LEN)}
+        };
+        match ranges {
+            [] => {
+                self.filter.insert(key, doc! {"$in": []});
+            }
+            [single] => {
+                self.filter.insert(key, range(single));
+            }
+            // Each range of the disjunction is scanned on the block number index.
+            ranges => {
+                let ranges = ranges.iter().map(|bounds| doc! {key.clone(): range(bounds)}).collect::<Vec<_>>();
+                self.filter.insert("$or", ranges);
+            }
+        }
+        self
+    }
 }
 
 impl<T: TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
@@ -205,6 +254,17 @@ impl<T: TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
         self
     }
 
+    /// Adds a filter on any of the transaction hashes.
+    #[must_use]
+    pub fn with_tx_hashes(mut self, hashes: &[B256]) -> Self {
+        let key = format!("{}.{}", self.target, self.target.transaction_hash());
+        self.filter.insert(
+            key,
+            doc! {"$in": hashes.iter().map(|hash| format_hex(hash, BLOCK_NUMBER_HEX_STRING_LEN)).collect::<Vec<_>>()},
+        );
+        self
+    }
+
     /// Adds a filter on the transaction index in the block.
     #[must_use]
     pub fn with_tx_index(mut self, index: &Index) -> Self {
@@ -230,17 +290,6 @@ impl<T: LogFiltering + BlockFiltering + Display + Default> EthDatabaseFilterBuil
         self
     }
 
-    /// Adds a filter on the block number range.
-    #[must_use]
-    pub fn with_block_number_range(mut self, from: u64, to: u64) -> Self {
-        let key = format!("{}.{}", self.target, self.target.block_number());
-        self.filter.insert(
-            key,
-            doc! {"$gte": format_hex(from, BLOCK_NUMBER_HEX_STRING_LEN), "$lte": format_hex(to, BLOCK_NUMBER_HEX_STRING_LEN)},
-        );
-        self
-    }
-
     /// Adds a filter on the topics.
     #[must_use]
     pub fn with_topics(mut self, topics: &[Topic; 4]) -> Self {
@@ -303,6 +352,24 @@ mod tests {
     use alloy_primitives::b256;
     use alloy_rpc_types::FilterSet;
 
+    #[test]
+    fn test_block_number_sort() {
+        // When
+        let sort = EthDatabaseFilterBuilder::<Log>::default().block_number_sort();
+
+        // Then
+        assert_eq!(sort, doc! {"log.blockNumber": 1});
+    }
+
+    #[test]
+    fn test_block_order_sort() {
+        // When
+        let sort = EthDatabaseFilterBuilder::<Log>::default().block_order_sort();
+
+        // Then
+        assert_eq!(sort, doc! {"log.blockNumber": 1, "_id": 1});
+    }
+
     #[test]
     fn test_into_filter_with_padding() {
         assert_eq!(into_filter::<u64>("test_key", &0x1234, 10), doc! {"test_key": "0x0000001234"});
@@ -395,6 +462,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transaction_hashes_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Transaction>::default();
+
+        // When
+        let filter = builder.with_tx_hashes(&[B256::left_padding_from(&[1]), B256::left_padding_from(&[2])]).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "tx.hash": {
+                    "$in": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000001",
+                        "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    ]
+                }
+            }
+        );
+    }
+
     #[test]
     fn test_receipt_block_number_filter() {
         // Given
@@ -446,6 +535,18 @@ mod tests {
         assert_eq!(filter, doc! {"header.number": "0x0000000000000001"});
     }
 
+    #[test]
+    fn test_header_block_number_range_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Header>::default();
+
+        // When
+        let filter = builder.with_block_number_range(1, 10).build();
+
+        // Then
+        assert_eq!(filter, doc! {"header.number": {"$gte": "0x0000000000000001", "$lte": "0x000000000000000a"}});
+    }
+
     #[test]
     fn test_log_block_hash_filter() {
         // Given
@@ -473,6 +574,36 @@ mod tests {
         assert_eq!(filter, doc! {"log.blockNumber": {"$gte": "0x0000000000000001", "$lte": "0x000000000000000a"}});
     }
 
+    #[test]
+    fn test_log_block_number_ranges_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Log>::default();
+
+        // When
+        let filter = builder.with_block_number_ranges(&[(1, 1), (4, 10)]).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {"$or": [
+                {"log.blockNumber": {"$gte": "0x0000000000000001", "$lte": "0x0000000000000001"}},
+                {"log.blockNumber": {"$gte": "0x0000000000000004", "$lte": "0x000000000000000a"}}
+            ]}
+        );
+    }
+
+    #[test]
+    fn test_log_single_block_number_range_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Log>::default();
+
+        // When
+        let filter = builder.with_block_number_ranges(&[(1, 10)]).build();
+
+        // Then
+        assert_eq!(filter, doc! {"log.blockNumber": {"$gte": "0x0000000000000001", "$lte": "0x000000000000000a"}});
+    }
+
     #[test]
     fn test_log_empty_addresses_filter() {
         // Given
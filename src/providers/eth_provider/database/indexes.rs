@@ -0,0 +1,170 @@
+use super::{
+    filter::{self, BlockFiltering, LogFiltering, TransactionFiltering},
+    types::{
+        header::StoredHeader,
+        log::StoredLog,
+        receipt::StoredTransactionReceipt,
+        transaction::{StoredEthStarknetTransactionHash, StoredTransaction},
+    },
+    CollectionName, Database, DatabaseResult,
+};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    error::ErrorKind,
+    IndexModel,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Display,
+};
+use tracing::instrument;
+
+/// Error code returned by `MongoDB` when a collection doesn't exist.
+const NAMESPACE_NOT_FOUND_CODE: i32 = 26;
+
+/// An ascending single field index required by the queries of the RPC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredIndex {
+    /// The name of the indexed collection.
+    pub collection: &'static str,
+    /// The indexed field.
+    pub field: String,
+}
+
+impl RequiredIndex {
+    fn new<T: CollectionName>(field: String) -> Self {
+        Self { collection: T::collection_name(), field }
+    }
+
+    /// Returns true if the index with the given keys can serve the queries on the field,
+    /// i.e. if the field is the prefix of its keys.
+    fn is_served_by(&self, keys: &Document) -> bool {
+        keys.keys().next().is_some_and(|key| *key == self.field)
+    }
+}
+
+impl Display for RequiredIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.collection, self.field)
+    }
+}
+
+/// Returns the key of the field of the filter target, as queried by [`filter::EthDatabaseFilterBuilder`].
+fn key(target: impl Display, field: &str) -> String {
+    format!("{target}.{field}")
+}
+
+/// Returns the indexes required by the queries of the RPC, for each collection it queries.
+pub fn required_indexes() -> Vec<RequiredIndex> {
+    let (header, tx, receipt, log, hashes) =
+        (filter::Header, filter::Transaction, filter::Receipt, filter::Log, filter::EthStarknetTransactionHash);
+
+    vec![
+        RequiredIndex::new::<StoredHeader>(key(&header, header.block_number())),
+        RequiredIndex::new::<StoredHeader>(key(&header, header.block_hash())),
+        RequiredIndex::new::<StoredTransaction>(key(&tx, tx.transaction_hash())),
+        RequiredIndex::new::<StoredTransaction>(key(&tx, tx.block_number())),
+        RequiredIndex::new::<StoredTransaction>(key(&tx, tx.block_hash())),
+        RequiredIndex::new::<StoredTransactionReceipt>(key(&receipt, receipt.transaction_hash())),
+        RequiredIndex::new::<StoredTransactionReceipt>(key(&receipt, receipt.block_number())),
+        RequiredIndex::new::<StoredTransactionReceipt>(key(&receipt, receipt.block_hash())),
+        RequiredIndex::new::<StoredLog>(key(&log, log.block_number())),
+        RequiredIndex::new::<StoredLog>(key(&log, log.block_hash())),
+        RequiredIndex::new::<StoredLog>(key(&log, log.address())),
+        // Serves the queries on the event signature, the first topic of the logs.
+        RequiredIndex::new::<StoredLog>(key(&log, "topics.0")),
+        RequiredIndex::new::<StoredEthStarknetTransactionHash>(key(&hashes, hashes.transaction_hash())),
+    ]
+}
+
+impl Database {
+    /// Returns the required indexes which are missing from the database.
+    #[instrument(skip_all, name = "db::missing_indexes", err)]
+    pub async fn missing_indexes(&self) -> DatabaseResult<Vec<RequiredIndex>> {
+        // The indexes of each collection are listed once.
+        let mut collections_keys = HashMap::new();
+        let mut missing = Vec::new();
+        for index in required_indexes() {
+            let keys = match collections_keys.entry(index.collection) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(self.index_keys(index.collection).await?),
+            };
+            if !keys.iter().any(|keys| index.is_served_by(keys)) {
+                missing.push(index);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Creates the required indexes which are missing from the database and returns them.
+    #[instrument(skip_all, name = "db::ensure_indexes", err)]
+    pub async fn ensure_indexes(&self) -> DatabaseResult<Vec<RequiredIndex>> {
+        let missing = self.missing_indexes().await?;
+        for index in &missing {
+            let model = IndexModel::builder().keys(doc! {&index.field: 1}).build();
+            self.inner().collection::<Document>(index.collection).create_index(model).await?;
+            tracing::info!(%index, "created database index");
+        }
+        Ok(missing)
+    }
+
+    /// Returns the keys of the indexes of the collection. A missing collection has no index.
+    async fn index_keys(&self, collection: &str) -> DatabaseResult<Vec<Document>> {
+        match self.inner().collection::<Document>(collection).list_indexes().await {
+            Ok(cursor) => Ok(cursor.map_ok(|index| index.keys).try_collect().await?),
+            Err(err) if matches!(*err.kind, ErrorKind::Command(ref err) if err.code == NAMESPACE_NOT_FOUND_CODE) => {
+                Ok(Vec::new())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[test]
+    fn test_required_indexes_keys() {
+        // When
+        let indexes = required_indexes().into_iter().map(|index| index.to_string()).collect::<Vec<_>>();
+
+        // Then
+        assert!(indexes.contains(&"headers.header.number".to_string()));
+        assert!(indexes.contains(&"transactions.tx.blockNumber".to_string()));
+        assert!(indexes.contains(&"receipts.receipt.transactionHash".to_string()));
+        assert!(indexes.contains(&"logs.log.address".to_string()));
+        assert!(indexes.contains(&"logs.log.topics.0".to_string()));
+        assert!(indexes.contains(&"transaction_hashes.hashes.eth_hash".to_string()));
+    }
+
+    #[test]
+    fn test_required_index_is_served_by() {
+        // Given
+        let index = RequiredIndex { collection: "headers", field: "header.number".to_string() };
+
+        // When & Then
+        assert!(index.is_served_by(&doc! {"header.number": 1}));
+        assert!(index.is_served_by(&doc! {"header.number": -1, "header.hash": 1}));
+        assert!(!index.is_served_by(&doc! {"header.hash": 1, "header.number": 1}));
+        assert!(!index.is_served_by(&doc! {"_id": 1}));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ensure_indexes() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        assert_eq!(database.missing_indexes().await.unwrap(), required_indexes());
+
+        // When
+        let created = database.ensure_indexes().await.unwrap();
+
+        // Then
+        assert_eq!(created, required_indexes());
+        assert!(database.missing_indexes().await.unwrap().is_empty());
+        assert!(database.ensure_indexes().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+use super::{
+    ethereum::{
+        build_block, EthereumBlockStore, EthereumIndexWriter, EthereumLogStore, EthereumReceiptStore,
+        EthereumTransactionStore, LogQuery,
+    },
+    types::{
+        header::{ExtendedBlock, HeaderBloom},
+        receipt::ExtendedTxReceipt,
+        transaction::{
+            EthStarknetHashes, ExtendedTransaction, StarknetExecutionCost, StoredEthStarknetTransactionHash,
+        },
+    },
+};
+use crate::providers::eth_provider::error::EthApiError;
+use alloy_primitives::{B256, U256};
+use alloy_rpc_types::{BlockHashOrNumber, Header, Index, Log};
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// The data held by the [`InMemoryDatabase`]. Transactions, receipts and logs are kept in
+/// insertion order, as returned by `MongoDB`.
+#[derive(Debug, Default)]
+struct Collections {
+    headers: BTreeMap<u64, Header>,
+    header_numbers: HashMap<B256, u64>,
+    transactions: Vec<ExtendedTransaction>,
+    receipts: Vec<ExtendedTxReceipt>,
+    logs: Vec<Log>,
+    transaction_hashes: HashMap<B256, StoredEthStarknetTransactionHash>,
+}
+
+/// An in-memory Ethereum storage, which allows running the RPC without `MongoDB`, e.g. for
+/// tests and local devnets. It is written through the [`EthereumIndexWriter`] and the data is
+/// lost when the process exits.
+#[derive(Debug, Default)]
+pub struct InMemoryDatabase(RwLock<Collections>);
+
+impl InMemoryDatabase {
+    /// Returns a new empty in-memory database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Collections> {
+        // The collections are never left in an inconsistent state, hence a poisoned lock is safe to use.
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Collections> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl EthereumIndexWriter for InMemoryDatabase {
+    async fn upsert_header(&self, header: Header) -> Result<(), EthApiError> {
+        let mut collections = self.write();
+        let (hash, number) = (header.hash, header.number);
+        if let Some(replaced) = collections.headers.insert(number, header) {
+            collections.header_numbers.remove(&replaced.hash);
+        }
+        collections.header_numbers.insert(hash, number);
+        Ok(())
+    }
+
+    async fn upsert_receipt(&self, receipt: ExtendedTxReceipt) -> Result<(), EthApiError> {
+        let mut collections = self.write();
+        let hash = receipt.transaction_hash;
+        collections.logs.retain(|log| log.transaction_hash != Some(hash));
+        collections.logs.extend(receipt.inner.inner.logs().iter().cloned());
+        match collections.receipts.iter_mut().find(|stored| stored.transaction_hash == hash) {
+            Some(stored) => *stored = receipt,
+            None => collections.receipts.push(receipt),
+        }
+        Ok(())
+    }
+}
+
+/// Returns the logs matching the query in block order.
+fn sorted_logs<'a>(logs: &'a [Log], query: &LogQuery) -> Vec<&'a Log> {
+    let mut logs = logs.iter().filter(|log| query.matches(log)).collect::<Vec<_>>();
+    logs.sort_by_key(|log| (log.block_number, log.transaction_index, log.log_index));
+    logs
+}
+
+/// Returns true if the block hash or number is the one of the given block.
+fn in_block(block_hash_or_number: BlockHashOrNumber, block_hash: Option<B256>, block_number: Option<u64>) -> bool {
+    match block_hash_or_number {
+        BlockHashOrNumber::Hash(hash) => block_hash == Some(hash),
+        BlockHashOrNumber::Number(number) => block_number == Some(number),
+    }
+}
+
+#[async_trait]
+impl EthereumTransactionStore for InMemoryDatabase {
+    async fn transaction(&self, hash: &B256) -> Result<Option<ExtendedTransaction>, EthApiError> {
+        Ok(self.read().transactions.iter().find(|tx| tx.hash == *hash).cloned())
+    }
+
+    async fn transactions_by_hashes(&self, hashes: &[B256]) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        Ok(self.read().transactions.iter().filter(|tx| hashes.contains(&tx.hash)).cloned().collect())
+    }
+
+    async fn transactions(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+    ) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        Ok(self
+            .read()
+            .transactions
+            .iter()
+            .filter(|tx| in_block(block_hash_or_number, tx.block_hash, tx.block_number))
+            .cloned()
+            .collect())
+    }
+
+    async fn transaction_by_block_and_index(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        index: Index,
+    ) -> Result<Option<ExtendedTransaction>, EthApiError> {
+        let index = usize::from(index) as u64;
+        Ok(self
+            .read()
+            .transactions
+            .iter()
+            .find(|tx| {
+                in_block(block_hash_or_number, tx.block_hash, tx.block_number) && tx.transaction_index == Some(index)
+            })
+            .cloned())
+    }
+
+    async fn upsert_transaction(&self, transaction: ExtendedTransaction) -> Result<(), EthApiError> {
+        let mut collections = self.write();
+        match collections.transactions.iter_mut().find(|tx| tx.hash == transaction.hash) {
+            Some(stored) => *stored = transaction,
+            None => collections.transactions.push(transaction),
+        }
+        Ok(())
+    }
+
+    async fn upsert_transaction_hashes(&self, transaction_hashes: EthStarknetHashes) -> Result<(), EthApiError> {
+        self.write()
+            .transaction_hashes
+            .insert(transaction_hashes.eth_hash, StoredEthStarknetTransactionHash::from(transaction_hashes));
+        Ok(())
+    }
+
+    async fn transaction_hashes(
+        &self,
+        eth_hash: &B256,
+    ) -> Result<Option<StoredEthStarknetTransactionHash>, EthApiError> {
+        Ok(self.read().transaction_hashes.get(eth_hash).cloned())
+    }
+
+    async fn update_starknet_execution_cost(
+        &self,
+        eth_hash: &B256,
+        execution_cost: StarknetExecutionCost,
+    ) -> Result<(), EthApiError> {
+        if let Some(mapping) = self.write().transaction_hashes.get_mut(eth_hash) {
+            mapping.execution_cost = Some(execution_cost);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EthereumBlockStore for InMemoryDatabase {
+    async fn latest_header(&self) -> Result<Option<Header>, EthApiError> {
+        Ok(self.read().headers.values().next_back().cloned())
+    }
+
+    async fn header(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<Header>, EthApiError> {
+        let collections = self.read();
+        Ok(match block_hash_or_number {
+            BlockHashOrNumber::Hash(hash) => {
+                collections.header_numbers.get(&hash).and_then(|number| collections.headers.get(number)).cloned()
+            }
+            BlockHashOrNumber::Number(number) => collections.headers.get(&number).cloned(),
+        })
+    }
+
+    async fn headers(&self, from: u64, to: u64) -> Result<Vec<Header>, EthApiError> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        Ok(self.read().headers.range(from..=to).map(|(_, header)| header.clone()).collect())
+    }
+
+    async fn block(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        full: bool,
+    ) -> Result<Option<ExtendedBlock>, EthApiError> {
+        let Some(header) = self.header(block_hash_or_number).await? else {
+            return Ok(None);
+        };
+        let transactions = self.transactions(block_hash_or_number).await?;
+        build_block(header, transactions, full).map(Some)
+    }
+
+    async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError> {
+        if !self.block_exists(block_hash_or_number).await? {
+            return Ok(None);
+        }
+        Ok(Some(U256::from(self.transactions(block_hash_or_number).await?.len())))
+    }
+
+    async fn logs_blooms(&self, from: u64, to: u64, limit: u64) -> Result<Vec<HeaderBloom>, EthApiError> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .read()
+            .headers
+            .range(from..=to)
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .map(|(number, header)| HeaderBloom { number: *number, logs_bloom: header.logs_bloom })
+            .collect())
+    }
+
+    /// The writer can rewrite any block without marking a pending block.
+    fn is_cacheable(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait]
+impl EthereumReceiptStore for InMemoryDatabase {
+    async fn receipt(&self, hash: &B256) -> Result<Option<ExtendedTxReceipt>, EthApiError> {
+        Ok(self.read().receipts.iter().find(|receipt| receipt.transaction_hash == *hash).cloned())
+    }
+
+    async fn receipts(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        Ok(self
+            .read()
+            .receipts
+            .iter()
+            .filter(|receipt| in_block(block_hash_or_number, receipt.block_hash, receipt.block_number))
+            .cloned()
+            .collect())
+    }
+
+    async fn receipts_by_block_range(&self, from: u64, to: u64) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        Ok(self
+            .read()
+            .receipts
+            .iter()
+            .filter(|receipt| receipt.block_number.is_some_and(|number| (from..=to).contains(&number)))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EthereumLogStore for InMemoryDatabase {
+    async fn logs(&self, query: &LogQuery, limit: Option<u64>) -> Result<Vec<Log>, EthApiError> {
+        let limit = limit.map_or(usize::MAX, |limit| usize::try_from(limit).unwrap_or(usize::MAX));
+        let skip = usize::try_from(query.skip).unwrap_or(usize::MAX);
+        Ok(sorted_logs(&self.read().logs, query).into_iter().skip(skip).take(limit).cloned().collect())
+    }
+
+    async fn nth_log_block_number(&self, query: &LogQuery, n: u64) -> Result<Option<u64>, EthApiError> {
+        let collections = self.read();
+        let logs = sorted_logs(&collections.logs, query);
+        Ok(usize::try_from(n).ok().and_then(|n| logs.get(n)).and_then(|log| log.block_number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::eth_provider::database::{
+        ethereum::LogBlocks,
+        types::{log::StoredLog, receipt::StoredTransactionReceipt, transaction::StoredTransaction},
+    };
+    use alloy_primitives::Address;
+    use alloy_rpc_types::Filter;
+    use arbitrary::Arbitrary;
+
+    fn unstructured_bytes() -> Vec<u8> {
+        (0..1024).map(|_| rand::random()).collect()
+    }
+
+    fn log(block_number: u64, address: Address, topic: B256) -> Log {
+        let bytes = unstructured_bytes();
+        let mut log: Log = StoredLog::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap().into();
+        log.block_number = Some(block_number);
+        log.inner.address = address;
+        log.inner.data = alloy_primitives::LogData::new_unchecked(vec![topic], Default::default());
+        log
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_block_store() {
+        // Given
+        let database = InMemoryDatabase::new();
+        for number in [3u8, 1, 2] {
+            database
+                .upsert_header(Header {
+                    number: number.into(),
+                    hash: B256::with_last_byte(number),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        // When & Then
+        assert_eq!(database.latest_header().await.unwrap().unwrap().number, 3);
+        assert_eq!(database.header(B256::with_last_byte(2).into()).await.unwrap().unwrap().number, 2);
+        assert!(database.header(4.into()).await.unwrap().is_none());
+        assert_eq!(
+            database.headers(2, 10).await.unwrap().iter().map(|header| header.number).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(
+            database.logs_blooms(1, 3, 2).await.unwrap().iter().map(|bloom| bloom.number).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(database.transaction_count(1.into()).await.unwrap(), Some(U256::ZERO));
+        assert_eq!(database.transaction_count(4.into()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_upsert_header_replaces_hash() {
+        // Given
+        let database = InMemoryDatabase::new();
+        let header = Header { number: 1, hash: B256::with_last_byte(1), ..Default::default() };
+        database.upsert_header(header).await.unwrap();
+
+        // When
+        database
+            .upsert_header(Header { number: 1, hash: B256::with_last_byte(2), ..Default::default() })
+            .await
+            .unwrap();
+
+        // Then
+        assert!(database.header(B256::with_last_byte(1).into()).await.unwrap().is_none());
+        assert_eq!(database.header(B256::with_last_byte(2).into()).await.unwrap().unwrap().number, 1);
+        assert_eq!(database.read().header_numbers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_transaction_store() {
+        // Given
+        let database = InMemoryDatabase::new();
+        let bytes = unstructured_bytes();
+        let mut transaction: ExtendedTransaction =
+            StoredTransaction::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap().into();
+        transaction.block_number = Some(1);
+        transaction.transaction_index = Some(0);
+
+        // When
+        database.upsert_transaction(transaction.clone()).await.unwrap();
+        database.upsert_transaction(transaction.clone()).await.unwrap();
+
+        // Then
+        assert_eq!(database.transaction(&transaction.hash).await.unwrap(), Some(transaction.clone()));
+        assert_eq!(database.transactions(1.into()).await.unwrap(), vec![transaction.clone()]);
+        assert_eq!(
+            database.transactions_by_hashes(&[transaction.hash, B256::ZERO]).await.unwrap(),
+            vec![transaction.clone()]
+        );
+        assert_eq!(database.transaction_by_block_and_index(1.into(), 0.into()).await.unwrap(), Some(transaction));
+        assert!(database.transaction_by_block_and_index(1.into(), 1.into()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_receipt_store() {
+        // Given
+        let database = InMemoryDatabase::new();
+        let bytes = unstructured_bytes();
+        let mut receipt: ExtendedTxReceipt =
+            StoredTransactionReceipt::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap().into();
+        receipt.block_number = Some(5);
+
+        // When
+        database.upsert_receipt(receipt.clone()).await.unwrap();
+        database.upsert_receipt(receipt.clone()).await.unwrap();
+
+        // Then
+        assert_eq!(database.receipt(&receipt.transaction_hash).await.unwrap(), Some(receipt.clone()));
+        assert_eq!(database.receipts(5.into()).await.unwrap(), vec![receipt.clone()]);
+        assert_eq!(database.receipts_by_block_range(0, 4).await.unwrap(), vec![]);
+        assert_eq!(database.read().logs.len(), receipt.inner.inner.logs().len());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_log_store() {
+        // Given
+        let database = InMemoryDatabase::new();
+        let (address, topic) = (Address::with_last_byte(1), B256::with_last_byte(1));
+        database.write().logs = vec![
+            log(3, address, topic),
+            log(1, address, topic),
+            log(2, Address::ZERO, topic),
+            log(4, address, B256::ZERO),
+        ];
+        let filter = Filter::new().address(address).event_signature(topic);
+
+        // When
+        let query = LogQuery::new(LogBlocks::Range(0, 10), &filter);
+
+        // Then
+        let logs = database.logs(&query, None).await.unwrap();
+        assert_eq!(logs.iter().map(|log| log.block_number.unwrap()).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(database.logs(&query, Some(1)).await.unwrap().len(), 1);
+        assert_eq!(database.nth_log_block_number(&query, 0).await.unwrap(), Some(1));
+        assert_eq!(database.nth_log_block_number(&query, 2).await.unwrap(), None);
+
+        let query = LogQuery::new(LogBlocks::Ranges(vec![(0, 0), (2, 10)]), &filter);
+        assert_eq!(database.nth_log_block_number(&query, 0).await.unwrap(), Some(3));
+    }
+}
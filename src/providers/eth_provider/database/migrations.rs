@@ -0,0 +1,212 @@
+use super::{CollectionName, Database, DatabaseResult};
+use async_trait::async_trait;
+use eyre::eyre;
+use mongodb::bson::doc;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// A versioned migration of the database schema, rolling out a change of the `Stored*` types
+/// or of the indexes.
+///
+/// Migrations are applied in version order, starting at version 1, and each version is
+/// applied once: the applied versions are recorded in the migrations collection.
+#[async_trait]
+pub trait Migration: std::fmt::Debug + Send + Sync {
+    /// Returns the version of the database schema once the migration is applied.
+    fn version(&self) -> u32;
+    /// Returns a short description of the migration.
+    fn description(&self) -> &'static str;
+    /// Applies the migration to the database.
+    async fn up(&self, database: &Database) -> DatabaseResult<()>;
+}
+
+/// A migration applied to the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredMigration {
+    /// The version of the database schema once the migration is applied.
+    pub version: u32,
+    /// The description of the migration.
+    pub description: String,
+}
+
+impl CollectionName for StoredMigration {
+    fn collection_name() -> &'static str {
+        "migrations"
+    }
+}
+
+/// Creates the indexes required by the queries of the RPC.
+#[derive(Debug)]
+struct CreateIndexes;
+
+#[async_trait]
+impl Migration for CreateIndexes {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn description(&self) -> &'static str {
+        "create the indexes required by the RPC queries"
+    }
+
+    async fn up(&self, database: &Database) -> DatabaseResult<()> {
+        database.ensure_indexes().await?;
+        Ok(())
+    }
+}
+
+/// Creates the index on the first topic of the logs, added to the required indexes after
+/// [`CreateIndexes`].
+#[derive(Debug)]
+struct CreateLogTopicsIndex;
+
+#[async_trait]
+impl Migration for CreateLogTopicsIndex {
+    fn version(&self) -> u32 {
+        2
+    }
+
+    fn description(&self) -> &'static str {
+        "create the index on the first topic of the logs"
+    }
+
+    async fn up(&self, database: &Database) -> DatabaseResult<()> {
+        database.ensure_indexes().await?;
+        Ok(())
+    }
+}
+
+/// Returns the migrations of the database schema, in version order.
+pub fn migrations() -> Vec<Box<dyn Migration>> {
+    vec![Box::new(CreateIndexes), Box::new(CreateLogTopicsIndex)]
+}
+
+/// Checks that the versions of the migrations are contiguous and start at 1.
+fn validate_migrations(migrations: &[Box<dyn Migration>]) -> DatabaseResult<()> {
+    for (expected, migration) in (1..).zip(migrations) {
+        if migration.version() != expected {
+            return Err(mongodb::error::Error::custom(eyre!(
+                "invalid migration version {} ({}), expected {expected}",
+                migration.version(),
+                migration.description()
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Returns the version of the database schema, i.e. the version of the last applied
+    /// migration. Returns 0 if no migration was applied.
+    #[instrument(skip_all, name = "db::schema_version", err)]
+    pub async fn schema_version(&self) -> DatabaseResult<u32> {
+        Ok(self.get_one::<StoredMigration>(None, doc! {"version": -1}).await?.map_or(0, |migration| migration.version))
+    }
+
+    /// Returns the migrations which aren't applied to the database yet.
+    ///
+    /// Fails if the database schema is more recent than the last migration, which happens
+    /// when the database was migrated by a more recent version of the RPC.
+    pub async fn pending_migrations<'a>(
+        &self,
+        migrations: &'a [Box<dyn Migration>],
+    ) -> DatabaseResult<Vec<&'a dyn Migration>> {
+        validate_migrations(migrations)?;
+
+        let schema_version = self.schema_version().await?;
+        let latest_version = migrations.last().map_or(0, |migration| migration.version());
+        if schema_version > latest_version {
+            return Err(mongodb::error::Error::custom(eyre!(
+                "database schema version {schema_version} is more recent than the latest supported version {latest_version}"
+            ))
+            .into());
+        }
+
+        Ok(migrations.iter().filter(|migration| migration.version() > schema_version).map(AsRef::as_ref).collect())
+    }
+
+    /// Applies the pending migrations in version order and returns their versions.
+    #[instrument(skip_all, name = "db::migrate", err)]
+    pub async fn migrate(&self, migrations: &[Box<dyn Migration>]) -> DatabaseResult<Vec<u32>> {
+        let mut applied = Vec::new();
+        for migration in self.pending_migrations(migrations).await? {
+            let (version, description) = (migration.version(), migration.description());
+            tracing::info!(version, description, "applying database migration");
+
+            migration.up(self).await?;
+            self.update_one(
+                StoredMigration { version, description: description.to_string() },
+                doc! {"version": i64::from(version)},
+                true,
+            )
+            .await?;
+
+            applied.push(version);
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[derive(Debug)]
+    struct NoopMigration(u32);
+
+    #[async_trait]
+    impl Migration for NoopMigration {
+        fn version(&self) -> u32 {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "noop"
+        }
+
+        async fn up(&self, _database: &Database) -> DatabaseResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_validate_migrations() {
+        // Given
+        let valid: Vec<Box<dyn Migration>> = vec![Box::new(NoopMigration(1)), Box::new(NoopMigration(2))];
+        let gap: Vec<Box<dyn Migration>> = vec![Box::new(NoopMigration(1)), Box::new(NoopMigration(3))];
+        let unordered: Vec<Box<dyn Migration>> = vec![Box::new(NoopMigration(2)), Box::new(NoopMigration(1))];
+
+        // When & Then
+        assert!(validate_migrations(&migrations()).is_ok());
+        assert!(validate_migrations(&valid).is_ok());
+        assert!(validate_migrations(&gap).is_err());
+        assert!(validate_migrations(&unordered).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_migrate() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let migrations = migrations();
+
+        // When
+        let applied = database.migrate(&migrations).await.unwrap();
+
+        // Then
+        assert_eq!(applied, vec![1, 2]);
+        assert_eq!(database.schema_version().await.unwrap(), 2);
+        assert!(database.missing_indexes().await.unwrap().is_empty());
+
+        // Migrating again doesn't apply anything.
+        assert!(database.migrate(&migrations).await.unwrap().is_empty());
+
+        // A schema more recent than the migrations is rejected.
+        let newer: Vec<Box<dyn Migration>> =
+            vec![Box::new(NoopMigration(1)), Box::new(NoopMigration(2)), Box::new(NoopMigration(3))];
+        database.migrate(&newer).await.unwrap();
+        assert!(database.pending_migrations(&migrations).await.is_err());
+    }
+}
@@ -1,14 +1,23 @@
 pub mod ethereum;
 pub mod filter;
+pub mod indexes;
+pub mod memory;
+pub mod migrations;
+pub mod postgres;
 pub mod state;
 pub mod types;
 
 use super::error::KakarotError;
-use crate::providers::eth_provider::database::types::{
-    header::StoredHeader,
-    log::StoredLog,
-    receipt::StoredTransactionReceipt,
-    transaction::{StoredEthStarknetTransactionHash, StoredTransaction},
+use crate::{
+    prometheus_handler::{
+        register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, HISTOGRAM_BUCKETS, U64,
+    },
+    providers::eth_provider::database::types::{
+        header::{StoredHeader, StoredHeaderBloom},
+        log::StoredLog,
+        receipt::StoredTransactionReceipt,
+        transaction::{StoredEthStarknetTransactionHash, StoredTransaction},
+    },
 };
 use futures::TryStreamExt;
 use itertools::Itertools;
@@ -18,9 +27,62 @@ use mongodb::{
     Collection, Database as MongoDatabase,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use std::{future::Future, sync::LazyLock, time::Instant};
 
 type DatabaseResult<T> = eyre::Result<T, KakarotError>;
 
+/// The metrics of the `MongoDB` queries, shared by all the databases of the process.
+pub static DATABASE_METRICS: LazyLock<DatabaseMetrics> =
+    LazyLock::new(|| DatabaseMetrics::new().expect("failed to create the database metrics"));
+
+/// Metrics of the `MongoDB` queries, labelled by collection and operation.
+#[derive(Debug, Clone)]
+pub struct DatabaseMetrics {
+    /// Histogram over the queries execution times.
+    queries_time: HistogramVec,
+    /// Number of failed queries.
+    queries_failed: CounterVec<U64>,
+}
+
+impl DatabaseMetrics {
+    fn new() -> Result<Self, PrometheusError> {
+        Ok(Self {
+            queries_time: HistogramVec::new(
+                HistogramOpts::new("mongo_queries_time", "Total time [μs] of the MongoDB queries")
+                    .buckets(HISTOGRAM_BUCKETS.to_vec()),
+                &["collection", "operation"],
+            )?,
+            queries_failed: CounterVec::new(
+                Opts::new("mongo_queries_failed", "Number of failed MongoDB queries"),
+                &["collection", "operation"],
+            )?,
+        })
+    }
+
+    /// Registers the metrics in the given registry.
+    pub fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+        register(self.queries_time.clone(), registry)?;
+        register(self.queries_failed.clone(), registry)?;
+        Ok(())
+    }
+
+    /// Runs the query, recording its execution time and its failure.
+    pub async fn observe<T>(
+        &self,
+        collection: &str,
+        operation: &str,
+        query: impl Future<Output = DatabaseResult<T>>,
+    ) -> DatabaseResult<T> {
+        let now = Instant::now();
+        let result = query.await;
+        self.queries_time.with_label_values(&[collection, operation]).observe(now.elapsed().as_micros() as _);
+        if result.is_err() {
+            self.queries_failed.with_label_values(&[collection, operation]).inc();
+        }
+        result
+    }
+}
+
 /// Struct for encapsulating find options for `MongoDB` queries.
 #[derive(Clone, Debug, Default)]
 pub struct FindOpts(FindOptions);
@@ -33,6 +95,20 @@ impl FindOpts {
         self
     }
 
+    /// Sets the number of documents to skip before the documents to retrieve.
+    #[must_use]
+    pub fn with_skip(mut self, skip: u64) -> Self {
+        self.0.skip = Some(skip);
+        self
+    }
+
+    /// Sets the sorting of the documents to retrieve.
+    #[must_use]
+    pub fn with_sort(mut self, sort: Document) -> Self {
+        self.0.sort = Some(sort);
+        self
+    }
+
     /// Sets the projection for the documents to retrieve.
     #[must_use]
     pub fn with_projection(mut self, projection: Document) -> Self {
@@ -83,13 +159,17 @@ impl Database {
         T: DeserializeOwned + CollectionName + Sync + Send,
     {
         let find_options = find_options.into();
-        Ok(self
-            .collection::<T>()
-            .find(Into::<Option<Document>>::into(filter).unwrap_or_default())
-            .with_options(find_options.unwrap_or_default().build())
-            .await?
-            .try_collect()
-            .await?)
+        DATABASE_METRICS
+            .observe(T::collection_name(), "find", async {
+                Ok(self
+                    .collection::<T>()
+                    .find(Into::<Option<Document>>::into(filter).unwrap_or_default())
+                    .with_options(find_options.unwrap_or_default().build())
+                    .await?
+                    .try_collect()
+                    .await?)
+            })
+            .await
     }
 
     /// Get all documents from a collection
@@ -99,7 +179,17 @@ impl Database {
     {
         let find_options = FindOpts::default().build();
 
-        Ok(self.collection::<T>().find(Default::default()).with_options(find_options).await?.try_collect().await?)
+        DATABASE_METRICS
+            .observe(T::collection_name(), "find", async {
+                Ok(self
+                    .collection::<T>()
+                    .find(Default::default())
+                    .with_options(find_options)
+                    .await?
+                    .try_collect()
+                    .await?)
+            })
+            .await
     }
 
     /// Retrieves documents from a collection and converts them into another type.
@@ -140,11 +230,15 @@ impl Database {
         T: DeserializeOwned + Unpin + Send + Sync + CollectionName,
     {
         let find_one_options = FindOneOptions::builder().sort(sort).build();
-        Ok(self
-            .collection::<T>()
-            .find_one(Into::<Option<Document>>::into(filter).unwrap_or_default())
-            .with_options(find_one_options)
-            .await?)
+        DATABASE_METRICS
+            .observe(T::collection_name(), "find_one", async {
+                Ok(self
+                    .collection::<T>()
+                    .find_one(Into::<Option<Document>>::into(filter).unwrap_or_default())
+                    .with_options(find_one_options)
+                    .await?)
+            })
+            .await
     }
 
     /// Get the first document from a collection
@@ -152,7 +246,11 @@ impl Database {
     where
         T: DeserializeOwned + Unpin + Send + Sync + CollectionName,
     {
-        Ok(self.collection::<T>().find_one(Default::default()).await?)
+        DATABASE_METRICS
+            .observe(T::collection_name(), "find_one", async {
+                Ok(self.collection::<T>().find_one(Default::default()).await?)
+            })
+            .await
     }
 
     /// Get a single document from aggregated collections
@@ -160,9 +258,13 @@ impl Database {
     where
         T: DeserializeOwned + CollectionName + Sync + Send,
     {
-        let mut cursor = self.collection::<T>().aggregate(pipeline).await?;
+        DATABASE_METRICS
+            .observe(T::collection_name(), "aggregate", async {
+                let mut cursor = self.collection::<T>().aggregate(pipeline).await?;
 
-        Ok(cursor.try_next().await?.map(|doc| mongodb::bson::de::from_document(doc)).transpose()?)
+                Ok(cursor.try_next().await?.map(|doc| mongodb::bson::de::from_document(doc)).transpose()?)
+            })
+            .await
     }
 
     /// Update a single document in a collection
@@ -173,12 +275,28 @@ impl Database {
         let doc = mongodb::bson::to_document(&doc).map_err(mongodb::error::Error::custom)?;
         let update_options = UpdateOptions::builder().upsert(upsert).build();
 
-        self.collection::<T>()
-            .update_one(filter.into(), UpdateModifications::Document(doc! {"$set": doc}))
-            .with_options(update_options)
-            .await?;
+        DATABASE_METRICS
+            .observe(T::collection_name(), "update_one", async {
+                self.collection::<T>()
+                    .update_one(filter.into(), UpdateModifications::Document(doc! {"$set": doc}))
+                    .with_options(update_options)
+                    .await?;
+                Ok(())
+            })
+            .await
+    }
 
-        Ok(())
+    /// Set the given fields of a single document in a collection, without inserting it if missing
+    pub async fn set_fields<T>(&self, fields: Document, filter: impl Into<Document>) -> DatabaseResult<()>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        DATABASE_METRICS
+            .observe(T::collection_name(), "update_one", async {
+                self.collection::<T>().update_one(filter.into(), doc! {"$set": fields}).await?;
+                Ok(())
+            })
+            .await
     }
 
     /// Delete a single document from a collection
@@ -186,8 +304,12 @@ impl Database {
     where
         T: CollectionName + Sync + Send,
     {
-        self.collection::<T>().delete_one(filter.into()).await?;
-        Ok(())
+        DATABASE_METRICS
+            .observe(T::collection_name(), "delete_one", async {
+                self.collection::<T>().delete_one(filter.into()).await?;
+                Ok(())
+            })
+            .await
     }
 
     /// Count the number of documents in a collection matching the filter
@@ -195,7 +317,9 @@ impl Database {
     where
         T: CollectionName + Sync + Send,
     {
-        Ok(self.collection::<T>().count_documents(filter).await?)
+        DATABASE_METRICS
+            .observe(T::collection_name(), "count", async { Ok(self.collection::<T>().count_documents(filter).await?) })
+            .await
     }
 }
 
@@ -218,6 +342,13 @@ impl CollectionName for StoredHeader {
     }
 }
 
+/// Implement [`CollectionName`] for [`StoredHeaderBloom`]
+impl CollectionName for StoredHeaderBloom {
+    fn collection_name() -> &'static str {
+        "headers"
+    }
+}
+
 /// Implement [`CollectionName`] for [`StoredTransaction`]
 impl CollectionName for StoredTransaction {
     fn collection_name() -> &'static str {
@@ -0,0 +1,658 @@
+use super::{
+    ethereum::{
+        build_block, EthereumBlockStore, EthereumIndexWriter, EthereumLogStore, EthereumReceiptStore,
+        EthereumTransactionStore, LogBlocks, LogQuery,
+    },
+    types::{
+        header::{ExtendedBlock, HeaderBloom},
+        receipt::ExtendedTxReceipt,
+        transaction::{
+            EthStarknetHashes, ExtendedTransaction, StarknetExecutionCost, StoredEthStarknetTransactionHash,
+        },
+    },
+};
+use crate::providers::eth_provider::error::{EthApiError, KakarotError};
+use alloy_primitives::{Bloom, B256, U256};
+use alloy_rpc_types::{BlockHashOrNumber, Header, Index, Log};
+use async_trait::async_trait;
+use sqlx::{
+    migrate::{MigrateError, Migrator},
+    postgres::{PgPool, PgPoolOptions},
+    types::Json,
+    Postgres, QueryBuilder,
+};
+use tracing::instrument;
+
+/// The migrations of the `PostgreSQL` schema.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Converts the block number to a `BIGINT`, saturating at `i64::MAX`.
+fn to_i64(number: u64) -> i64 {
+    i64::try_from(number).unwrap_or(i64::MAX)
+}
+
+/// Converts the `BIGINT` to a block number. Block numbers are never negative.
+fn to_u64(number: i64) -> u64 {
+    u64::try_from(number).unwrap_or_default()
+}
+
+/// A `PostgreSQL` Ethereum storage. Each table holds the JSON documents of the indexed data,
+/// along with the columns filtered by the queries, see the `migrations/postgres` schema. The
+/// tables are filled by the indexer run with the Postgres sink, which writes its output to the
+/// `indexer_items` table projected into the other tables.
+#[derive(Debug, Clone)]
+pub struct PostgresDatabase(PgPool);
+
+impl PostgresDatabase {
+    pub const fn new(pool: PgPool) -> Self {
+        Self(pool)
+    }
+
+    /// Connects to the `PostgreSQL` database at the given URL.
+    pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        Ok(Self(PgPoolOptions::new().connect(url).await?))
+    }
+
+    /// Get a reference to the inner `PgPool`
+    pub const fn inner(&self) -> &PgPool {
+        &self.0
+    }
+
+    /// Applies the pending migrations of the schema.
+    #[instrument(skip_all, name = "pg::migrate", err)]
+    pub async fn migrate(&self) -> Result<(), MigrateError> {
+        MIGRATOR.run(&self.0).await
+    }
+}
+
+/// Adds the condition on the block hash or number to the query.
+fn push_block_condition(builder: &mut QueryBuilder<'_, Postgres>, block_hash_or_number: BlockHashOrNumber) {
+    match block_hash_or_number {
+        BlockHashOrNumber::Hash(hash) => builder.push("block_hash = ").push_bind(hash.to_vec()),
+        BlockHashOrNumber::Number(number) => builder.push("block_number = ").push_bind(to_i64(number)),
+    };
+}
+
+/// Adds the conditions of the log query to the query.
+fn push_log_conditions(builder: &mut QueryBuilder<'_, Postgres>, query: &LogQuery) {
+    match &query.blocks {
+        LogBlocks::Hash(hash) => {
+            builder.push(" WHERE block_hash = ").push_bind(hash.to_vec());
+        }
+        LogBlocks::Range(from, to) => {
+            builder.push(" WHERE block_number BETWEEN ").push_bind(to_i64(*from)).push(" AND ").push_bind(to_i64(*to));
+        }
+        LogBlocks::Ranges(ranges) => {
+            builder.push(" WHERE (FALSE");
+            for (from, to) in ranges {
+                builder.push(" OR block_number BETWEEN ").push_bind(to_i64(*from)).push(" AND ").push_bind(to_i64(*to));
+            }
+            builder.push(")");
+        }
+    };
+    if !query.addresses.is_empty() {
+        let addresses = query.addresses.iter().map(|address| address.to_vec()).collect::<Vec<_>>();
+        builder.push(" AND address = ANY(").push_bind(addresses).push(")");
+    }
+    for (index, topic) in query.topics.iter().enumerate() {
+        if !topic.is_empty() {
+            let topics = topic.iter().map(|topic| topic.to_vec()).collect::<Vec<_>>();
+            builder.push(format!(" AND topic{index} = ANY(")).push_bind(topics).push(")");
+        }
+    }
+}
+
+#[async_trait]
+impl EthereumTransactionStore for PostgresDatabase {
+    #[instrument(skip_all, name = "pg::transaction", err)]
+    async fn transaction(&self, hash: &B256) -> Result<Option<ExtendedTransaction>, EthApiError> {
+        let tx = sqlx::query_scalar::<_, Json<ExtendedTransaction>>("SELECT tx FROM transactions WHERE hash = $1")
+            .bind(hash.to_vec())
+            .fetch_optional(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(tx.map(|tx| tx.0))
+    }
+
+    #[instrument(skip_all, name = "pg::transactions_by_hashes", err)]
+    async fn transactions_by_hashes(&self, hashes: &[B256]) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        let hashes = hashes.iter().map(|hash| hash.to_vec()).collect::<Vec<_>>();
+        let txs = sqlx::query_scalar::<_, Json<ExtendedTransaction>>(
+            "SELECT tx FROM transactions WHERE hash = ANY($1) ORDER BY block_number, transaction_index",
+        )
+        .bind(hashes)
+        .fetch_all(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(txs.into_iter().map(|tx| tx.0).collect())
+    }
+
+    #[instrument(skip_all, name = "pg::transactions", err)]
+    async fn transactions(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+    ) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT tx FROM transactions WHERE ");
+        push_block_condition(&mut builder, block_hash_or_number);
+        builder.push(" ORDER BY transaction_index");
+
+        let txs = builder
+            .build_query_scalar::<Json<ExtendedTransaction>>()
+            .fetch_all(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(txs.into_iter().map(|tx| tx.0).collect())
+    }
+
+    #[instrument(skip_all, name = "pg::transaction_by_block_and_index", err)]
+    async fn transaction_by_block_and_index(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        index: Index,
+    ) -> Result<Option<ExtendedTransaction>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT tx FROM transactions WHERE ");
+        push_block_condition(&mut builder, block_hash_or_number);
+        builder.push(" AND transaction_index = ").push_bind(i64::try_from(usize::from(index)).unwrap_or(i64::MAX));
+
+        let tx = builder
+            .build_query_scalar::<Json<ExtendedTransaction>>()
+            .fetch_optional(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(tx.map(|tx| tx.0))
+    }
+
+    #[instrument(skip_all, name = "pg::upsert_transaction", err)]
+    async fn upsert_transaction(&self, transaction: ExtendedTransaction) -> Result<(), EthApiError> {
+        sqlx::query(
+            "INSERT INTO transactions (hash, block_hash, block_number, transaction_index, tx) VALUES ($1, $2, $3, $4, \
+             $5) ON CONFLICT (hash) DO UPDATE SET block_hash = EXCLUDED.block_hash, block_number = \
+             EXCLUDED.block_number, transaction_index = EXCLUDED.transaction_index, tx = EXCLUDED.tx",
+        )
+        .bind(transaction.hash.to_vec())
+        .bind(transaction.block_hash.map(|hash| hash.to_vec()))
+        .bind(transaction.block_number.map(to_i64))
+        .bind(transaction.transaction_index.map(to_i64))
+        .bind(Json(&transaction))
+        .execute(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "pg::upsert_transaction_hashes", err)]
+    async fn upsert_transaction_hashes(&self, transaction_hashes: EthStarknetHashes) -> Result<(), EthApiError> {
+        sqlx::query(
+            "INSERT INTO transaction_hashes (eth_hash, hashes) VALUES ($1, $2) ON CONFLICT (eth_hash) DO UPDATE SET \
+             hashes = EXCLUDED.hashes",
+        )
+        .bind(transaction_hashes.eth_hash.to_vec())
+        .bind(Json(&transaction_hashes))
+        .execute(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "pg::transaction_hashes", err)]
+    async fn transaction_hashes(
+        &self,
+        eth_hash: &B256,
+    ) -> Result<Option<StoredEthStarknetTransactionHash>, EthApiError> {
+        let row = sqlx::query_as::<_, (Json<EthStarknetHashes>, Option<Json<StarknetExecutionCost>>)>(
+            "SELECT hashes, execution_cost FROM transaction_hashes WHERE eth_hash = $1",
+        )
+        .bind(eth_hash.to_vec())
+        .fetch_optional(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(row.map(|(hashes, execution_cost)| StoredEthStarknetTransactionHash {
+            hashes: hashes.0,
+            execution_cost: execution_cost.map(|cost| cost.0),
+        }))
+    }
+
+    #[instrument(skip_all, name = "pg::update_starknet_execution_cost", err)]
+    async fn update_starknet_execution_cost(
+        &self,
+        eth_hash: &B256,
+        execution_cost: StarknetExecutionCost,
+    ) -> Result<(), EthApiError> {
+        sqlx::query("UPDATE transaction_hashes SET execution_cost = $2 WHERE eth_hash = $1")
+            .bind(eth_hash.to_vec())
+            .bind(Json(&execution_cost))
+            .execute(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EthereumBlockStore for PostgresDatabase {
+    #[instrument(skip_all, name = "pg::latest_header", err)]
+    async fn latest_header(&self) -> Result<Option<Header>, EthApiError> {
+        let header = sqlx::query_scalar::<_, Json<Header>>("SELECT header FROM headers ORDER BY number DESC LIMIT 1")
+            .fetch_optional(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(header.map(|header| header.0))
+    }
+
+    #[instrument(skip_all, name = "pg::header", err)]
+    async fn header(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<Header>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT header FROM headers WHERE ");
+        match block_hash_or_number {
+            BlockHashOrNumber::Hash(hash) => builder.push("hash = ").push_bind(hash.to_vec()),
+            BlockHashOrNumber::Number(number) => builder.push("number = ").push_bind(to_i64(number)),
+        };
+
+        let header =
+            builder.build_query_scalar::<Json<Header>>().fetch_optional(&self.0).await.map_err(KakarotError::from)?;
+        Ok(header.map(|header| header.0))
+    }
+
+    #[instrument(skip_all, name = "pg::headers", err)]
+    async fn headers(&self, from: u64, to: u64) -> Result<Vec<Header>, EthApiError> {
+        let headers = sqlx::query_scalar::<_, Json<Header>>(
+            "SELECT header FROM headers WHERE number BETWEEN $1 AND $2 ORDER BY number",
+        )
+        .bind(to_i64(from))
+        .bind(to_i64(to))
+        .fetch_all(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(headers.into_iter().map(|header| header.0).collect())
+    }
+
+    #[instrument(skip_all, name = "pg::block", err)]
+    async fn block(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+        full: bool,
+    ) -> Result<Option<ExtendedBlock>, EthApiError> {
+        let Some(header) = self.header(block_hash_or_number).await? else {
+            return Ok(None);
+        };
+        let transactions = self.transactions(block_hash_or_number).await?;
+        build_block(header, transactions, full).map(Some)
+    }
+
+    #[instrument(skip_all, name = "pg::transaction_count", err)]
+    async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError> {
+        if !self.block_exists(block_hash_or_number).await? {
+            return Ok(None);
+        }
+
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM transactions WHERE ");
+        push_block_condition(&mut builder, block_hash_or_number);
+
+        let count = builder.build_query_scalar::<i64>().fetch_one(&self.0).await.map_err(KakarotError::from)?;
+        Ok(Some(U256::from(to_u64(count))))
+    }
+
+    #[instrument(skip_all, name = "pg::logs_blooms", err)]
+    async fn logs_blooms(&self, from: u64, to: u64, limit: u64) -> Result<Vec<HeaderBloom>, EthApiError> {
+        let blooms = sqlx::query_as::<_, (i64, Vec<u8>)>(
+            "SELECT number, logs_bloom FROM headers WHERE number BETWEEN $1 AND $2 ORDER BY number LIMIT $3",
+        )
+        .bind(to_i64(from))
+        .bind(to_i64(to))
+        .bind(to_i64(limit))
+        .fetch_all(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(blooms
+            .into_iter()
+            .map(|(number, logs_bloom)| HeaderBloom {
+                number: to_u64(number),
+                logs_bloom: Bloom::from_slice(&logs_bloom),
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EthereumReceiptStore for PostgresDatabase {
+    #[instrument(skip_all, name = "pg::receipt", err)]
+    async fn receipt(&self, hash: &B256) -> Result<Option<ExtendedTxReceipt>, EthApiError> {
+        let receipt = sqlx::query_scalar::<_, Json<ExtendedTxReceipt>>(
+            "SELECT receipt FROM receipts WHERE transaction_hash = $1",
+        )
+        .bind(hash.to_vec())
+        .fetch_optional(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(receipt.map(|receipt| receipt.0))
+    }
+
+    #[instrument(skip_all, name = "pg::receipts", err)]
+    async fn receipts(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT receipt FROM receipts WHERE ");
+        push_block_condition(&mut builder, block_hash_or_number);
+        builder.push(" ORDER BY transaction_index");
+
+        let receipts = builder
+            .build_query_scalar::<Json<ExtendedTxReceipt>>()
+            .fetch_all(&self.0)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(receipts.into_iter().map(|receipt| receipt.0).collect())
+    }
+
+    #[instrument(skip_all, name = "pg::receipts_by_block_range", err)]
+    async fn receipts_by_block_range(&self, from: u64, to: u64) -> Result<Vec<ExtendedTxReceipt>, EthApiError> {
+        let receipts = sqlx::query_scalar::<_, Json<ExtendedTxReceipt>>(
+            "SELECT receipt FROM receipts WHERE block_number BETWEEN $1 AND $2 ORDER BY block_number, \
+             transaction_index",
+        )
+        .bind(to_i64(from))
+        .bind(to_i64(to))
+        .fetch_all(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(receipts.into_iter().map(|receipt| receipt.0).collect())
+    }
+}
+
+#[async_trait]
+impl EthereumLogStore for PostgresDatabase {
+    #[instrument(skip_all, name = "pg::logs", err)]
+    async fn logs(&self, query: &LogQuery, limit: Option<u64>) -> Result<Vec<Log>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT log FROM logs");
+        push_log_conditions(&mut builder, query);
+        builder.push(" ORDER BY block_number, transaction_index, log_index");
+        if let Some(limit) = limit {
+            builder.push(" LIMIT ").push_bind(to_i64(limit));
+        }
+        if query.skip > 0 {
+            builder.push(" OFFSET ").push_bind(to_i64(query.skip));
+        }
+
+        let logs = builder.build_query_scalar::<Json<Log>>().fetch_all(&self.0).await.map_err(KakarotError::from)?;
+        Ok(logs.into_iter().map(|log| log.0).collect())
+    }
+
+    #[instrument(skip_all, name = "pg::nth_log_block_number", err)]
+    async fn nth_log_block_number(&self, query: &LogQuery, n: u64) -> Result<Option<u64>, EthApiError> {
+        let mut builder = QueryBuilder::new("SELECT block_number FROM logs");
+        push_log_conditions(&mut builder, query);
+        builder.push(" ORDER BY block_number, transaction_index, log_index LIMIT 1 OFFSET ").push_bind(to_i64(n));
+
+        let block_number =
+            builder.build_query_scalar::<Option<i64>>().fetch_optional(&self.0).await.map_err(KakarotError::from)?;
+        Ok(block_number.flatten().map(to_u64))
+    }
+}
+
+#[async_trait]
+impl EthereumIndexWriter for PostgresDatabase {
+    #[instrument(skip_all, name = "pg::upsert_header", err)]
+    async fn upsert_header(&self, header: Header) -> Result<(), EthApiError> {
+        sqlx::query(
+            "INSERT INTO headers (number, hash, logs_bloom, header) VALUES ($1, $2, $3, $4) ON CONFLICT (number) DO \
+             UPDATE SET hash = EXCLUDED.hash, logs_bloom = EXCLUDED.logs_bloom, header = EXCLUDED.header",
+        )
+        .bind(to_i64(header.number))
+        .bind(header.hash.to_vec())
+        .bind(header.logs_bloom.to_vec())
+        .bind(Json(&header))
+        .execute(&self.0)
+        .await
+        .map_err(KakarotError::from)?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "pg::upsert_receipt", err)]
+    async fn upsert_receipt(&self, receipt: ExtendedTxReceipt) -> Result<(), EthApiError> {
+        let mut tx = self.0.begin().await.map_err(KakarotError::from)?;
+        let hash = receipt.transaction_hash.to_vec();
+
+        sqlx::query(
+            "INSERT INTO receipts (transaction_hash, block_hash, block_number, transaction_index, receipt) VALUES \
+             ($1, $2, $3, $4, $5) ON CONFLICT (transaction_hash) DO UPDATE SET block_hash = EXCLUDED.block_hash, \
+             block_number = EXCLUDED.block_number, transaction_index = EXCLUDED.transaction_index, receipt = \
+             EXCLUDED.receipt",
+        )
+        .bind(&hash)
+        .bind(receipt.block_hash.map(|hash| hash.to_vec()))
+        .bind(receipt.block_number.map(to_i64))
+        .bind(receipt.transaction_index.map(to_i64))
+        .bind(Json(&receipt))
+        .execute(&mut *tx)
+        .await
+        .map_err(KakarotError::from)?;
+
+        sqlx::query("DELETE FROM logs WHERE transaction_hash = $1")
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(KakarotError::from)?;
+        for log in receipt.inner.inner.logs() {
+            let topic = |index: usize| log.topics().get(index).map(|topic| topic.to_vec());
+            sqlx::query(
+                "INSERT INTO logs (transaction_hash, block_hash, block_number, transaction_index, log_index, \
+                 address, topic0, topic1, topic2, topic3, log) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            )
+            .bind(log.transaction_hash.map(|hash| hash.to_vec()))
+            .bind(log.block_hash.map(|hash| hash.to_vec()))
+            .bind(log.block_number.map(to_i64))
+            .bind(log.transaction_index.map(to_i64))
+            .bind(log.log_index.map(to_i64))
+            .bind(log.address().to_vec())
+            .bind(topic(0))
+            .bind(topic(1))
+            .bind(topic(2))
+            .bind(topic(3))
+            .bind(Json(log))
+            .execute(&mut *tx)
+            .await
+            .map_err(KakarotError::from)?;
+        }
+
+        tx.commit().await.map_err(KakarotError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        mongo::{StoreFuzzer, RANDOM_BYTES_SIZE},
+        postgres::PostgresContainer,
+    };
+    use alloy_primitives::Address;
+    use alloy_rpc_types::Filter;
+
+    #[test]
+    fn test_block_number_conversions() {
+        // When & Then
+        assert_eq!(to_i64(1), 1);
+        assert_eq!(to_i64(u64::MAX), i64::MAX);
+        assert_eq!(to_u64(1), 1);
+        assert_eq!(to_u64(-1), 0);
+    }
+
+    #[test]
+    fn test_log_conditions_block_range() {
+        // Given
+        let filter = Filter::new()
+            .address(vec![Address::with_last_byte(1), Address::with_last_byte(2)])
+            .event_signature(B256::with_last_byte(1))
+            .topic2(B256::with_last_byte(2));
+        let query = LogQuery::new(LogBlocks::Range(1, 10), &filter);
+        let mut builder = QueryBuilder::new("SELECT log FROM logs");
+
+        // When
+        push_log_conditions(&mut builder, &query);
+
+        // Then
+        assert_eq!(
+            builder.sql(),
+            "SELECT log FROM logs WHERE block_number BETWEEN $1 AND $2 AND address = ANY($3) AND topic0 = ANY($4) \
+             AND topic2 = ANY($5)"
+        );
+    }
+
+    #[test]
+    fn test_log_conditions_block_ranges() {
+        // Given
+        let query = LogQuery::new(LogBlocks::Ranges(vec![(1, 4), (6, 10)]), &Filter::new());
+        let mut builder = QueryBuilder::new("SELECT log FROM logs");
+
+        // When
+        push_log_conditions(&mut builder, &query);
+
+        // Then
+        assert_eq!(
+            builder.sql(),
+            "SELECT log FROM logs WHERE (FALSE OR block_number BETWEEN $1 AND $2 OR block_number BETWEEN $3 AND $4)"
+        );
+    }
+
+    #[test]
+    fn test_log_conditions_block_hash() {
+        // Given
+        let query = LogQuery::new(LogBlocks::Hash(B256::with_last_byte(1)), &Filter::new());
+        let mut builder = QueryBuilder::new("SELECT log FROM logs");
+
+        // When
+        push_log_conditions(&mut builder, &query);
+
+        // Then
+        assert_eq!(builder.sql(), "SELECT log FROM logs WHERE block_hash = $1");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_postgres_index_writer() {
+        // Given
+        let postgres = PostgresContainer::new().await;
+        let database = &postgres.database;
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
+        fuzzer.add_random_transactions(10).expect("Failed to add documents");
+
+        // When
+        fuzzer.write_to(database).await;
+
+        // Then
+        let header = &fuzzer.headers.first().unwrap().header;
+        assert_eq!(database.header(header.number.into()).await.unwrap().as_ref(), Some(header));
+        assert_eq!(database.header(header.hash.into()).await.unwrap().as_ref(), Some(header));
+        let latest = fuzzer.headers.iter().map(|header| header.number).max();
+        assert_eq!(database.latest_header().await.unwrap().map(|header| header.number), latest);
+
+        for transaction in &fuzzer.transactions {
+            assert_eq!(database.transaction(&transaction.hash).await.unwrap().as_ref(), Some(&transaction.tx));
+        }
+        let hashes = fuzzer.transactions.iter().map(|transaction| transaction.hash).collect::<Vec<_>>();
+        assert_eq!(database.transactions_by_hashes(&hashes).await.unwrap().len(), hashes.len());
+        for receipt in &fuzzer.receipts {
+            let hash = receipt.receipt.transaction_hash;
+            assert_eq!(database.receipt(&hash).await.unwrap().as_ref(), Some(&receipt.receipt));
+        }
+        let n_logs = fuzzer.receipts.iter().map(|receipt| receipt.receipt.inner.inner.logs().len()).sum::<usize>();
+        let query = LogQuery::new(LogBlocks::Range(0, u64::MAX), &Filter::new());
+        assert_eq!(database.logs(&query, None).await.unwrap().len(), n_logs);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_postgres_upsert_header_replaces_hash() {
+        // Given
+        let postgres = PostgresContainer::new().await;
+        let database = &postgres.database;
+        let header = Header { number: 1, hash: B256::with_last_byte(1), ..Default::default() };
+        database.upsert_header(header).await.unwrap();
+
+        // When
+        database
+            .upsert_header(Header { number: 1, hash: B256::with_last_byte(2), ..Default::default() })
+            .await
+            .unwrap();
+
+        // Then
+        assert!(database.header(B256::with_last_byte(1).into()).await.unwrap().is_none());
+        assert_eq!(database.header(B256::with_last_byte(2).into()).await.unwrap().unwrap().number, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_postgres_upsert_receipt_replaces_logs() {
+        // Given
+        let postgres = PostgresContainer::new().await;
+        let database = &postgres.database;
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
+        fuzzer.add_random_transactions(1).expect("Failed to add documents");
+        let receipt = fuzzer.receipts.first().unwrap().receipt.clone();
+        let query = LogQuery::new(LogBlocks::Range(0, u64::MAX), &Filter::new());
+
+        // When
+        database.upsert_receipt(receipt.clone()).await.unwrap();
+        database.upsert_receipt(receipt.clone()).await.unwrap();
+
+        // Then
+        assert_eq!(database.logs(&query, None).await.unwrap().len(), receipt.inner.inner.logs().len());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_postgres_indexer_sink() {
+        // Given
+        let postgres = PostgresContainer::new().await;
+        let database = &postgres.database;
+        let mut fuzzer = StoreFuzzer::new(RANDOM_BYTES_SIZE);
+        fuzzer.add_random_transactions(2).expect("Failed to add documents");
+        let items = fuzzer
+            .headers
+            .iter()
+            .map(|header| ("headers", serde_json::to_value(header)))
+            .chain(fuzzer.transactions.iter().map(|tx| ("transactions", serde_json::to_value(tx))))
+            .chain(fuzzer.receipts.iter().map(|receipt| ("receipts", serde_json::to_value(receipt))))
+            .chain(fuzzer.logs.iter().map(|log| ("logs", serde_json::to_value(log))))
+            .map(|(collection, data)| (collection, data.unwrap()))
+            .collect::<Vec<_>>();
+
+        // When
+        for (collection, data) in &items {
+            sqlx::query("INSERT INTO indexer_items (collection, data, _cursor) VALUES ($1, $2, int8range(1, NULL))")
+                .bind(collection)
+                .bind(data)
+                .execute(database.inner())
+                .await
+                .unwrap();
+        }
+
+        // Then
+        let header = &fuzzer.headers.first().unwrap().header;
+        assert_eq!(database.header(header.hash.into()).await.unwrap().as_ref(), Some(header));
+        for transaction in &fuzzer.transactions {
+            assert_eq!(database.transaction(&transaction.hash).await.unwrap().as_ref(), Some(&transaction.tx));
+        }
+        for receipt in &fuzzer.receipts {
+            let hash = receipt.receipt.transaction_hash;
+            assert_eq!(database.receipt(&hash).await.unwrap().as_ref(), Some(&receipt.receipt));
+        }
+        let query = LogQuery::new(LogBlocks::Range(0, u64::MAX), &Filter::new());
+        assert_eq!(database.logs(&query, None).await.unwrap().len(), fuzzer.logs.len());
+
+        // When
+        sqlx::query("DELETE FROM indexer_items").execute(database.inner()).await.unwrap();
+
+        // Then
+        assert!(database.header(header.hash.into()).await.unwrap().is_none());
+        let transaction = fuzzer.transactions.first().unwrap();
+        assert!(database.transaction(&transaction.hash).await.unwrap().is_none());
+        assert!(database.logs(&query, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_postgres_errors_are_propagated() {
+        // Given
+        let postgres = PostgresContainer::new().await;
+        let database = &postgres.database;
+
+        // When
+        database.inner().close().await;
+
+        // Then
+        assert!(matches!(database.header(1.into()).await, Err(EthApiError::Kakarot(KakarotError::Postgres(_)))));
+    }
+}
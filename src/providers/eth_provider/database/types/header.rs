@@ -1,4 +1,5 @@
 use super::transaction::ExtendedTransaction;
+use alloy_primitives::Bloom;
 use alloy_rpc_types::{Block, Header};
 use alloy_serde::WithOtherFields;
 use serde::{Deserialize, Serialize};
@@ -40,6 +41,26 @@ impl Deref for StoredHeader {
     }
 }
 
+/// The block number and logs bloom of a header as stored in the database.
+///
+/// Used to scan the blooms of a range of headers without deserializing the full headers.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StoredHeaderBloom {
+    #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
+    pub header: HeaderBloom,
+}
+
+/// The block number and logs bloom of a header.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderBloom {
+    /// The block number.
+    #[serde(with = "alloy_serde::quantity")]
+    pub number: u64,
+    /// The bloom filter of the logs of the block.
+    pub logs_bloom: Bloom,
+}
+
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 impl Arbitrary<'_> for StoredHeader {
     fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
@@ -25,3 +25,18 @@ where
     let deserializer = MapDeserializer::new(s.into_iter());
     T::deserialize(deserializer).map_err(|err: serde_json::Error| serde::de::Error::custom(err.to_string()))
 }
+
+/// Used in order to perform a custom deserialization of optional stored data
+/// from the database. See [`deserialize_intermediate`].
+pub fn deserialize_option_intermediate<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let s: Option<HashMap<String, Value>> = Option::deserialize(deserializer)?;
+    s.map(|s| {
+        let deserializer = MapDeserializer::new(s.into_iter());
+        T::deserialize(deserializer).map_err(|err: serde_json::Error| serde::de::Error::custom(err.to_string()))
+    })
+    .transpose()
+}
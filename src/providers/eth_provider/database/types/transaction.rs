@@ -2,7 +2,7 @@ use alloy_primitives::B256;
 use alloy_rpc_types::Transaction;
 use alloy_serde::WithOtherFields;
 use serde::{Deserialize, Serialize};
-use starknet::core::types::Felt;
+use starknet::core::types::{ExecutionResources, FeePayment, Felt};
 use std::ops::Deref;
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 use {
@@ -23,11 +23,18 @@ pub struct StoredEthStarknetTransactionHash {
     /// Contains both Ethereum and Starknet transaction hashes.
     #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
     pub hashes: EthStarknetHashes,
+    /// The execution cost of the Starknet transaction, cached once fetched from Starknet.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_option_intermediate"
+    )]
+    pub execution_cost: Option<StarknetExecutionCost>,
 }
 
 impl From<EthStarknetHashes> for StoredEthStarknetTransactionHash {
     fn from(hashes: EthStarknetHashes) -> Self {
-        Self { hashes }
+        Self { hashes, execution_cost: None }
     }
 }
 
@@ -40,6 +47,17 @@ pub struct EthStarknetHashes {
     pub starknet_hash: Felt,
 }
 
+/// The execution cost of the Starknet transaction which executed an Ethereum transaction.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StarknetExecutionCost {
+    /// The Starknet transaction hash the cost relates to.
+    pub starknet_hash: Felt,
+    /// The fee paid on Starknet for the transaction.
+    pub actual_fee: FeePayment,
+    /// The resources consumed by the transaction on Starknet (steps, builtins, L1 data gas).
+    pub execution_resources: ExecutionResources,
+}
+
 /// A full transaction as stored in the database
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct StoredTransaction {
@@ -40,6 +40,7 @@ impl From<&EthApiError> for EthRpcErrorCode {
             | EthApiError::CalldataExceededLimit(_, _)
             | EthApiError::RethEthApi(_) => Self::InvalidParams,
             EthApiError::Transaction(err) => err.into(),
+            EthApiError::Logs(_) => Self::RequestLimitExceeded,
             // TODO improve the error
             EthApiError::Unsupported(_) | EthApiError::Kakarot(_) | EthApiError::Pool(_) => Self::InternalError,
             EthApiError::Execution(_) => Self::ExecutionError,
@@ -66,6 +67,8 @@ pub enum EthApiError {
     Transaction(#[from] TransactionError),
     /// Error related to transaction pool
     Pool(#[from] PoolError),
+    /// Error related to logs queries
+    Logs(#[from] LogsError),
     /// Error related to signing
     Signature(#[from] SignatureError),
     /// Unsupported feature
@@ -90,6 +93,7 @@ impl std::fmt::Display for EthApiError {
             Self::TransactionNotFound(tx) => write!(f, "transaction not found {tx}"),
             Self::Transaction(err) => write!(f, "{err}"),
             Self::Pool(err) => write!(f, "{err}"),
+            Self::Logs(err) => write!(f, "{err}"),
             Self::Signature(err) => write!(f, "{err}"),
             Self::RethEthApi(err) => write!(f, "{err}"),
             Self::Unsupported(feature) => write!(f, "unsupported: {feature}"),
@@ -147,6 +151,9 @@ pub enum KakarotError {
     /// Error related to the database deserialization.
     #[error(transparent)]
     DatabaseDeserialization(#[from] mongodb::bson::de::Error),
+    /// Error related to the Postgres database.
+    #[error(transparent)]
+    Postgres(#[from] sqlx::Error),
 }
 
 impl From<KakarotError> for EthApiError {
@@ -169,13 +176,18 @@ pub enum ExecutionError {
 impl From<cainome::cairo_serde::Error> for ExecutionError {
     fn from(error: cainome::cairo_serde::Error) -> Self {
         let error = error.to_string();
-        if error.contains("RunResources has no remaining steps.") {
+        if is_vm_out_of_resources(&error) {
             return Self::CairoVm(CairoError::VmOutOfResources);
         }
         Self::Other(error)
     }
 }
 
+/// Returns true if the Starknet error message is the one of the Cairo VM running out of resources.
+pub(crate) fn is_vm_out_of_resources(error: &str) -> bool {
+    error.contains("RunResources has no remaining steps")
+}
+
 impl std::fmt::Display for ExecutionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("execution reverted")?;
@@ -283,6 +295,24 @@ impl From<Vec<Felt>> for EvmError {
     }
 }
 
+/// Error related to a logs query.
+#[derive(Debug, Error)]
+pub enum LogsError {
+    /// Thrown when the block range of the query exceeds the maximum block range.
+    #[error("query exceeds max block range {0}")]
+    BlockRangeExceeded(u64),
+    /// Thrown when the query matches more logs than the maximum number of results,
+    /// along with a block range within which the results fit.
+    #[error("query returned more than {limit} results. Try with this block range [{from:#x}, {to:#x}].")]
+    ResultsExceeded { limit: u64, from: u64, to: u64 },
+    /// Thrown when the query of the logs of a single block matches more logs than the maximum
+    /// number of results, which can only be retrieved by pages.
+    #[error(
+        "query returned more than {limit} results in a single block. Retrieve them by pages with kakarot_getLogsPaged."
+    )]
+    BlockResultsExceeded { limit: u64 },
+}
+
 /// Error related to a transaction.
 #[derive(Debug, Error)]
 pub enum TransactionError {
@@ -304,6 +334,17 @@ pub enum TransactionError {
     /// Thrown when the gas limit exceeds the block's gas limit.
     #[error("transaction gas limit {0} exceeds block gas limit {1}")]
     ExceedsBlockGasLimit(u128, u128),
+    /// Thrown when the transaction can't be executed with the highest gas limit
+    /// allowed by the block gas limit, the request gas limit or the sender's balance.
+    #[error("gas required exceeds allowance ({0})")]
+    GasRequiredExceedsAllowance(u64),
+    /// Thrown when the sender's balance doesn't cover the transferred value.
+    #[error("insufficient funds for transfer")]
+    InsufficientFundsForTransfer,
+    /// Thrown when the Starknet transaction relaying the transaction would exceed
+    /// the Starknet execution resources limits (e.g. the step limit).
+    #[error("transaction exceeds the Starknet execution resources limits")]
+    ExceedsStarknetResources,
     /// Thrown when the transaction isn't the
     /// [`BlockTransactions::FullTransactions`] variant.
     #[error("expected full transactions")]
@@ -317,13 +358,20 @@ pub enum TransactionError {
     /// Thrown if the call with state or block overrides fails
     #[error("tracing error: {0}")]
     Call(Box<dyn std::error::Error + Send + Sync>),
+    /// Thrown when a transaction is sent while the RPC shuts down.
+    #[error("the RPC is shutting down and no longer accepts transactions")]
+    ShuttingDown,
 }
 
 impl From<&TransactionError> for EthRpcErrorCode {
     fn from(error: &TransactionError) -> Self {
         match error {
-            TransactionError::InvalidChainId | TransactionError::InvalidTransactionType => Self::InvalidInput,
+            TransactionError::InvalidChainId
+            | TransactionError::InvalidTransactionType
+            | TransactionError::GasRequiredExceedsAllowance(_)
+            | TransactionError::InsufficientFundsForTransfer => Self::InvalidInput,
             TransactionError::GasOverflow
+            | TransactionError::ExceedsStarknetResources
             | TransactionError::FeeCapTooLow(_, _)
             | TransactionError::TipAboveFeeCap(_, _) => Self::TransactionRejected,
             TransactionError::ExpectedFullTransactions
@@ -331,6 +379,7 @@ impl From<&TransactionError> for EthRpcErrorCode {
             | TransactionError::Call(_)
             | TransactionError::Broadcast(_)
             | TransactionError::ExceedsBlockGasLimit(_, _) => Self::InternalError,
+            TransactionError::ShuttingDown => Self::ResourceUnavailable,
         }
     }
 }
@@ -426,6 +475,51 @@ mod tests {
         assert_eq!(json_err.message(), "starknet provider error: StarknetError(UnexpectedError(\"test\"))");
     }
 
+    #[test]
+    fn test_exceeds_starknet_resources_error() {
+        // Given
+        let err = EthApiError::Transaction(TransactionError::ExceedsStarknetResources);
+
+        // When
+        let json_err: ErrorObject<'static> = err.into();
+
+        // Then
+        assert_eq!(json_err.message(), "transaction exceeds the Starknet execution resources limits");
+        assert_eq!(json_err.code(), EthRpcErrorCode::TransactionRejected as i32);
+    }
+
+    #[test]
+    fn test_logs_block_results_exceeded_error() {
+        // Given
+        let err = EthApiError::Logs(LogsError::BlockResultsExceeded { limit: 10000 });
+
+        // When
+        let json_err: ErrorObject<'static> = err.into();
+
+        // Then
+        assert_eq!(
+            json_err.message(),
+            "query returned more than 10000 results in a single block. Retrieve them by pages with kakarot_getLogsPaged."
+        );
+        assert_eq!(json_err.code(), EthRpcErrorCode::RequestLimitExceeded as i32);
+    }
+
+    #[test]
+    fn test_logs_results_exceeded_error() {
+        // Given
+        let err = EthApiError::Logs(LogsError::ResultsExceeded { limit: 10000, from: 0x10, to: 0x1f });
+
+        // When
+        let json_err: ErrorObject<'static> = err.into();
+
+        // Then
+        assert_eq!(
+            json_err.message(),
+            "query returned more than 10000 results. Try with this block range [0x10, 0x1f]."
+        );
+        assert_eq!(json_err.code(), EthRpcErrorCode::RequestLimitExceeded as i32);
+    }
+
     #[test]
     fn test_decode_revert_message() {
         // Given
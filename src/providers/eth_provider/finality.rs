@@ -0,0 +1,136 @@
+use super::{
+    error::KakarotError,
+    provider::{EthApiResult, EthDataProvider},
+};
+use crate::{
+    config::config,
+    providers::{eth_provider::BlockProvider, sn_provider::STARKNET_METRICS},
+};
+use starknet::{
+    core::types::{BlockId, BlockStatus, Felt, MaybePendingBlockWithTxHashes, StarknetError},
+    providers::{Provider, ProviderError},
+};
+use std::{str::FromStr, sync::atomic::Ordering};
+use tracing::Instrument;
+
+/// The block considered safe, returned for the `safe` block tag and configured with
+/// `SAFE_BLOCK_TAG`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SafeBlock {
+    /// The latest sealed block.
+    #[default]
+    Latest,
+    /// The latest block accepted on L1, as for the `finalized` tag.
+    Finalized,
+    /// The block with the given number of confirmations, i.e. this many blocks before the
+    /// latest sealed block.
+    Confirmations(u64),
+}
+
+impl FromStr for SafeBlock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "finalized" => Ok(Self::Finalized),
+            confirmations => confirmations
+                .parse()
+                .map(Self::Confirmations)
+                .map_err(|_| format!("expected latest, finalized or a number of confirmations, got {confirmations}")),
+        }
+    }
+}
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the number of the latest EVM block whose Starknet block is accepted on L1,
+    /// bounded by the latest sealed block. The genesis block is always final.
+    ///
+    /// Blocks are accepted on L1 in order, hence the block is searched between the last known
+    /// block accepted on L1 and the latest sealed block.
+    pub(crate) async fn finalized_block_number(&self) -> EthApiResult<u64> {
+        let latest = self.block_number().await?.to::<u64>();
+        let known = self.finalized().load(Ordering::Relaxed).min(latest);
+        if known == latest || !self.is_accepted_on_l1(known + 1).await? {
+            return Ok(known);
+        }
+
+        let finalized = if self.is_accepted_on_l1(latest).await? {
+            latest
+        } else {
+            // Blocks up to `low` are accepted on L1, `high` isn't.
+            let (mut low, mut high) = (known + 1, latest);
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                if self.is_accepted_on_l1(middle).await? {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            low
+        };
+
+        // Blocks are accepted on L1 in order, hence the number only increases
+        self.finalized().fetch_max(finalized, Ordering::Relaxed);
+        Ok(finalized)
+    }
+
+    /// Returns the number of the block considered safe, see [`SafeBlock`].
+    pub(crate) async fn safe_block_number(&self) -> EthApiResult<u64> {
+        match config().safe_block {
+            SafeBlock::Latest => Ok(self.block_number().await?.to()),
+            SafeBlock::Finalized => self.finalized_block_number().await,
+            SafeBlock::Confirmations(confirmations) => {
+                Ok(self.block_number().await?.to::<u64>().saturating_sub(confirmations))
+            }
+        }
+    }
+
+    /// Returns the Starknet block of the EVM block with the given number, if indexed and sealed.
+    ///
+    /// Headers are indexed with the hash of their Starknet block, which identifies the Starknet
+    /// block without assuming the numbers of both chains match.
+    pub(crate) async fn starknet_block_id(&self, number: u64) -> EthApiResult<Option<BlockId>> {
+        let header = self.database().header(number.into()).await?;
+        Ok(header
+            .filter(|header| !header.hash.is_zero())
+            .map(|header| BlockId::Hash(Felt::from_bytes_be(&header.hash.0))))
+    }
+
+    /// Returns true if the Starknet block of the EVM block with the given number is accepted on
+    /// L1. Blocks which aren't indexed or unknown to Starknet aren't.
+    async fn is_accepted_on_l1(&self, number: u64) -> EthApiResult<bool> {
+        let Some(block_id) = self.starknet_block_id(number).await? else {
+            return Ok(false);
+        };
+
+        let span = tracing::span!(tracing::Level::INFO, "sn::block_status");
+        let block = match STARKNET_METRICS
+            .observe("block_status", self.starknet_provider_inner().get_block_with_tx_hashes(block_id).instrument(span))
+            .await
+        {
+            Ok(block) => block,
+            Err(ProviderError::StarknetError(StarknetError::BlockNotFound)) => return Ok(false),
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+        Ok(matches!(block, MaybePendingBlockWithTxHashes::Block(block) if block.status == BlockStatus::AcceptedOnL1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_block_from_str() {
+        // When & Then
+        assert_eq!(SafeBlock::from_str("latest"), Ok(SafeBlock::Latest));
+        assert_eq!(SafeBlock::from_str("finalized"), Ok(SafeBlock::Finalized));
+        assert_eq!(SafeBlock::from_str("12"), Ok(SafeBlock::Confirmations(12)));
+        assert!(SafeBlock::from_str("safe").is_err());
+    }
+}
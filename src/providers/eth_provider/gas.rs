@@ -1,22 +1,35 @@
 use super::{
-    constant::BLOCK_NUMBER_HEX_STRING_LEN,
-    error::{ExecutionError, KakarotError},
-    starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
+    constant::{
+        ESTIMATE_GAS_ERROR_RATIO, MAX_FEE_HISTORY_BLOCK_COUNT, TX_ACCESS_LIST_ADDRESS_GAS,
+        TX_ACCESS_LIST_STORAGE_KEY_GAS, TX_DATA_NON_ZERO_GAS, TX_DATA_ZERO_GAS, TX_GAS, TX_GAS_CONTRACT_CREATION,
+        TX_INIT_CODE_WORD_GAS,
+    },
+    error::{EthApiError, EvmError, ExecutionError, KakarotError, TransactionError},
+    starknet::kakarot_core::core::KakarotCoreReader,
 };
 use crate::{
+    constants::KKRT_BLOCK_GAS_LIMIT,
     into_via_wrapper,
-    providers::eth_provider::{
-        database::{filter::format_hex, types::header::StoredHeader},
-        provider::{EthApiResult, EthDataProvider},
+    providers::{
+        eth_provider::{
+            database::{
+                ethereum::{EthereumBlockStore, EthereumReceiptStore},
+                types::receipt::ExtendedTxReceipt,
+            },
+            provider::{EthApiResult, EthDataProvider},
+            StateProvider, TransactionProvider,
+        },
+        sn_provider::STARKNET_METRICS,
     },
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
-use alloy_primitives::{U256, U64};
+use alloy_primitives::{TxKind, U256, U64};
 use alloy_rpc_types::{FeeHistory, TransactionRequest};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use eyre::eyre;
-use mongodb::bson::doc;
+use itertools::Itertools;
+use reth_rpc_eth_types::EthApiError as RethEthApiError;
 use tracing::Instrument;
 
 #[async_trait]
@@ -42,37 +55,126 @@ impl<SP> GasProvider for EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
-    async fn estimate_gas(&self, request: TransactionRequest, block_id: Option<BlockId>) -> EthApiResult<U256> {
-        // Set a high gas limit to make sure the transaction will not fail due to gas.
-        let request = TransactionRequest { gas: Some(u64::MAX), ..request };
+    async fn estimate_gas(&self, mut request: TransactionRequest, block_id: Option<BlockId>) -> EthApiResult<U256> {
+        // The fee cap is only used to cap the gas limit by the sender's allowance when
+        // it is explicitly provided by the caller, as geth does.
+        let fee_cap = request.max_fee_per_gas.or(request.gas_price).unwrap_or_default();
+
+        // Fill the fees and the nonce once, so that the executions of the binary search below
+        // don't fetch them from Starknet over and over again. The fee cap of an EIP-1559 request
+        // is filled instead of the gas price, so that the request keeps its type.
+        if request.gas_price.is_none() && request.max_fee_per_gas.is_none() {
+            let base_fee: u128 = self.gas_price().await?.try_into().map_err(|_| TransactionError::GasOverflow)?;
+            match request.max_priority_fee_per_gas {
+                Some(priority_fee) => request.max_fee_per_gas = Some(base_fee.saturating_add(priority_fee)),
+                None => request.gas_price = Some(base_fee),
+            }
+        }
+        if let (None, Some(from)) = (request.nonce, request.from) {
+            request.nonce = Some(self.transaction_count(from, block_id).await?.to());
+        }
+
+        let intrinsic_gas = intrinsic_gas(&request);
 
-        let gas_used = self.estimate_gas_inner(request, block_id).await?;
+        // Determine the highest gas limit that can be used for the estimation.
+        let mut highest_gas_limit = match request.gas {
+            Some(gas) if gas >= intrinsic_gas => gas.min(KKRT_BLOCK_GAS_LIMIT),
+            _ => KKRT_BLOCK_GAS_LIMIT,
+        };
+
+        // Cap the highest gas limit by the sender's allowance.
+        if let (Some(from), true) = (request.from, fee_cap != 0) {
+            let balance = self.balance(from, block_id).await?;
+            let value = request.value.unwrap_or_default();
+            if value > balance {
+                return Err(TransactionError::InsufficientFundsForTransfer.into());
+            }
+            let allowance = (balance - value) / U256::from(fee_cap);
+            highest_gas_limit = highest_gas_limit.min(allowance.saturating_to());
+        }
+
+        if highest_gas_limit < intrinsic_gas {
+            return Err(TransactionError::GasRequiredExceedsAllowance(highest_gas_limit).into());
+        }
+
+        // Fast path: Kakarot returns the gas used by the transaction when executed with the
+        // highest gas limit. The gas used is a lower bound of the required gas limit, and is
+        // most of the time the required gas limit itself.
+        let gas_used = match self
+            .estimate_gas_inner(TransactionRequest { gas: Some(highest_gas_limit), ..request.clone() }, block_id)
+            .await
+        {
+            Ok(gas_used) => u64::try_from(gas_used).map_err(|_| TransactionError::GasOverflow)?,
+            Err(EthApiError::Execution(ExecutionError::Evm(EvmError::OutOfGas))) => {
+                return Err(TransactionError::GasRequiredExceedsAllowance(highest_gas_limit).into())
+            }
+            Err(err) => return Err(err),
+        };
+        if gas_used >= highest_gas_limit {
+            return Ok(U256::from(highest_gas_limit));
+        }
+
+        let gas_used = gas_used.max(intrinsic_gas);
+        if self.call_inner(TransactionRequest { gas: Some(gas_used), ..request.clone() }, block_id).await.is_ok() {
+            return Ok(U256::from(gas_used));
+        }
+
+        // Slow path: binary search between the gas used, which failed, and the highest gas
+        // limit, which succeeded. This handles contracts that check `gasleft()` or forward
+        // 63/64 of the gas to their sub calls.
+        let mut lowest_gas_limit = gas_used;
+        while lowest_gas_limit + 1 < highest_gas_limit {
+            // Stop once the estimation is close enough to the exact required gas.
+            if ((highest_gas_limit - lowest_gas_limit) as f64) / (highest_gas_limit as f64) < ESTIMATE_GAS_ERROR_RATIO {
+                break;
+            }
+
+            // The required gas is usually close to the gas used, so the search is biased
+            // towards the lower bound.
+            let mid_gas_limit = ((lowest_gas_limit + highest_gas_limit) / 2).min(lowest_gas_limit.saturating_mul(2));
+
+            match self.call_inner(TransactionRequest { gas: Some(mid_gas_limit), ..request.clone() }, block_id).await {
+                Ok(_) => highest_gas_limit = mid_gas_limit,
+                Err(EthApiError::Execution(_)) => lowest_gas_limit = mid_gas_limit,
+                Err(err) => return Err(err),
+            }
+        }
 
-        // Increase the gas used by 40% to make sure the transaction will not fail due to gas.
-        // This is a temporary solution until we have a proper gas estimation.
-        // Does not apply to Hive feature otherwise end2end tests will fail.
-        let gas_used = if cfg!(feature = "hive") { gas_used } else { gas_used * 140 / 100 };
-        Ok(U256::from(gas_used))
+        Ok(U256::from(highest_gas_limit))
     }
 
     async fn fee_history(
         &self,
         block_count: U64,
         newest_block: BlockNumberOrTag,
-        _reward_percentiles: Option<Vec<f64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> EthApiResult<FeeHistory> {
         if block_count == U64::ZERO {
             return Ok(FeeHistory::default());
         }
 
+        // The percentiles must be monotonically increasing values between 0 and 100.
+        if let Some(percentiles) = &reward_percentiles {
+            if percentiles.windows(2).any(|w| w[0] > w[1]) || percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) {
+                return Err(RethEthApiError::InvalidRewardPercentiles.into());
+            }
+        }
+
+        // Cap the number of blocks as geth does.
+        let block_count = block_count.saturating_to::<u64>().min(MAX_FEE_HISTORY_BLOCK_COUNT);
+
         let end_block = self.tag_into_block_number(newest_block).await?;
         let end_block_plus_one = end_block.saturating_add(1);
 
         // 0 <= start_block <= end_block
-        let start_block = end_block_plus_one.saturating_sub(block_count.to());
+        let start_block = end_block_plus_one.saturating_sub(block_count);
 
-        let header_filter = doc! {"$and": [ { "header.number": { "$gte": format_hex(start_block, BLOCK_NUMBER_HEX_STRING_LEN) } }, { "header.number": { "$lte": format_hex(end_block, BLOCK_NUMBER_HEX_STRING_LEN) } } ] };
-        let blocks: Vec<StoredHeader> = self.database().get(header_filter, None).await?;
+        // The header following the newest block, if any, gives the base fee of the next block.
+        let mut blocks = self.database().headers(start_block, end_block_plus_one).await?;
+        let next_header = match blocks.last() {
+            Some(header) if header.number == end_block_plus_one => blocks.pop(),
+            _ => None,
+        };
 
         if blocks.is_empty() {
             return Err(
@@ -94,23 +196,204 @@ where
 
         let mut base_fee_per_gas =
             blocks.iter().map(|header| header.base_fee_per_gas.unwrap_or_default()).collect::<Vec<_>>();
-        // TODO(EIP1559): Remove this when proper base fee computation: if gas_ratio > 50%, increase base_fee_per_gas
-        base_fee_per_gas.extend_from_within((base_fee_per_gas.len() - 1)..);
+
+        // The base fee of the block following the newest block is the one of the next indexed
+        // block or, for the head of the chain, the base fee currently set in the Kakarot contract.
+        let next_base_fee = match next_header {
+            Some(header) => header.base_fee_per_gas.unwrap_or_default(),
+            None => self.gas_price().await?.saturating_to(),
+        };
+        base_fee_per_gas.push(next_base_fee);
+
+        let reward = match reward_percentiles {
+            Some(percentiles) => {
+                let receipts = self.database().receipts_by_block_range(start_block, end_block).await?;
+
+                // Group the receipts by block number.
+                let mut receipts_by_block = receipts.into_iter().into_group_map_by(|r| r.block_number);
+
+                Some(
+                    blocks
+                        .iter()
+                        .map(|header| {
+                            let receipts = receipts_by_block.remove(&Some(header.number)).unwrap_or_default();
+                            block_rewards(header.base_fee_per_gas.unwrap_or_default().into(), &receipts, &percentiles)
+                        })
+                        .collect(),
+                )
+            }
+            None => None,
+        };
 
         Ok(FeeHistory {
             base_fee_per_gas: base_fee_per_gas.into_iter().map(Into::into).collect(),
             gas_used_ratio,
             oldest_block: start_block,
-            reward: Some(vec![]),
+            reward,
             ..Default::default()
         })
     }
 
     async fn gas_price(&self) -> EthApiResult<U256> {
-        let kakarot_contract = KakarotCoreReader::new(*KAKAROT_ADDRESS, self.starknet_provider_inner());
+        let kakarot_contract =
+            KakarotCoreReader::new(config().starknet.kakarot_address, self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::base_fee");
-        let gas_price =
-            kakarot_contract.get_base_fee().call().instrument(span).await.map_err(ExecutionError::from)?.base_fee;
+        let gas_price = STARKNET_METRICS
+            .observe("base_fee", kakarot_contract.get_base_fee().call().instrument(span))
+            .await
+            .map_err(ExecutionError::from)?
+            .base_fee;
         Ok(into_via_wrapper!(gas_price))
     }
 }
+
+/// Computes the effective priority fees paid in a block at the given percentiles,
+/// weighted by the gas used of each transaction. Follows the geth implementation.
+fn block_rewards(base_fee: u128, receipts: &[ExtendedTxReceipt], percentiles: &[f64]) -> Vec<u128> {
+    if receipts.is_empty() {
+        return vec![0; percentiles.len()];
+    }
+
+    // Sort the (reward, gas used) pairs by increasing reward.
+    let mut rewards =
+        receipts.iter().map(|r| (r.effective_gas_price.saturating_sub(base_fee), r.gas_used)).collect::<Vec<_>>();
+    rewards.sort_unstable_by_key(|(reward, _)| *reward);
+
+    let total_gas_used: u128 = rewards.iter().map(|(_, gas_used)| gas_used).sum();
+
+    let mut index = 0;
+    let mut cumulative_gas_used = rewards[0].1;
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let threshold = (total_gas_used as f64 * percentile / 100.) as u128;
+            while cumulative_gas_used < threshold && index < rewards.len() - 1 {
+                index += 1;
+                cumulative_gas_used += rewards[index].1;
+            }
+            rewards[index].0
+        })
+        .collect()
+}
+
+/// Computes the intrinsic gas of the transaction request, following the Shanghai rules.
+fn intrinsic_gas(request: &TransactionRequest) -> u64 {
+    let is_create = !matches!(request.to, Some(TxKind::Call(_)));
+    let input = request.input.input().cloned().unwrap_or_default();
+
+    let zero_bytes = input.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zero_bytes = input.len() as u64 - zero_bytes;
+
+    let mut gas = if is_create { TX_GAS_CONTRACT_CREATION } else { TX_GAS };
+    gas += zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS;
+
+    if is_create {
+        gas += (input.len() as u64).div_ceil(32) * TX_INIT_CODE_WORD_GAS;
+    }
+
+    if let Some(access_list) = &request.access_list {
+        gas += access_list
+            .0
+            .iter()
+            .map(|item| TX_ACCESS_LIST_ADDRESS_GAS + item.storage_keys.len() as u64 * TX_ACCESS_LIST_STORAGE_KEY_GAS)
+            .sum::<u64>();
+    }
+
+    gas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::eth_provider::database::types::receipt::StoredTransactionReceipt;
+    use alloy_eips::eip2930::{AccessList, AccessListItem};
+    use alloy_primitives::{bytes, Address, B256};
+    use alloy_rpc_types::request::TransactionInput;
+    use arbitrary::Arbitrary;
+    use rand::Rng;
+
+    fn receipt(effective_gas_price: u128, gas_used: u128) -> ExtendedTxReceipt {
+        let mut bytes = [0u8; 1024];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        let mut receipt: ExtendedTxReceipt =
+            StoredTransactionReceipt::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap().into();
+        receipt.effective_gas_price = effective_gas_price;
+        receipt.gas_used = gas_used;
+        receipt
+    }
+
+    #[test]
+    fn test_block_rewards_empty_block() {
+        // When
+        let rewards = block_rewards(10, &[], &[25., 50., 75.]);
+
+        // Then
+        assert_eq!(rewards, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_block_rewards_weighted_by_gas_used() {
+        // Given
+        let base_fee = 10;
+        let receipts = vec![receipt(40, 21_000), receipt(12, 50_000), receipt(20, 29_000)];
+
+        // When
+        let rewards = block_rewards(base_fee, &receipts, &[0., 50., 60., 100.]);
+
+        // Then
+        // Sorted rewards: 2 (50% of the gas), 10 (29% of the gas), 30 (21% of the gas).
+        assert_eq!(rewards, vec![2, 2, 10, 30]);
+    }
+
+    #[test]
+    fn test_intrinsic_gas_transfer() {
+        // Given
+        let request = TransactionRequest { to: Some(TxKind::Call(Address::ZERO)), ..Default::default() };
+
+        // When
+        let gas = intrinsic_gas(&request);
+
+        // Then
+        assert_eq!(gas, TX_GAS);
+    }
+
+    #[test]
+    fn test_intrinsic_gas_call_with_data_and_access_list() {
+        // Given
+        let request = TransactionRequest {
+            to: Some(TxKind::Call(Address::ZERO)),
+            input: TransactionInput::new(bytes!("00010200")),
+            access_list: Some(AccessList(vec![AccessListItem {
+                address: Address::ZERO,
+                storage_keys: vec![B256::ZERO, B256::ZERO],
+            }])),
+            ..Default::default()
+        };
+
+        // When
+        let gas = intrinsic_gas(&request);
+
+        // Then
+        assert_eq!(
+            gas,
+            TX_GAS
+                + 2 * TX_DATA_ZERO_GAS
+                + 2 * TX_DATA_NON_ZERO_GAS
+                + TX_ACCESS_LIST_ADDRESS_GAS
+                + 2 * TX_ACCESS_LIST_STORAGE_KEY_GAS
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_gas_create() {
+        // Given
+        let request = TransactionRequest { input: TransactionInput::new(bytes!("6080604052")), ..Default::default() };
+
+        // When
+        let gas = intrinsic_gas(&request);
+
+        // Then
+        assert_eq!(gas, TX_GAS_CONTRACT_CREATION + 5 * TX_DATA_NON_ZERO_GAS + TX_INIT_CODE_WORD_GAS);
+    }
+}
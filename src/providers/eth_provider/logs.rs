@@ -1,24 +1,58 @@
 use super::{
-    constant::MAX_LOGS,
-    database::{filter::EthDatabaseFilterBuilder, types::log::StoredLog},
-    error::EthApiError,
-};
-use crate::providers::eth_provider::{
+    constant::{DEFAULT_LOGS_PAGE_SIZE, LOGS_BLOOM_SCAN_BATCH_SIZE},
     database::{
-        filter::{self},
-        FindOpts,
+        ethereum::{EthereumBlockStore, EthereumLogStore, LogBlocks, LogQuery},
+        types::header::HeaderBloom,
+    },
+    error::{EthApiError, LogsError},
+};
+use crate::{
+    config::config,
+    providers::eth_provider::{
+        provider::{EthApiResult, EthDataProvider},
+        BlockProvider,
     },
-    provider::{EthApiResult, EthDataProvider},
-    BlockProvider,
 };
-use alloy_rpc_types::{Filter, FilterChanges};
+use alloy_primitives::U64;
+use alloy_rpc_types::{BloomFilter, Filter, FilterChanges, FilteredParams, Log, Topic};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+use serde::{Deserialize, Serialize};
+
+/// A page of logs returned by `kakarot_getLogsPaged`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogsPage {
+    /// The logs of the page.
+    pub logs: Vec<Log>,
+    /// The cursor to pass to retrieve the next page, `None` for the last page.
+    pub cursor: Option<LogsCursor>,
+}
+
+/// The position of the first log of a page of `kakarot_getLogsPaged`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsCursor {
+    /// The number of the block of the log. Unused for the logs of a block hash.
+    pub block_number: U64,
+    /// The position of the log among the logs of the block matching the filter.
+    pub offset: U64,
+}
+
+impl LogsCursor {
+    /// Returns the cursor of the log at the given position in the block.
+    pub fn new(block_number: u64, offset: u64) -> Self {
+        Self { block_number: U64::from(block_number), offset: U64::from(offset) }
+    }
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
 pub trait LogProvider: BlockProvider {
     async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges>;
+    /// Returns the page of the logs matching the filter which starts at the cursor. Pages hold
+    /// up to `MAX_LOGS` logs. Pages of a block range are cut on block boundaries, unless a single
+    /// block holds more logs, which are then paged by their position in the block.
+    async fn get_logs_paged(&self, filter: Filter, cursor: Option<LogsCursor>) -> EthApiResult<LogsPage>;
 }
 
 #[async_trait]
@@ -27,49 +61,348 @@ where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges> {
-        let block_hash = filter.get_block_hash();
+        let max_logs = config().max_logs;
 
-        // Create the database filter.
-        let mut builder = EthDatabaseFilterBuilder::<filter::Log>::default();
-        builder = if block_hash.is_some() {
+        if let Some(block_hash) = filter.get_block_hash() {
             // We filter by block hash on matching the exact block hash.
-            builder.with_block_hash(&block_hash.unwrap())
+            let query = LogQuery::new(LogBlocks::Hash(block_hash), &filter);
+            let logs = self.database().logs(&query, max_logs.map(|limit| limit.saturating_add(1))).await?;
+
+            // A narrower block range can't be suggested, the logs of the block must be paged.
+            if let Some(limit) = max_logs {
+                if logs.len() as u64 > limit {
+                    return Err(LogsError::BlockResultsExceeded { limit }.into());
+                }
+            }
+            return Ok(FilterChanges::Logs(logs));
+        }
+
+        let Some((from, to)) = self.logs_block_range(&filter).await? else {
+            return Ok(FilterChanges::Empty);
+        };
+        if let Some(max_range) = config().max_logs_block_range {
+            if to - from >= max_range {
+                return Err(LogsError::BlockRangeExceeded(max_range).into());
+            }
+        }
+
+        let range_logs = self.range_logs(&filter, from, to, max_logs).await?;
+        if let (Some(limit), Some(exceeding_block)) = (max_logs, range_logs.exceeding_block) {
+            // The logs of the blocks before the exceeding block fit within the limit.
+            let to = exceeding_block.saturating_sub(1).max(from);
+            return Err(LogsError::ResultsExceeded { limit, from, to }.into());
+        }
+
+        Ok(FilterChanges::Logs(range_logs.logs))
+    }
+
+    async fn get_logs_paged(&self, filter: Filter, cursor: Option<LogsCursor>) -> EthApiResult<LogsPage> {
+        let page_size = config().max_logs.unwrap_or(DEFAULT_LOGS_PAGE_SIZE);
+
+        if let Some(block_hash) = filter.get_block_hash() {
+            // The logs of a single block are paged by their position in the block.
+            let offset = cursor.map_or(0, |cursor| cursor.offset.to::<u64>());
+            let query = LogQuery::new(LogBlocks::Hash(block_hash), &filter);
+            return self.block_logs_page(query, offset, page_size, None).await;
+        }
+
+        let Some((from, to)) = self.logs_block_range(&filter).await? else {
+            return Ok(LogsPage::default());
+        };
+        let (from, offset) = match cursor {
+            Some(cursor) if cursor.block_number.to::<u64>() >= from => (cursor.block_number.to(), cursor.offset.to()),
+            _ => (from, 0),
+        };
+        if from > to {
+            return Ok(LogsPage::default());
+        }
+        let next_block = from.checked_add(1).filter(|block| *block <= to);
+
+        // The page continues the logs of a block which exceeded the page size.
+        if offset > 0 {
+            let query = LogQuery::new(LogBlocks::Range(from, from), &filter);
+            return self.block_logs_page(query, offset, page_size, next_block).await;
+        }
+
+        let page_to = config().max_logs_block_range.map_or(to, |max_range| to.min(from.saturating_add(max_range - 1)));
+
+        let RangeLogs { logs, exceeding_block } = self.range_logs(&filter, from, page_to, Some(page_size)).await?;
+        let (logs, next_block) = match exceeding_block {
+            // The first block alone exceeds the page size, its logs are paged by position.
+            Some(block) if block == from => {
+                let query = LogQuery::new(LogBlocks::Range(from, from), &filter);
+                return self.block_logs_page(query, 0, page_size, next_block).await;
+            }
+            Some(block) => (logs, Some(block)),
+            None => (logs, page_to.checked_add(1)),
+        };
+        let cursor = next_block.filter(|block| *block <= to).map(|block| LogsCursor::new(block, 0));
+
+        Ok(LogsPage { logs, cursor })
+    }
+}
+
+/// The logs of a block range, cut before the first block whose logs exceed the limit.
+#[derive(Debug, Default)]
+struct RangeLogs {
+    /// The logs of the whole range, or of the blocks before the exceeding block.
+    logs: Vec<Log>,
+    /// The first block whose logs exceed the limit, if any.
+    exceeding_block: Option<u64>,
+}
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the block range of the filter, bounded by the current block. Returns `None` if
+    /// the range is empty.
+    async fn logs_block_range(&self, filter: &Filter) -> EthApiResult<Option<(u64, u64)>> {
+        let current_block = self.block_number().await?;
+        let current_block =
+            current_block.try_into().map_err(|_| EthApiError::UnknownBlockNumber(Some(current_block.to())))?;
+
+        let from = match filter.block_option.get_from_block() {
+            Some(tag) => self.tag_into_block_number(*tag).await?,
+            None => 0,
+        };
+        let to = match filter.block_option.get_to_block() {
+            Some(tag) => self.tag_into_block_number(*tag).await?,
+            None => current_block,
+        };
+
+        Ok(match (from, to) {
+            (from, to) if from > current_block || to < from => None,
+            (from, to) if to > current_block => Some((from, current_block)),
+            other => Some(other),
+        })
+    }
+
+    /// Returns the page of the logs of the single block of the query which starts at the given
+    /// position in the block. The page after the last logs of the block starts at the next
+    /// block, if any.
+    async fn block_logs_page(
+        &self,
+        query: LogQuery,
+        offset: u64,
+        page_size: u64,
+        next_block: Option<u64>,
+    ) -> EthApiResult<LogsPage> {
+        let query = query.with_skip(offset);
+        let mut logs = self.database().logs(&query, Some(page_size.saturating_add(1))).await?;
+        let cursor = if logs.len() as u64 > page_size {
+            let block_number = logs.first().and_then(|log| log.block_number).unwrap_or_default();
+            Some(LogsCursor::new(block_number, offset.saturating_add(page_size)))
         } else {
-            let current_block = self.block_number().await?;
-            let current_block =
-                current_block.try_into().map_err(|_| EthApiError::UnknownBlockNumber(Some(current_block.to())))?;
+            next_block.map(|block| LogsCursor::new(block, 0))
+        };
+        logs.truncate(usize::try_from(page_size).unwrap_or(usize::MAX));
+        Ok(LogsPage { logs, cursor })
+    }
 
-            let from = filter.get_from_block().unwrap_or_default();
-            let to = filter.get_to_block().unwrap_or(current_block);
+    /// Returns the logs matching the filter within the block range, bounds included.
+    ///
+    /// If the logs exceed the limit, only the logs of the blocks before the first exceeding
+    /// block are returned, which all fit within the limit.
+    ///
+    /// The logs are only queried for the candidate blocks, i.e. the blocks whose logs bloom
+    /// matches the addresses and topics. The blooms are read from the headers by batches of
+    /// consecutive block numbers on the block number index, projected to the number and the
+    /// bloom, so scanning them doesn't need a separate bitmap index per block range.
+    async fn range_logs(&self, filter: &Filter, from: u64, to: u64, limit: Option<u64>) -> EthApiResult<RangeLogs> {
+        // Without addresses nor topics, every block of the range is a candidate.
+        let use_blooms = !filter.address.is_empty() || !filter.topics.iter().all(Topic::is_empty);
+        let address_filter = FilteredParams::address_filter(&filter.address);
+        let topics_filter = FilteredParams::topics_filter(&filter.topics);
 
-            let (from, to) = match (from, to) {
-                (from, to) if from > current_block || to < from => return Ok(FilterChanges::Empty),
-                (from, to) if to > current_block => (from, current_block),
-                other => other,
+        let mut logs = Vec::new();
+        let mut remaining = limit;
+        let mut start = from;
+        loop {
+            // Keep the blocks whose logs bloom matches the addresses and topics, scanning the
+            // headers by batches. The batch ends at the last scanned header, unless all the
+            // headers up to the end of the range were scanned.
+            let (end, candidates) = if use_blooms {
+                let blooms = self.database().logs_blooms(start, to, LOGS_BLOOM_SCAN_BATCH_SIZE).await?;
+                let end = match blooms.last() {
+                    Some(last) if blooms.len() as u64 == LOGS_BLOOM_SCAN_BATCH_SIZE => last.number,
+                    _ => to,
+                };
+                let excluded = bloom_excluded_blocks(&blooms, &address_filter, &topics_filter);
+                (end, candidate_ranges(start, end, &excluded))
+            } else {
+                (to, vec![(start, to)])
             };
-            // We filter by block number using $gte and $lte.
-            builder.with_block_number_range(from, to)
-        };
 
-        // TODO: this will work for now but isn't very efficient. Would need to:
-        // 1. Create the bloom filter from the topics
-        // 2. Query the database for logs within block range with the bloom filter
-        // 3. Filter this reduced set of logs by the topics
-        // 4. Limit the number of logs returned
-
-        // Convert the topics to a MongoDB filter and add it to the database filter
-        builder = builder.with_topics(&filter.topics);
-
-        // Add the addresses
-        builder = builder.with_addresses(&filter.address.into_iter().collect::<Vec<_>>());
-
-        Ok(FilterChanges::Logs(
-            self.database()
-                .get_and_map_to::<_, StoredLog>(
-                    builder.build(),
-                    (*MAX_LOGS).map(|limit| FindOpts::default().with_limit(limit)),
-                )
-                .await?,
-        ))
+            if !candidates.is_empty() {
+                let batch_query = |end| LogQuery::new(LogBlocks::Ranges(ranges_up_to(&candidates, end)), filter);
+
+                // Query one more log than the remaining ones to detect when the limit is exceeded.
+                let batch = self
+                    .database()
+                    .logs(&batch_query(end), remaining.map(|remaining| remaining.saturating_add(1)))
+                    .await?;
+
+                if let Some(remaining) = remaining.as_mut() {
+                    if batch.len() as u64 > *remaining {
+                        let exceeding_block =
+                            self.database().nth_log_block_number(&batch_query(end), *remaining).await?.unwrap_or(start);
+                        if exceeding_block > start {
+                            logs.extend(self.database().logs(&batch_query(exceeding_block - 1), None).await?);
+                        }
+                        return Ok(RangeLogs { logs, exceeding_block: Some(exceeding_block) });
+                    }
+                    *remaining -= batch.len() as u64;
+                }
+                logs.extend(batch);
+            }
+
+            if end == to {
+                break;
+            }
+            start = end + 1;
+        }
+
+        Ok(RangeLogs { logs, exceeding_block: None })
+    }
+}
+
+/// Returns the ranges of the block numbers between `start` and `end`, bounds included, which
+/// aren't excluded. The excluded block numbers are sorted.
+fn candidate_ranges(start: u64, end: u64, excluded: &[u64]) -> Vec<(u64, u64)> {
+    let mut ranges = Vec::new();
+    let mut next = Some(start);
+    for &number in excluded.iter().filter(|number| (start..=end).contains(*number)) {
+        match next {
+            Some(first) if first < number => ranges.push((first, number - 1)),
+            Some(_) => {}
+            None => break,
+        }
+        next = number.checked_add(1);
+    }
+    if let Some(first) = next.filter(|first| *first <= end) {
+        ranges.push((first, end));
+    }
+    ranges
+}
+
+/// Returns the parts of the sorted block ranges up to the given block number, included.
+fn ranges_up_to(ranges: &[(u64, u64)], end: u64) -> Vec<(u64, u64)> {
+    ranges.iter().filter(|(from, _)| *from <= end).map(|(from, to)| (*from, (*to).min(end))).collect()
+}
+
+/// Returns the numbers of the blocks whose logs bloom proves that they don't contain
+/// logs matching the address and topics bloom filters.
+///
+/// Blocks without a header are never excluded, since their logs can't be ruled out.
+fn bloom_excluded_blocks(
+    blooms: &[HeaderBloom],
+    address_filter: &BloomFilter,
+    topics_filter: &[BloomFilter],
+) -> Vec<u64> {
+    blooms
+        .iter()
+        .filter(|header| {
+            !FilteredParams::matches_address(header.logs_bloom, address_filter)
+                || !FilteredParams::matches_topics(header.logs_bloom, topics_filter)
+        })
+        .map(|header| header.number)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bloom, B256};
+
+    fn header_bloom(number: u64, address: Address, topics: &[B256]) -> HeaderBloom {
+        let mut logs_bloom = Bloom::default();
+        logs_bloom.accrue_raw_log(address, topics);
+        HeaderBloom { number, logs_bloom }
+    }
+
+    #[test]
+    fn test_bloom_excluded_blocks_address() {
+        // Given
+        let address = Address::with_last_byte(1);
+        let blooms = [
+            header_bloom(1, address, &[]),
+            header_bloom(2, Address::with_last_byte(2), &[]),
+            header_bloom(3, address, &[B256::with_last_byte(1)]),
+        ];
+        let filter = Filter::new().address(address);
+
+        // When
+        let excluded = bloom_excluded_blocks(
+            &blooms,
+            &FilteredParams::address_filter(&filter.address),
+            &FilteredParams::topics_filter(&filter.topics),
+        );
+
+        // Then
+        assert_eq!(excluded, vec![2]);
+    }
+
+    #[test]
+    fn test_bloom_excluded_blocks_topics() {
+        // Given
+        let address = Address::with_last_byte(1);
+        let (topic_one, topic_two) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let blooms = [
+            header_bloom(1, address, &[topic_one]),
+            header_bloom(2, address, &[topic_two, topic_one]),
+            header_bloom(3, address, &[topic_two]),
+        ];
+        let filter = Filter::new().event_signature(topic_one).topic1(topic_one);
+
+        // When
+        let excluded = bloom_excluded_blocks(
+            &blooms,
+            &FilteredParams::address_filter(&filter.address),
+            &FilteredParams::topics_filter(&filter.topics),
+        );
+
+        // Then
+        // The bloom doesn't keep track of the position of the topics, block 2 is a false positive.
+        assert_eq!(excluded, vec![3]);
+    }
+
+    #[test]
+    fn test_candidate_ranges() {
+        // When & Then
+        assert_eq!(candidate_ranges(1, 10, &[]), vec![(1, 10)]);
+        assert_eq!(candidate_ranges(1, 10, &[1, 2, 5, 10]), vec![(3, 4), (6, 9)]);
+        assert_eq!(candidate_ranges(1, 3, &[1, 2, 3]), vec![]);
+        assert_eq!(candidate_ranges(5, 6, &[1, 6, 9]), vec![(5, 5)]);
+        assert_eq!(candidate_ranges(u64::MAX - 1, u64::MAX, &[u64::MAX]), vec![(u64::MAX - 1, u64::MAX - 1)]);
+    }
+
+    #[test]
+    fn test_ranges_up_to() {
+        // Given
+        let ranges = [(1, 4), (6, 9)];
+
+        // When & Then
+        assert_eq!(ranges_up_to(&ranges, 7), vec![(1, 4), (6, 7)]);
+        assert_eq!(ranges_up_to(&ranges, 5), vec![(1, 4)]);
+        assert_eq!(ranges_up_to(&ranges, 0), vec![]);
+    }
+
+    #[test]
+    fn test_bloom_excluded_blocks_no_filter() {
+        // Given
+        let blooms =
+            [header_bloom(1, Address::with_last_byte(1), &[]), HeaderBloom { number: 2, ..Default::default() }];
+        let filter = Filter::new();
+
+        // When
+        let excluded = bloom_excluded_blocks(
+            &blooms,
+            &FilteredParams::address_filter(&filter.address),
+            &FilteredParams::topics_filter(&filter.topics),
+        );
+
+        // Then
+        assert!(excluded.is_empty());
     }
 }
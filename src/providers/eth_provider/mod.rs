@@ -1,9 +1,11 @@
 pub mod blocks;
+pub mod cache;
 pub mod chain;
 pub mod constant;
 pub mod contracts;
 pub mod database;
 pub mod error;
+pub mod finality;
 pub mod gas;
 pub mod logs;
 pub mod provider;
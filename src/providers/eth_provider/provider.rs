@@ -1,20 +1,21 @@
 use super::{
+    cache::ProviderCache,
     constant::CALL_REQUEST_GAS_LIMIT,
-    database::{ethereum::EthereumBlockStore, Database},
-    error::{EthApiError, EvmError, ExecutionError, TransactionError},
+    database::ethereum::{EthereumBlockStore, EthereumStore},
+    error::{CairoError, EthApiError, EvmError, ExecutionError, TransactionError},
     starknet::kakarot_core::{
         self,
         core::{CallInput, KakarotCoreReader, Uint256},
-        KAKAROT_ADDRESS,
     },
 };
 use crate::{
+    config::config,
     constants::ETH_CHAIN_ID,
     into_via_try_wrapper, into_via_wrapper,
     models::block::{EthBlockId, EthBlockNumberOrTag},
     providers::{
         eth_provider::{BlockProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider},
-        sn_provider::StarknetProvider,
+        sn_provider::{StarknetProvider, STARKNET_METRICS},
     },
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
@@ -26,6 +27,7 @@ use itertools::Itertools;
 use mongodb::bson::doc;
 use num_traits::cast::ToPrimitive;
 use starknet::core::types::Felt;
+use std::sync::{atomic::AtomicU64, Arc};
 use tracing::{instrument, Instrument};
 #[cfg(feature = "hive")]
 use {
@@ -59,8 +61,11 @@ impl<T> EthereumProvider for T where
 /// the rest is fetched from the Starknet Provider.
 #[derive(Debug, Clone)]
 pub struct EthDataProvider<SP: starknet::providers::Provider + Send + Sync> {
-    database: Database,
+    database: Arc<dyn EthereumStore>,
     starknet_provider: StarknetProvider<SP>,
+    cache: Arc<ProviderCache>,
+    /// The last known block number accepted on L1.
+    finalized: Arc<AtomicU64>,
     pub chain_id: u64,
 }
 
@@ -69,10 +74,20 @@ where
     SP: starknet::providers::Provider + Send + Sync,
 {
     /// Returns a reference to the database.
-    pub const fn database(&self) -> &Database {
+    pub const fn database(&self) -> &Arc<dyn EthereumStore> {
         &self.database
     }
 
+    /// Returns a reference to the caches of the provider.
+    pub fn cache(&self) -> &ProviderCache {
+        &self.cache
+    }
+
+    /// Returns the last known block number accepted on L1, see `finalized_block_number`.
+    pub(crate) fn finalized(&self) -> &AtomicU64 {
+        &self.finalized
+    }
+
     /// Returns a reference to the Starknet provider.
     pub const fn starknet_provider(&self) -> &StarknetProvider<SP> {
         &self.starknet_provider
@@ -88,8 +103,16 @@ impl<SP> EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
-    pub fn new(database: Database, starknet_provider: StarknetProvider<SP>) -> Self {
-        Self { database, starknet_provider, chain_id: *ETH_CHAIN_ID }
+    pub fn new(database: Arc<dyn EthereumStore>, starknet_provider: StarknetProvider<SP>) -> Self {
+        // The caches are only kept up to date for the storages written along the pending block
+        let cache_size = if database.is_cacheable() { config().provider_cache_size } else { 0 };
+        Self {
+            database,
+            starknet_provider,
+            cache: Arc::new(ProviderCache::new(cache_size)),
+            finalized: Arc::default(),
+            chain_id: *ETH_CHAIN_ID,
+        }
     }
 
     /// Prepare the call input for an estimate gas or call from a transaction request.
@@ -119,8 +142,9 @@ where
 
         // We cannot unwrap_or_default() here because Kakarot.eth_call will
         // Reject transactions with gas_price < Kakarot.base_fee
+        // The fee cap is the gas price of an EIP-1559 request.
         let gas_price = {
-            let gas_price = match request.gas_price {
+            let gas_price = match request.gas_price.or(request.max_fee_per_gas) {
                 Some(gas_price) => U256::from(gas_price),
                 None => self.gas_price().await?,
             };
@@ -155,24 +179,29 @@ where
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
         let call_input = self.prepare_call_input(request, block_id).await?;
 
-        let kakarot_contract = KakarotCoreReader::new(*KAKAROT_ADDRESS, self.starknet_provider_inner());
+        let kakarot_contract =
+            KakarotCoreReader::new(config().starknet.kakarot_address, self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::eth_call");
-        let call_output = kakarot_contract
-            .eth_call(
-                &call_input.nonce,
-                &call_input.from,
-                &call_input.to,
-                &call_input.gas_limit,
-                &call_input.gas_price,
-                &call_input.value,
-                &call_input.calldata.len().into(),
-                &CairoArrayLegacy(call_input.calldata),
-                &Felt::ZERO,
-                &CairoArrayLegacy(vec![]),
+        let call_output = STARKNET_METRICS
+            .observe(
+                "eth_call",
+                kakarot_contract
+                    .eth_call(
+                        &call_input.nonce,
+                        &call_input.from,
+                        &call_input.to,
+                        &call_input.gas_limit,
+                        &call_input.gas_price,
+                        &call_input.value,
+                        &call_input.calldata.len().into(),
+                        &CairoArrayLegacy(call_input.calldata),
+                        &Felt::ZERO,
+                        &CairoArrayLegacy(vec![]),
+                    )
+                    .block_id(starknet_block_id)
+                    .call()
+                    .instrument(span),
             )
-            .block_id(starknet_block_id)
-            .call()
-            .instrument(span)
             .await
             .map_err(ExecutionError::from)?;
 
@@ -183,7 +212,8 @@ where
         Ok(return_data)
     }
 
-    /// Estimate the gas used in Kakarot for the given request.
+    /// Estimate the gas used in Kakarot for the given request, and check that relaying it
+    /// wouldn't exceed the Starknet execution resources limits by simulating it.
     pub(crate) async fn estimate_gas_inner(
         &self,
         request: TransactionRequest,
@@ -192,32 +222,47 @@ where
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
         let call_input = self.prepare_call_input(request, block_id).await?;
 
-        let kakarot_contract = KakarotCoreReader::new(*KAKAROT_ADDRESS, self.starknet_provider_inner());
+        let kakarot_contract =
+            KakarotCoreReader::new(config().starknet.kakarot_address, self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::eth_estimate_gas");
-        let estimate_gas_output = kakarot_contract
-            .eth_estimate_gas(
-                &call_input.nonce,
-                &call_input.from,
-                &call_input.to,
-                &call_input.gas_limit,
-                &call_input.gas_price,
-                &call_input.value,
-                &call_input.calldata.len().into(),
-                &CairoArrayLegacy(call_input.calldata),
-                &Felt::ZERO,
-                &CairoArrayLegacy(vec![]),
+        let estimate_gas_output = STARKNET_METRICS
+            .observe(
+                "eth_estimate_gas",
+                kakarot_contract
+                    .eth_estimate_gas(
+                        &call_input.nonce,
+                        &call_input.from,
+                        &call_input.to,
+                        &call_input.gas_limit,
+                        &call_input.gas_price,
+                        &call_input.value,
+                        &call_input.calldata.len().into(),
+                        &CairoArrayLegacy(call_input.calldata.clone()),
+                        &Felt::ZERO,
+                        &CairoArrayLegacy(vec![]),
+                    )
+                    .block_id(starknet_block_id)
+                    .call()
+                    .instrument(span),
             )
-            .block_id(starknet_block_id)
-            .call()
-            .instrument(span)
             .await
-            .map_err(ExecutionError::from)?;
+            .map_err(|err| match ExecutionError::from(err) {
+                // The transaction would exceed the Starknet execution resources limits once relayed.
+                ExecutionError::CairoVm(CairoError::VmOutOfResources) => {
+                    EthApiError::from(TransactionError::ExceedsStarknetResources)
+                }
+                err => err.into(),
+            })?;
 
         let return_data = estimate_gas_output.return_data;
         if estimate_gas_output.success == Felt::ZERO {
             return Err(ExecutionError::from(EvmError::from(return_data.0)).into());
         }
         let required_gas = estimate_gas_output.required_gas.to_u128().ok_or(TransactionError::GasOverflow)?;
+
+        // Kakarot's estimation doesn't account for the Starknet resources limits, which
+        // only the simulation of the invoke transaction running the call enforces.
+        self.simulate_call_input(&call_input, starknet_block_id).await?;
         Ok(required_gas)
     }
 
@@ -249,6 +294,12 @@ where
                             Ok(starknet::core::types::BlockId::Number(number))
                         }
                     }
+                    // Starknet has no finality tags, hence the Starknet block of the EVM block they
+                    // resolve to is used.
+                    BlockNumberOrTag::Finalized | BlockNumberOrTag::Safe => {
+                        let number = self.tag_into_block_number(number_or_tag).await?;
+                        self.starknet_block_id(number).await?.ok_or(EthApiError::UnknownBlockNumber(Some(number)))
+                    }
                     _ => Ok(EthBlockNumberOrTag::from(number_or_tag).into()),
                 }
             }
@@ -264,11 +315,13 @@ where
             BlockNumberOrTag::Earliest => Ok(0),
             // Converts the tag containing a specific block number into a `U64`.
             BlockNumberOrTag::Number(number) => Ok(number),
-            // Returns `self.block_number()` which is the block number of the latest finalized block.
-            BlockNumberOrTag::Latest | BlockNumberOrTag::Finalized | BlockNumberOrTag::Safe => {
-                self.block_number().await.map(|x| x.to())
-            }
-            // Adds 1 to the block number of the latest finalized block.
+            // Returns `self.block_number()` which is the block number of the latest sealed block.
+            BlockNumberOrTag::Latest => self.block_number().await.map(|x| x.to()),
+            // Returns the block number of the latest block accepted on L1.
+            BlockNumberOrTag::Finalized => self.finalized_block_number().await,
+            // Returns the block number of the block configured as safe.
+            BlockNumberOrTag::Safe => self.safe_block_number().await,
+            // Adds 1 to the block number of the latest sealed block.
             BlockNumberOrTag::Pending => Ok(self.block_number().await?.to::<u64>().saturating_add(1)),
         }
     }
@@ -317,7 +370,7 @@ where
         if contract_not_found(&maybe_is_initialized) {
             let execution = ExecutionV1::new(
                 vec![Call {
-                    to: *KAKAROT_ADDRESS,
+                    to: config().starknet.kakarot_address,
                     selector: get_selector_from_name("deploy_externally_owned_account").unwrap(),
                     calldata: vec![into_via_wrapper!(signer)],
                 }],
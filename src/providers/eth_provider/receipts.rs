@@ -1,17 +1,26 @@
-use super::database::{filter::EthDatabaseFilterBuilder, types::receipt::StoredTransactionReceipt};
-use crate::providers::eth_provider::{
-    database::{
-        ethereum::EthereumBlockStore,
-        filter::{self},
-        types::receipt::ExtendedTxReceipt,
+use crate::providers::{
+    eth_provider::{
+        constant::STARKNET_EXECUTION_COST_CONCURRENCY,
+        database::{
+            ethereum::{EthereumBlockStore, EthereumReceiptStore, EthereumTransactionStore},
+            types::{receipt::ExtendedTxReceipt, transaction::StarknetExecutionCost},
+        },
+        error::KakarotError,
+        provider::{EthApiResult, EthDataProvider},
     },
-    provider::{EthApiResult, EthDataProvider},
+    sn_provider::STARKNET_METRICS,
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::B256;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
+use futures::{stream, StreamExt};
+use serde_json::Value;
+use starknet::{
+    core::types::{StarknetError, TransactionReceipt},
+    providers::{Provider, ProviderError},
+};
+use tracing::Instrument;
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -29,8 +38,21 @@ where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn transaction_receipt(&self, hash: B256) -> EthApiResult<Option<ExtendedTxReceipt>> {
-        let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&hash).build();
-        Ok(self.database().get_one::<StoredTransactionReceipt>(filter, None).await?.map(Into::into))
+        if let Some(receipt) = self.cache().receipt(&hash) {
+            return Ok(Some(receipt));
+        }
+
+        let Some(receipt) = self.database().receipt(&hash).await? else {
+            return Ok(None);
+        };
+        // Only cache the receipt once the Starknet execution cost was successfully resolved.
+        let execution_cost = self.starknet_execution_cost(&hash).await;
+        let is_resolved = execution_cost.is_ok();
+        let receipt = with_starknet_execution_cost(receipt, execution_cost);
+        if is_resolved {
+            self.cache().insert_receipt(&receipt);
+        }
+        Ok(Some(receipt))
     }
 
     async fn block_receipts(&self, block_id: Option<BlockId>) -> EthApiResult<Option<Vec<ExtendedTxReceipt>>> {
@@ -41,19 +63,106 @@ where
                     return Ok(None);
                 }
 
-                let filter =
-                    EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number(block_number).build();
-                let tx: Vec<StoredTransactionReceipt> = self.database().get(filter, None).await?;
-                Ok(Some(tx.into_iter().map(Into::into).collect()))
+                let receipts = self.database().receipts(block_number.into()).await?;
+                Ok(Some(self.with_starknet_execution_costs(receipts).await))
             }
             BlockId::Hash(hash) => {
                 if !self.database().block_exists(hash.block_hash.into()).await? {
                     return Ok(None);
                 }
-                let filter =
-                    EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_hash(&hash.block_hash).build();
-                Ok(Some(self.database().get_and_map_to::<_, StoredTransactionReceipt>(filter, None).await?))
+                let receipts = self.database().receipts(hash.block_hash.into()).await?;
+                Ok(Some(self.with_starknet_execution_costs(receipts).await))
             }
         }
     }
 }
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the execution cost of the Starknet transaction linked to the given Ethereum
+    /// transaction hash, if any. The cost is fetched from Starknet once the transaction is
+    /// included in a block, and then cached in the database.
+    async fn starknet_execution_cost(&self, eth_hash: &B256) -> EthApiResult<Option<StarknetExecutionCost>> {
+        let Some(hash_mapping) = self.database().transaction_hashes(eth_hash).await? else {
+            return Ok(None);
+        };
+        let starknet_hash = hash_mapping.hashes.starknet_hash;
+
+        // The cached cost is stale if the transaction was relayed again since.
+        if let Some(execution_cost) = hash_mapping.execution_cost.filter(|cost| cost.starknet_hash == starknet_hash) {
+            return Ok(Some(execution_cost));
+        }
+
+        let span = tracing::span!(tracing::Level::INFO, "sn::transaction_receipt");
+        let receipt = match STARKNET_METRICS
+            .observe(
+                "transaction_receipt",
+                self.starknet_provider_inner().get_transaction_receipt(starknet_hash).instrument(span),
+            )
+            .await
+        {
+            Ok(receipt) => receipt,
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => return Ok(None),
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+
+        // Kakarot transactions are always relayed through invoke transactions.
+        let TransactionReceipt::Invoke(invoke_receipt) = receipt.receipt else {
+            return Ok(None);
+        };
+        let execution_cost = StarknetExecutionCost {
+            starknet_hash,
+            actual_fee: invoke_receipt.actual_fee,
+            execution_resources: invoke_receipt.execution_resources,
+        };
+
+        if receipt.block.is_block() {
+            self.database().update_starknet_execution_cost(eth_hash, execution_cost.clone()).await?;
+        }
+
+        Ok(Some(execution_cost))
+    }
+
+    /// Adds the Starknet execution cost to each receipt fields.
+    async fn with_starknet_execution_costs(&self, receipts: Vec<ExtendedTxReceipt>) -> Vec<ExtendedTxReceipt> {
+        stream::iter(receipts)
+            .map(|receipt| async move {
+                let execution_cost = self.starknet_execution_cost(&receipt.transaction_hash).await;
+                with_starknet_execution_cost(receipt, execution_cost)
+            })
+            .buffered(STARKNET_EXECUTION_COST_CONCURRENCY)
+            .collect()
+            .await
+    }
+}
+
+/// Adds the Starknet transaction hash, actual fee and execution resources to the receipt fields.
+/// Failing to fetch the Starknet execution cost doesn't fail the receipt query.
+fn with_starknet_execution_cost(
+    mut receipt: ExtendedTxReceipt,
+    execution_cost: EthApiResult<Option<StarknetExecutionCost>>,
+) -> ExtendedTxReceipt {
+    match execution_cost {
+        Ok(Some(execution_cost)) => {
+            receipt.other.insert(
+                "starknetTransactionHash".to_string(),
+                Value::String(execution_cost.starknet_hash.to_fixed_hex_string()),
+            );
+            receipt.other.insert(
+                "starknetActualFee".to_string(),
+                serde_json::to_value(execution_cost.actual_fee).unwrap_or_default(),
+            );
+            receipt.other.insert(
+                "starknetExecutionResources".to_string(),
+                serde_json::to_value(execution_cost.execution_resources).unwrap_or_default(),
+            );
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tracing::warn!(?err, hash = ?receipt.transaction_hash, "failed to fetch the Starknet execution cost");
+        }
+    }
+    receipt
+}
@@ -1,6 +1,7 @@
 #![allow(non_snake_case, clippy::derive_partial_eq_without_eq)]
 pub mod kakarot_core;
 pub mod relayer;
+pub mod simulation;
 
 use cainome::rs::abigen_legacy;
 use starknet::core::types::Felt;
@@ -1,8 +1,12 @@
 use crate::{
+    config::config,
     constants::STARKNET_CHAIN_ID,
     models::transaction::transaction_data_to_starknet_calldata,
     providers::eth_provider::{
-        database::{ethereum::EthereumTransactionStore, types::transaction::EthStarknetHashes, Database},
+        database::{
+            ethereum::{EthereumStore, EthereumTransactionStore},
+            types::transaction::EthStarknetHashes,
+        },
         error::{SignatureError, TransactionError},
         provider::EthApiResult,
         starknet::kakarot_core::{starknet_address, EXECUTE_FROM_OUTSIDE},
@@ -16,19 +20,13 @@ use starknet::{
     signers::{LocalWallet, SigningKey},
 };
 use std::{
-    env::var,
     ops::Deref,
-    str::FromStr,
     sync::{Arc, LazyLock},
 };
 
 /// Signer for all relayers
-static RELAYER_SIGNER: LazyLock<LocalWallet> = LazyLock::new(|| {
-    LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
-        Felt::from_str(&var("RELAYER_PRIVATE_KEY").expect("missing relayer private key"))
-            .expect("failed to parse relayer private key"),
-    ))
-});
+static RELAYER_SIGNER: LazyLock<LocalWallet> =
+    LazyLock::new(|| LocalWallet::from_signing_key(SigningKey::from_secret_scalar(config().relayer_private_key)));
 
 /// A relayer holding an account and a balance.
 ///
@@ -40,7 +38,7 @@ pub struct Relayer<SP: Provider + Send + Sync> {
     /// The balance of the relayer
     balance: Felt,
     /// The database used to store the relayer's transaction hashes map (Ethereum -> Starknet)
-    database: Option<Arc<Database>>,
+    database: Option<Arc<dyn EthereumStore>>,
 }
 
 impl<SP> Relayer<SP>
@@ -48,7 +46,7 @@ where
     SP: Provider + Send + Sync,
 {
     /// Create a new relayer with the provided Starknet provider, address, balance.
-    pub fn new(address: Felt, balance: Felt, provider: SP, database: Option<Arc<Database>>) -> Self {
+    pub fn new(address: Felt, balance: Felt, provider: SP, database: Option<Arc<dyn EthereumStore>>) -> Self {
         let relayer = SingleOwnerAccount::new(
             provider,
             RELAYER_SIGNER.clone(),
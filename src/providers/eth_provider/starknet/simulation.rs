@@ -0,0 +1,198 @@
+use crate::{
+    config::config,
+    models::transaction::transaction_data_to_starknet_calldata,
+    providers::{
+        eth_provider::{
+            error::{is_vm_out_of_resources, EthApiError, KakarotError, SignatureError, TransactionError},
+            provider::{EthApiResult, EthDataProvider},
+            starknet::kakarot_core::{core::CallInput, starknet_address, ETH_CALL, EXECUTE_FROM_OUTSIDE},
+        },
+        sn_provider::STARKNET_METRICS,
+    },
+};
+use reth_primitives::TransactionSigned;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{
+        BlockId, BlockTag, BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV1, BroadcastedTransaction, Call,
+        ExecuteInvocation, ExecutionResources, FeeEstimate, Felt, SimulationFlag, StarknetError, TransactionTrace,
+    },
+    providers::{Provider, ProviderError},
+};
+use tracing::Instrument;
+
+/// Error returned when a simulation is required but no simulation account is configured, see
+/// `SIMULATION_ACCOUNT_ADDRESS`.
+const NO_SIMULATION_ACCOUNT: EthApiError = EthApiError::Unsupported("Starknet simulation without a simulation account");
+
+/// The resources consumed on Starknet by the transaction relaying an Ethereum transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StarknetResources {
+    /// The fee estimation of the Starknet transaction.
+    pub fee_estimate: FeeEstimate,
+    /// The resources consumed by the Starknet transaction (steps, builtins, L1 data gas).
+    pub execution_resources: ExecutionResources,
+    /// The revert reason of the Starknet transaction, if it reverted.
+    pub revert_reason: Option<String>,
+}
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    /// Simulates the `execute_from_outside` invoke transaction which would relay the given
+    /// Ethereum transaction on Starknet, and returns the resources it consumes. Fails if no
+    /// simulation account is configured.
+    ///
+    /// Fails with [`TransactionError::ExceedsStarknetResources`] if the transaction would exceed
+    /// the Starknet execution resources limits.
+    pub async fn simulate_transaction(&self, transaction: &TransactionSigned) -> EthApiResult<StarknetResources> {
+        let sender = config().simulation_account_address.ok_or(NO_SIMULATION_ACCOUNT)?;
+
+        // Build the call exactly as the relayer does.
+        let calldata = transaction_data_to_starknet_calldata(transaction, sender)?;
+        let eoa_address = transaction.recover_signer().ok_or(SignatureError::Recovery)?;
+        let call = Call { to: starknet_address(eoa_address), selector: *EXECUTE_FROM_OUTSIDE, calldata };
+
+        self.simulate_calls(sender, &[call], BlockId::Tag(BlockTag::Pending)).await
+    }
+
+    /// Simulates the invoke transaction running the call input through the Kakarot `eth_call`
+    /// entrypoint on the given block, and returns the resources it consumes. Fails if no
+    /// simulation account is configured.
+    ///
+    /// An unsigned request can't go through the signature check of `execute_from_outside`, the
+    /// EVM execution it relays to Kakarot is simulated instead.
+    pub(crate) async fn simulate_call_input(
+        &self,
+        call_input: &CallInput,
+        block_id: BlockId,
+    ) -> EthApiResult<StarknetResources> {
+        let sender = config().simulation_account_address.ok_or(NO_SIMULATION_ACCOUNT)?;
+
+        let mut calldata = vec![
+            call_input.nonce,
+            call_input.from,
+            call_input.to.is_some,
+            call_input.to.value,
+            call_input.gas_limit,
+            call_input.gas_price,
+            call_input.value.low,
+            call_input.value.high,
+            call_input.calldata.len().into(),
+        ];
+        calldata.extend_from_slice(&call_input.calldata);
+        // Empty access list.
+        calldata.push(Felt::ZERO);
+        let call = Call { to: config().starknet.kakarot_address, selector: *ETH_CALL, calldata };
+
+        self.simulate_calls(sender, &[call], block_id).await
+    }
+
+    /// Simulates an invoke transaction of the calls from the sender on the given block,
+    /// skipping the validation and the fee charge.
+    async fn simulate_calls(&self, sender: Felt, calls: &[Call], block_id: BlockId) -> EthApiResult<StarknetResources> {
+        let span = tracing::span!(tracing::Level::INFO, "sn::get_nonce");
+        let nonce = STARKNET_METRICS
+            .observe("get_nonce", self.starknet_provider_inner().get_nonce(block_id, sender).instrument(span))
+            .await
+            .map_err(KakarotError::from)?;
+
+        let transaction =
+            BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(BroadcastedInvokeTransactionV1 {
+                sender_address: sender,
+                calldata: execute_calldata(calls),
+                max_fee: Felt::ZERO,
+                signature: vec![],
+                nonce,
+                is_query: true,
+            }));
+
+        let span = tracing::span!(tracing::Level::INFO, "sn::simulate_transaction");
+        let simulated = match STARKNET_METRICS
+            .observe(
+                "simulate_transaction",
+                self.starknet_provider_inner()
+                    .simulate_transaction(
+                        block_id,
+                        transaction,
+                        [SimulationFlag::SkipValidate, SimulationFlag::SkipFeeCharge],
+                    )
+                    .instrument(span),
+            )
+            .await
+        {
+            Ok(simulated) => simulated,
+            Err(ProviderError::StarknetError(StarknetError::TransactionExecutionError(data)))
+                if is_vm_out_of_resources(&data.execution_error) =>
+            {
+                return Err(TransactionError::ExceedsStarknetResources.into())
+            }
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+
+        let TransactionTrace::Invoke(trace) = simulated.transaction_trace else {
+            return Err(TransactionError::Tracing("expected an invoke transaction trace".into()).into());
+        };
+        let revert_reason = match trace.execute_invocation {
+            ExecuteInvocation::Success(_) => None,
+            ExecuteInvocation::Reverted(reverted) => Some(reverted.revert_reason),
+        };
+        if revert_reason.as_deref().is_some_and(is_vm_out_of_resources) {
+            return Err(TransactionError::ExceedsStarknetResources.into());
+        }
+
+        Ok(StarknetResources {
+            fee_estimate: simulated.fee_estimation,
+            execution_resources: trace.execution_resources,
+            revert_reason,
+        })
+    }
+}
+
+/// Encodes the calls into the calldata of the `__execute__` entrypoint of an account,
+/// following the [`starknet::accounts::ExecutionEncoding::New`] encoding used by the relayers.
+fn execute_calldata(calls: &[Call]) -> Vec<Felt> {
+    let mut calldata = vec![calls.len().into()];
+    for call in calls {
+        calldata.push(call.to);
+        calldata.push(call.selector);
+        calldata.push(call.calldata.len().into());
+        calldata.extend_from_slice(&call.calldata);
+    }
+    calldata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute_calldata() {
+        // Given
+        let calls = [
+            Call { to: Felt::ONE, selector: Felt::from(2), calldata: vec![Felt::from(3), Felt::from(4)] },
+            Call { to: Felt::from(5), selector: Felt::from(6), calldata: vec![] },
+        ];
+
+        // When
+        let calldata = execute_calldata(&calls);
+
+        // Then
+        assert_eq!(
+            calldata,
+            vec![
+                Felt::from(2),
+                Felt::ONE,
+                Felt::from(2),
+                Felt::from(2),
+                Felt::from(3),
+                Felt::from(4),
+                Felt::from(5),
+                Felt::from(6),
+                Felt::ZERO
+            ]
+        );
+    }
+}
@@ -6,9 +6,12 @@ use super::{
 };
 use crate::{
     into_via_wrapper,
-    providers::eth_provider::{
-        provider::{EthApiResult, EthDataProvider},
-        BlockProvider, ChainProvider,
+    providers::{
+        eth_provider::{
+            provider::{EthApiResult, EthDataProvider},
+            BlockProvider, ChainProvider,
+        },
+        sn_provider::STARKNET_METRICS,
     },
 };
 use alloy_eips::BlockId;
@@ -84,8 +87,9 @@ where
         let storage_address = get_storage_var_address("Account_storage", &keys).expect("Storage var name is not ASCII");
 
         let span = tracing::span!(tracing::Level::INFO, "sn::storage");
-        let maybe_storage =
-            contract.storage(&storage_address).block_id(starknet_block_id).call().instrument(span).await;
+        let maybe_storage = STARKNET_METRICS
+            .observe("storage", contract.storage(&storage_address).block_id(starknet_block_id).call().instrument(span))
+            .await;
 
         if contract_not_found(&maybe_storage) || entrypoint_not_found(&maybe_storage) {
             return Ok(U256::ZERO.into());
@@ -102,18 +106,33 @@ where
     async fn get_code(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<Bytes> {
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
 
-        let address = starknet_address(address);
-        let account_contract = AccountContractReader::new(address, self.starknet_provider_inner());
-        let span = tracing::span!(tracing::Level::INFO, "sn::code");
-        let bytecode = account_contract.bytecode().block_id(starknet_block_id).call().instrument(span).await;
-
-        if contract_not_found(&bytecode) || entrypoint_not_found(&bytecode) {
-            return Ok(Bytes::default());
+        // The code at a sealed block number never changes, hence can be cached.
+        let sealed_block_number = match starknet_block_id {
+            starknet::core::types::BlockId::Number(number) => Some(number),
+            _ => None,
+        };
+        if let Some(code) = sealed_block_number.and_then(|number| self.cache().code(address, number)) {
+            return Ok(code);
         }
 
-        let bytecode = bytecode.map_err(ExecutionError::from)?.bytecode.0;
-
-        Ok(Bytes::from(bytecode.into_iter().filter_map(|x| x.to_u8()).collect::<Vec<_>>()))
+        let contract_address = starknet_address(address);
+        let account_contract = AccountContractReader::new(contract_address, self.starknet_provider_inner());
+        let span = tracing::span!(tracing::Level::INFO, "sn::code");
+        let bytecode = STARKNET_METRICS
+            .observe("code", account_contract.bytecode().block_id(starknet_block_id).call().instrument(span))
+            .await;
+
+        let code = if contract_not_found(&bytecode) || entrypoint_not_found(&bytecode) {
+            Bytes::default()
+        } else {
+            let bytecode = bytecode.map_err(ExecutionError::from)?.bytecode.0;
+            Bytes::from(bytecode.into_iter().filter_map(|x| x.to_u8()).collect::<Vec<_>>())
+        };
+
+        if let Some(number) = sealed_block_number {
+            self.cache().insert_code(address, number, &code);
+        }
+        Ok(code)
     }
 
     async fn call(
@@ -1,18 +1,17 @@
 use super::{
-    database::{
-        filter::EthDatabaseFilterBuilder,
-        types::transaction::{ExtendedTransaction, StoredTransaction},
-    },
+    database::{ethereum::EthereumTransactionStore, types::transaction::ExtendedTransaction},
     error::ExecutionError,
     starknet::kakarot_core::{account_contract::AccountContractReader, starknet_address},
     utils::{contract_not_found, entrypoint_not_found},
 };
 use crate::{
     into_via_wrapper,
-    providers::eth_provider::{
-        database::filter::{self},
-        provider::{EthApiResult, EthDataProvider},
-        ChainProvider,
+    providers::{
+        eth_provider::{
+            provider::{EthApiResult, EthDataProvider},
+            ChainProvider,
+        },
+        sn_provider::STARKNET_METRICS,
     },
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
@@ -20,7 +19,6 @@ use alloy_primitives::{Address, B256, U256};
 use alloy_rpc_types::Index;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
 use tracing::Instrument;
 
 #[async_trait]
@@ -53,8 +51,15 @@ where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn transaction_by_hash(&self, hash: B256) -> EthApiResult<Option<ExtendedTransaction>> {
-        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hash(&hash).build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        if let Some(transaction) = self.cache().transaction(&hash) {
+            return Ok(Some(transaction));
+        }
+
+        let transaction = self.database().transaction(&hash).await?;
+        if let Some(transaction) = &transaction {
+            self.cache().insert_transaction(transaction);
+        }
+        Ok(transaction)
     }
 
     async fn transaction_by_block_hash_and_index(
@@ -62,11 +67,7 @@ where
         hash: B256,
         index: Index,
     ) -> EthApiResult<Option<ExtendedTransaction>> {
-        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
-            .with_block_hash(&hash)
-            .with_tx_index(&index)
-            .build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        self.database().transaction_by_block_and_index(hash.into(), index).await
     }
 
     async fn transaction_by_block_number_and_index(
@@ -75,11 +76,7 @@ where
         index: Index,
     ) -> EthApiResult<Option<ExtendedTransaction>> {
         let block_number = self.tag_into_block_number(number_or_tag).await?;
-        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
-            .with_block_number(block_number)
-            .with_tx_index(&index)
-            .build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        self.database().transaction_by_block_and_index(block_number.into(), index).await
     }
 
     async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256> {
@@ -88,7 +85,9 @@ where
         let address = starknet_address(address);
         let account_contract = AccountContractReader::new(address, self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::kkrt_nonce");
-        let maybe_nonce = account_contract.get_nonce().block_id(starknet_block_id).call().instrument(span).await;
+        let maybe_nonce = STARKNET_METRICS
+            .observe("kkrt_nonce", account_contract.get_nonce().block_id(starknet_block_id).call().instrument(span))
+            .await;
 
         if contract_not_found(&maybe_nonce) || entrypoint_not_found(&maybe_nonce) {
             return Ok(U256::ZERO);
@@ -0,0 +1,376 @@
+//! A JSON-RPC transport spreading the Starknet calls over several endpoints.
+//!
+//! The endpoints are tried in the configured order, the first one being the primary. An endpoint
+//! failing `failure_threshold` times in a row is skipped for the `cooldown` (the circuit is
+//! open), after which it is tried again. When all the endpoints are skipped, e.g. the single
+//! endpoint of a deployment without fallback, the calls probe the endpoint whose cooldown ends
+//! first rather than failing until then. The reads are retried with an exponential backoff on
+//! the next endpoint and can be hedged: if the endpoint hasn't answered after the hedge delay,
+//! the call is sent to the next endpoint as well and the first answer is kept. The transactions
+//! are only sent once.
+//!
+//! Only the transport failures count as endpoint failures, the JSON-RPC errors, e.g. a contract
+//! not found, are answers and returned as is.
+
+use futures::future::{select, Either};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use starknet::providers::{
+    jsonrpc::{HttpTransport, HttpTransportError, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport},
+    JsonRpcClient, ProviderRequestData,
+};
+use std::{
+    pin::pin,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// A Starknet provider over the [`FailoverTransport`].
+pub type FailoverProvider = JsonRpcClient<FailoverTransport>;
+
+/// Retries, circuit breaker and hedging of the Starknet calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailoverConfig {
+    /// Number of times a failed read is retried.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled at each retry.
+    pub retry_backoff: Duration,
+    /// Number of consecutive failures after which an endpoint is skipped.
+    pub failure_threshold: u32,
+    /// Time an endpoint is skipped for once it reached the failure threshold.
+    pub cooldown: Duration,
+    /// Time after which a read is also sent to the next endpoint, disabled if `None`.
+    pub hedge_delay: Option<Duration>,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+            hedge_delay: None,
+        }
+    }
+}
+
+/// Errors of the [`FailoverTransport`].
+#[derive(Debug, thiserror::Error)]
+pub enum FailoverError {
+    /// The last endpoint tried failed.
+    #[error(transparent)]
+    Transport(#[from] HttpTransportError),
+    /// The request or the result couldn't be (de)serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Failures of an endpoint.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// Time until which the endpoint is skipped.
+    open_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn is_available(&self, now: Instant) -> bool {
+        self.open_until.map_or(true, |open_until| now >= open_until)
+    }
+
+    fn record_success(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Records the failure, returns true if the endpoint is skipped from now on.
+    fn record_failure(&mut self, now: Instant, config: &FailoverConfig) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let open = self.consecutive_failures >= config.failure_threshold;
+        if open {
+            self.open_until = Some(now + config.cooldown);
+        }
+        open
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Url,
+    transport: HttpTransport,
+    health: Mutex<EndpointHealth>,
+}
+
+/// A request to send to an endpoint.
+#[derive(Debug, Clone, Copy)]
+enum Request<'a> {
+    Single(JsonRpcMethod, &'a Value),
+    Batch(&'a [ProviderRequestData]),
+}
+
+impl Request<'_> {
+    /// Returns true if the request doesn't send transactions, which makes it safe to send
+    /// several times.
+    fn is_idempotent(&self) -> bool {
+        match self {
+            Self::Single(method, _) => !matches!(
+                method,
+                JsonRpcMethod::AddInvokeTransaction
+                    | JsonRpcMethod::AddDeclareTransaction
+                    | JsonRpcMethod::AddDeployAccountTransaction
+            ),
+            Self::Batch(requests) => !requests.iter().any(|request| {
+                matches!(
+                    request,
+                    ProviderRequestData::AddInvokeTransaction(_)
+                        | ProviderRequestData::AddDeclareTransaction(_)
+                        | ProviderRequestData::AddDeployAccountTransaction(_)
+                )
+            }),
+        }
+    }
+}
+
+/// Answer of an endpoint to a [`Request`].
+#[derive(Debug)]
+enum Response {
+    Single(JsonRpcResponse<Value>),
+    Batch(Vec<JsonRpcResponse<Value>>),
+}
+
+/// A JSON-RPC transport over several Starknet endpoints, see the module documentation.
+#[derive(Debug)]
+pub struct FailoverTransport {
+    endpoints: Vec<Endpoint>,
+    config: FailoverConfig,
+}
+
+impl FailoverTransport {
+    /// Creates a transport over the endpoints, in order of preference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no endpoint is given.
+    pub fn new(urls: impl IntoIterator<Item = Url>, config: FailoverConfig) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                transport: HttpTransport::new(url.clone()),
+                url,
+                health: Mutex::new(EndpointHealth::default()),
+            })
+            .collect::<Vec<_>>();
+        assert!(!endpoints.is_empty(), "at least one Starknet endpoint is required");
+        Self { endpoints, config }
+    }
+
+    /// Returns the health of the endpoint.
+    fn health(&self, index: usize) -> EndpointHealth {
+        *self.endpoints[index].health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the indices of the endpoints which aren't skipped, in order of preference.
+    fn available(&self) -> Vec<usize> {
+        let now = Instant::now();
+        (0..self.endpoints.len()).filter(|index| self.health(*index).is_available(now)).collect()
+    }
+
+    /// Returns the indices of the endpoints to send the calls to: the available ones, or the
+    /// endpoint whose cooldown ends first if all of them are skipped.
+    fn candidates(&self) -> Vec<usize> {
+        let available = self.available();
+        if !available.is_empty() {
+            return available;
+        }
+        (0..self.endpoints.len()).min_by_key(|index| self.health(*index).open_until).into_iter().collect()
+    }
+
+    /// Sends the request to the endpoint, recording its health.
+    async fn send_to(&self, index: usize, request: Request<'_>) -> Result<Response, HttpTransportError> {
+        let endpoint = &self.endpoints[index];
+        let response = match request {
+            Request::Single(method, params) => {
+                endpoint.transport.send_request(method, params).await.map(Response::Single)
+            }
+            Request::Batch(requests) => endpoint.transport.send_requests(requests).await.map(Response::Batch),
+        };
+
+        if let Err(err) = &response {
+            let mut health = endpoint.health.lock().unwrap_or_else(PoisonError::into_inner);
+            if health.record_failure(Instant::now(), &self.config) {
+                tracing::warn!(url = %endpoint.url, %err, failures = health.consecutive_failures, "skipping the failing Starknet endpoint");
+            } else {
+                tracing::debug!(url = %endpoint.url, %err, "Starknet endpoint failed");
+            }
+        } else {
+            endpoint.health.lock().unwrap_or_else(PoisonError::into_inner).record_success();
+        }
+        response
+    }
+
+    /// Sends the request to the endpoint, and to the hedge endpoint as well if the first one
+    /// hasn't answered after the hedge delay.
+    async fn send_hedged(
+        &self,
+        index: usize,
+        hedge: Option<(usize, Duration)>,
+        request: Request<'_>,
+    ) -> Result<Response, HttpTransportError> {
+        let mut first = pin!(self.send_to(index, request));
+        let Some((hedge, delay)) = hedge else {
+            return first.await;
+        };
+        if let Ok(response) = tokio::time::timeout(delay, &mut first).await {
+            return response;
+        }
+
+        let second = pin!(self.send_to(hedge, request));
+        match select(first, second).await {
+            Either::Left((Ok(response), _)) | Either::Right((Ok(response), _)) => Ok(response),
+            Either::Left((Err(_), other)) => other.await,
+            Either::Right((Err(_), other)) => other.await,
+        }
+    }
+
+    /// Sends the request, retrying the idempotent ones on the next endpoints.
+    async fn send(&self, request: Request<'_>) -> Result<Response, FailoverError> {
+        let idempotent = request.is_idempotent();
+        let retries = if idempotent { self.config.max_retries } else { 0 };
+
+        let mut attempt = 0;
+        loop {
+            let candidates = self.candidates();
+            let index = candidates[attempt as usize % candidates.len()];
+            let hedge = self
+                .config
+                .hedge_delay
+                .filter(|_| idempotent && candidates.len() > 1)
+                .map(|delay| (candidates[(attempt as usize + 1) % candidates.len()], delay));
+
+            match self.send_hedged(index, hedge, request).await {
+                Ok(response) => return Ok(response),
+                Err(err) if attempt == retries => return Err(err.into()),
+                Err(_) => {
+                    tokio::time::sleep(self.config.retry_backoff.saturating_mul(1 << attempt.min(16))).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Converts the result of the response to the expected type.
+fn typed<R: DeserializeOwned>(response: JsonRpcResponse<Value>) -> Result<JsonRpcResponse<R>, serde_json::Error> {
+    Ok(match response {
+        JsonRpcResponse::Success { id, result } => {
+            JsonRpcResponse::Success { id, result: serde_json::from_value(result)? }
+        }
+        JsonRpcResponse::Error { id, error } => JsonRpcResponse::Error { id, error },
+    })
+}
+
+#[async_trait::async_trait]
+impl JsonRpcTransport for FailoverTransport {
+    type Error = FailoverError;
+
+    async fn send_request<P, R>(&self, method: JsonRpcMethod, params: P) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)?;
+        match self.send(Request::Single(method, &params)).await? {
+            Response::Single(response) => Ok(typed(response)?),
+            Response::Batch(_) => unreachable!("single request answered with a batch"),
+        }
+    }
+
+    async fn send_requests<R>(&self, requests: R) -> Result<Vec<JsonRpcResponse<Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        match self.send(Request::Batch(requests.as_ref())).await? {
+            Response::Batch(responses) => Ok(responses),
+            Response::Single(_) => unreachable!("batch request answered with a single response"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        // Given
+        let config = FailoverConfig { failure_threshold: 2, cooldown: Duration::from_secs(10), ..Default::default() };
+        let mut health = EndpointHealth::default();
+        let now = Instant::now();
+
+        // When
+        let opened_first = health.record_failure(now, &config);
+        let opened_second = health.record_failure(now, &config);
+
+        // Then
+        assert!(!opened_first);
+        assert!(opened_second);
+        assert!(!health.is_available(now + Duration::from_secs(5)));
+        assert!(health.is_available(now + Duration::from_secs(10)));
+        health.record_success();
+        assert!(health.is_available(now));
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failover_transport_skips_failing_endpoints() {
+        // Given
+        let config = FailoverConfig {
+            max_retries: 1,
+            retry_backoff: Duration::ZERO,
+            failure_threshold: 1,
+            ..Default::default()
+        };
+        let transport = FailoverTransport::new(
+            [Url::parse("http://127.0.0.1:1").unwrap(), Url::parse("http://127.0.0.1:2").unwrap()],
+            config,
+        );
+
+        // When
+        let result = transport.send_request::<_, Value>(JsonRpcMethod::BlockNumber, [(); 0]).await;
+
+        // Then
+        assert!(matches!(result, Err(FailoverError::Transport(_))));
+        assert!(transport.available().is_empty());
+        assert!(!Request::Single(JsonRpcMethod::AddInvokeTransaction, &Value::Null).is_idempotent());
+    }
+
+    #[tokio::test]
+    async fn test_failover_transport_probes_when_all_endpoints_are_skipped() {
+        // Given
+        let config = FailoverConfig {
+            max_retries: 0,
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let transport = FailoverTransport::new(
+            [Url::parse("http://127.0.0.1:1").unwrap(), Url::parse("http://127.0.0.1:2").unwrap()],
+            config,
+        );
+        transport.send_to(1, Request::Single(JsonRpcMethod::BlockNumber, &Value::Null)).await.unwrap_err();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        transport.send_to(0, Request::Single(JsonRpcMethod::BlockNumber, &Value::Null)).await.unwrap_err();
+
+        // When
+        let candidates = transport.candidates();
+
+        // Then
+        // The second endpoint failed first, hence its cooldown ends first.
+        assert_eq!(candidates, vec![1]);
+        assert!(matches!(
+            transport.send_request::<_, Value>(JsonRpcMethod::BlockNumber, [(); 0]).await,
+            Err(FailoverError::Transport(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod failover;
 pub mod starknet_provider;
 
-pub use starknet_provider::StarknetProvider;
+pub use failover::{FailoverConfig, FailoverProvider, FailoverTransport};
+pub use starknet_provider::{StarknetProvider, STARKNET_METRICS};
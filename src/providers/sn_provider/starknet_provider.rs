@@ -1,5 +1,8 @@
 use crate::{
     into_via_wrapper,
+    prometheus_handler::{
+        register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, HISTOGRAM_BUCKETS, U64,
+    },
     providers::eth_provider::{
         error::ExecutionError,
         starknet::{ERC20Reader, STARKNET_NATIVE_TOKEN},
@@ -11,9 +14,57 @@ use starknet::{
     core::types::{BlockId, Felt},
     providers::Provider,
 };
-use std::ops::Deref;
+use std::{future::Future, ops::Deref, sync::LazyLock, time::Instant};
 use tracing::Instrument;
 
+/// The metrics of the Starknet calls, shared by all the providers of the process.
+pub static STARKNET_METRICS: LazyLock<StarknetMetrics> =
+    LazyLock::new(|| StarknetMetrics::new().expect("failed to create the Starknet metrics"));
+
+/// Metrics of the Starknet calls, labelled by method. The methods are the ones of the `sn::*`
+/// tracing spans.
+#[derive(Debug, Clone)]
+pub struct StarknetMetrics {
+    /// Histogram over the calls execution times.
+    calls_time: HistogramVec,
+    /// Number of failed calls.
+    calls_failed: CounterVec<U64>,
+}
+
+impl StarknetMetrics {
+    fn new() -> Result<Self, PrometheusError> {
+        Ok(Self {
+            calls_time: HistogramVec::new(
+                HistogramOpts::new("starknet_calls_time", "Total time [μs] of the Starknet calls")
+                    .buckets(HISTOGRAM_BUCKETS.to_vec()),
+                &["method"],
+            )?,
+            calls_failed: CounterVec::new(
+                Opts::new("starknet_calls_failed", "Number of failed Starknet calls"),
+                &["method"],
+            )?,
+        })
+    }
+
+    /// Registers the metrics in the given registry.
+    pub fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+        register(self.calls_time.clone(), registry)?;
+        register(self.calls_failed.clone(), registry)?;
+        Ok(())
+    }
+
+    /// Runs the call, recording its execution time and its failure.
+    pub async fn observe<T, E>(&self, method: &str, call: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+        let now = Instant::now();
+        let result = call.await;
+        self.calls_time.with_label_values(&[method]).observe(now.elapsed().as_micros() as _);
+        if result.is_err() {
+            self.calls_failed.with_label_values(&[method]).inc();
+        }
+        result
+    }
+}
+
 /// A provider wrapper around the Starknet provider to expose utility methods.
 #[derive(Debug, Clone)]
 pub struct StarknetProvider<SP: Provider + Send + Sync> {
@@ -51,7 +102,9 @@ where
 
         // Call the `balanceOf` method on the contract for the given address and block ID, awaiting the result
         let span = tracing::span!(tracing::Level::INFO, "sn::balance");
-        let res = eth_contract.balanceOf(&address).block_id(block_id).call().instrument(span).await;
+        let res = STARKNET_METRICS
+            .observe("balance", eth_contract.balanceOf(&address).block_id(block_id).call().instrument(span))
+            .await;
 
         // Check if the contract was not found or the class hash not declared,
         // returning a default balance of 0 if true.
@@ -71,3 +124,24 @@ where
         Ok(low + (high << 128))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_starknet_metrics_observe() {
+        // Given
+        let metrics = StarknetMetrics::new().unwrap();
+
+        // When
+        let success = metrics.observe("block_number", async { Ok::<_, ()>(1) }).await;
+        let failure = metrics.observe("block_number", async { Err::<u64, _>(()) }).await;
+
+        // Then
+        assert_eq!(success, Ok(1));
+        assert_eq!(failure, Err(()));
+        assert_eq!(metrics.calls_time.with_label_values(&["block_number"]).get_sample_count(), 2);
+        assert_eq!(metrics.calls_failed.with_label_values(&["block_number"]).get(), 1);
+    }
+}
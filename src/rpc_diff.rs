@@ -0,0 +1,293 @@
+//! Field level comparison of JSON-RPC responses, used to check the responses of a node against
+//! captured or reference responses.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+/// A JSON-RPC call to send to the compared nodes. The captured calls can be used as well, their
+/// other fields are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcCall {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A field whose value differs between the expected and the actual response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldDiff {
+    /// Path of the field, e.g. `result.transactions[0].hash`.
+    pub path: String,
+    /// Expected value, `None` if the field is missing from the expected response.
+    pub expected: Option<Value>,
+    /// Actual value, `None` if the field is missing from the actual response.
+    pub actual: Option<Value>,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<Value>| value.as_ref().map_or_else(|| "<missing>".to_string(), Value::to_string);
+        write!(f, "{}: expected {}, got {}", self.path, value(&self.expected), value(&self.actual))
+    }
+}
+
+/// Returns the differences between the `result` or `error` of the two JSON-RPC responses,
+/// ignoring their `id` and `jsonrpc` version.
+pub fn diff_responses(expected: &Value, actual: &Value) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    for field in ["result", "error"] {
+        diff_fields(field.to_string(), expected.get(field), actual.get(field), &mut diffs);
+    }
+    diffs
+}
+
+/// Returns the differences between the two values, field by field.
+pub fn diff(expected: &Value, actual: &Value) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff_fields(String::new(), Some(expected), Some(actual), &mut diffs);
+    diffs
+}
+
+fn diff_fields(path: String, expected: Option<&Value>, actual: Option<&Value>, diffs: &mut Vec<FieldDiff>) {
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            let mut keys = expected.keys().chain(actual.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                diff_fields(path, expected.get(key), actual.get(key), diffs);
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for index in 0..expected.len().max(actual.len()) {
+                diff_fields(format!("{path}[{index}]"), expected.get(index), actual.get(index), diffs);
+            }
+        }
+        (expected, actual) if expected != actual => {
+            diffs.push(FieldDiff { path, expected: expected.cloned(), actual: actual.cloned() });
+        }
+        _ => {}
+    }
+}
+
+/// Splits a field path into its segments: `result.logs[0].data` gives `result`, `logs`, `0`
+/// and `data`.
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['.', '[', ']']).filter(|segment| !segment.is_empty())
+}
+
+/// Returns the path with the array indices replaced by `*`, to group the differences of the
+/// elements of an array.
+pub fn generalize_path(path: &str) -> String {
+    let mut generalized = String::with_capacity(path.len());
+    for segment in segments(path) {
+        if segment.bytes().all(|byte| byte.is_ascii_digit()) {
+            generalized.push_str("[*]");
+        } else {
+            if !generalized.is_empty() {
+                generalized.push('.');
+            }
+            generalized.push_str(segment);
+        }
+    }
+    generalized
+}
+
+/// Returns true if the text matches the glob pattern, where `*` matches any characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            text.strip_prefix(prefix).is_some_and(|text| (0..=text.len()).any(|at| glob_match(rest, &text[at..])))
+        }
+    }
+}
+
+/// A known and accepted difference, as `[<method glob>:]<path>`. The segments of the path are
+/// separated by dots, `*` matches any segment, including array indices, and `**` matches any
+/// number of segments: `eth_getBlockBy*:result.hash`, `**.blockHash`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    /// Glob of the methods the rule applies to, all the methods if `None`.
+    method: Option<String>,
+    path: Vec<String>,
+}
+
+impl IgnoreRule {
+    /// Returns true if the difference of the field at the path is accepted for the method.
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        if self.method.as_ref().is_some_and(|pattern| !glob_match(pattern, method)) {
+            return false;
+        }
+        Self::matches_segments(&self.path, &segments(path).collect::<Vec<_>>())
+    }
+
+    fn matches_segments(pattern: &[String], path: &[&str]) -> bool {
+        match (pattern.split_first(), path.split_first()) {
+            (None, None) => true,
+            (Some((first, rest)), _) if first == "**" => {
+                (0..=path.len()).any(|skipped| Self::matches_segments(rest, &path[skipped..]))
+            }
+            (Some((first, rest)), Some((segment, path))) => {
+                (first == "*" || first == segment) && Self::matches_segments(rest, path)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IgnoreRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let (method, path) = match rule.split_once(':') {
+            Some((method, path)) => (Some(method.trim().to_string()), path),
+            None => (None, rule),
+        };
+        let path = segments(path.trim()).map(ToString::to_string).collect::<Vec<_>>();
+        if path.is_empty() {
+            return Err(format!("invalid ignore rule {rule}, expected [<method>:]<path>"));
+        }
+        Ok(Self { method, path })
+    }
+}
+
+/// The differences between the Kakarot RPC and a reference node running the same Hive chain,
+/// imported with `hive_chain`, which are expected.
+///
+/// The transactions of the chain are relayed to Starknet and packed into different blocks, hence
+/// the fields locating a transaction in its block and the fields derived from the block differ.
+pub const KAKAROT_IGNORE_RULES: &[&str] = &[
+    "**.blockHash",
+    "**.blockNumber",
+    "**.transactionIndex",
+    "**.logIndex",
+    "eth_getTransactionReceipt:result.cumulativeGasUsed",
+    "eth_getTransactionReceipt:result.effectiveGasPrice",
+    "eth_getTransactionByHash:result.gasPrice",
+    "eth_getBlockBy*:result.hash",
+    "eth_getBlockBy*:result.parentHash",
+    "eth_getBlockBy*:result.stateRoot",
+    "eth_getBlockBy*:result.receiptsRoot",
+    "eth_getBlockBy*:result.mixHash",
+    "eth_getBlockBy*:result.nonce",
+    "eth_getBlockBy*:result.miner",
+    "eth_getBlockBy*:result.extraData",
+    "eth_getBlockBy*:result.size",
+    "eth_getBlockBy*:result.difficulty",
+    "eth_getBlockBy*:result.totalDifficulty",
+    "eth_getBlockBy*:result.timestamp",
+    "eth_getBlockBy*:result.gasLimit",
+    "eth_getBlockBy*:result.baseFeePerGas",
+];
+
+/// Filters out the differences which are known and accepted.
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    /// Accepted differences.
+    pub rules: Vec<IgnoreRule>,
+    /// Compare the messages of the errors, which aren't standardized, and not only their code
+    /// and data.
+    pub compare_error_messages: bool,
+    /// Consider a null field the same as a missing field.
+    pub null_as_missing: bool,
+}
+
+impl Normalizer {
+    /// Returns the normalizer accepting the [`KAKAROT_IGNORE_RULES`], the differences of error
+    /// messages and the null fields missing from the other response.
+    pub fn kakarot() -> Self {
+        Self {
+            rules: KAKAROT_IGNORE_RULES.iter().map(|rule| rule.parse().expect("valid ignore rule")).collect(),
+            compare_error_messages: false,
+            null_as_missing: true,
+        }
+    }
+
+    /// Returns true if the difference is accepted for the method.
+    pub fn is_accepted(&self, method: &str, diff: &FieldDiff) -> bool {
+        if !self.compare_error_messages && diff.path == "error.message" {
+            return true;
+        }
+        if self.null_as_missing
+            && matches!((&diff.expected, &diff.actual), (None | Some(Value::Null), None | Some(Value::Null)))
+        {
+            return true;
+        }
+        self.rules.iter().any(|rule| rule.matches(method, &diff.path))
+    }
+
+    /// Returns the differences between the responses of the method which aren't accepted.
+    pub fn diff_responses(&self, method: &str, expected: &Value, actual: &Value) -> Vec<FieldDiff> {
+        diff_responses(expected, actual).into_iter().filter(|diff| !self.is_accepted(method, diff)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_responses() {
+        // Given
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "hash": "0x1", "logs": [{ "index": "0x0" }, { "index": "0x1" }], "to": null }
+        });
+        let actual = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "result": { "hash": "0x1", "logs": [{ "index": "0x2" }], "from": "0xab", "to": null }
+        });
+
+        // When
+        let diffs = diff_responses(&expected, &actual);
+
+        // Then
+        assert_eq!(
+            diffs.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                r#"result.from: expected <missing>, got "0xab""#,
+                r#"result.logs[0].index: expected "0x0", got "0x2""#,
+                r#"result.logs[1]: expected {"index":"0x1"}, got <missing>"#,
+            ]
+        );
+        assert!(diff(&expected["result"], &expected["result"]).is_empty());
+    }
+
+    #[test]
+    fn test_normalizer() {
+        // Given
+        let normalizer = Normalizer::kakarot();
+        let expected = json!({
+            "error": { "code": 3, "message": "execution reverted: nope", "data": "0x08c379a0" }
+        });
+        let actual = json!({
+            "error": { "code": 3, "message": "execution reverted", "data": "0x08c379a1" }
+        });
+        let receipt = |block_hash: &str, status: &str| json!({ "result": { "logs": [{ "blockHash": block_hash, "removed": null }], "status": status } });
+
+        // When
+        let error_diffs = normalizer.diff_responses("eth_call", &expected, &actual);
+        let receipt_diffs =
+            normalizer.diff_responses("eth_getTransactionReceipt", &receipt("0x1", "0x1"), &receipt("0x2", "0x0"));
+
+        // Then
+        assert_eq!(error_diffs.iter().map(|diff| diff.path.as_str()).collect::<Vec<_>>(), vec!["error.data"]);
+        assert_eq!(receipt_diffs.iter().map(|diff| diff.path.as_str()).collect::<Vec<_>>(), vec!["result.status"]);
+        assert_eq!(generalize_path("result.logs[12].topics[0]"), "result.logs[*].topics[*]");
+        assert!("eth_getBlockBy*:result.hash"
+            .parse::<IgnoreRule>()
+            .unwrap()
+            .matches("eth_getBlockByNumber", "result.hash"));
+        assert!(!"eth_getBlockBy*:result.hash".parse::<IgnoreRule>().unwrap().matches("eth_call", "result.hash"));
+        assert!("result.*.to"
+            .parse::<IgnoreRule>()
+            .unwrap()
+            .matches("eth_getBlockByNumber", "result.transactions[3].to"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Graceful shutdown of the RPC: on `SIGINT` or `SIGTERM` the transactions are no longer
+//! accepted, the transactions of the mempool are given some time to be relayed to Starknet and
+//! the background tasks are stopped before the servers.
+
+use std::{io, time::Duration};
+use tokio::sync::watch;
+
+/// Default time given to the transactions of the mempool to be relayed at shutdown.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Triggers the shutdown of the background tasks subscribed to it.
+#[derive(Debug)]
+pub struct ShutdownSignal(watch::Sender<bool>);
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownSignal {
+    /// Returns a signal which isn't triggered yet.
+    pub fn new() -> Self {
+        Self(watch::channel(false).0)
+    }
+
+    /// Returns a [`Shutdown`] notified when the signal is triggered.
+    pub fn subscribe(&self) -> Shutdown {
+        Shutdown(self.0.subscribe())
+    }
+
+    /// Notifies all the subscribed tasks.
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+/// Notified of the shutdown of the process. A [`Shutdown`] whose [`ShutdownSignal`] was dropped
+/// without being triggered is never notified.
+#[derive(Debug, Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    /// Returns a [`Shutdown`] which is never notified, for the tasks running until the end of
+    /// the process.
+    pub fn never() -> Self {
+        ShutdownSignal::new().subscribe()
+    }
+
+    /// Returns true if the shutdown was triggered.
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits until the shutdown is triggered.
+    pub async fn wait(&self) {
+        let mut receiver = self.0.clone();
+        if receiver.wait_for(|triggered| *triggered).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Waits for a `SIGINT` or, on Unix, a `SIGTERM`.
+pub async fn wait_for_signal() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_signal() {
+        // Given
+        let signal = ShutdownSignal::new();
+        let shutdown = signal.subscribe();
+        let never = Shutdown::never();
+
+        // When
+        let waiting = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+        signal.trigger();
+
+        // Then
+        assert!(shutdown.is_triggered());
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        assert!(!never.is_triggered());
+        assert!(tokio::time::timeout(Duration::from_millis(10), never.wait()).await.is_err());
+    }
+}
@@ -161,7 +161,7 @@ impl<P: Provider + Send + Sync + Clone> KakarotEOA<P> {
             self.relayer.address(),
             relayer_balance,
             self.starknet_provider(),
-            Some(Arc::new(self.eth_client.eth_provider().database().clone())),
+            Some(self.eth_client.eth_provider().database().clone()),
         )
         .relay_transaction(&tx_signed)
         .await
@@ -235,7 +235,7 @@ impl<P: Provider + Send + Sync + Clone> KakarotEOA<P> {
             self.relayer.address(),
             relayer_balance,
             self.starknet_provider(),
-            Some(Arc::new(self.eth_client.eth_provider().database().clone())),
+            Some(self.eth_client.eth_provider().database().clone()),
         )
         .relay_transaction(&tx_signed)
         .await
@@ -3,6 +3,7 @@ pub mod genesis;
 use super::mongo::MongoImage;
 use crate::{
     client::EthClient,
+    config::Config,
     constants::KKRT_BLOCK_GAS_LIMIT,
     providers::eth_provider::{
         constant::U64_HEX_STRING_LEN,
@@ -15,7 +16,7 @@ use crate::{
                 receipt::{ExtendedTxReceipt, StoredTransactionReceipt},
                 transaction::{ExtendedTransaction, StoredTransaction},
             },
-            CollectionName,
+            CollectionName, Database,
         },
         provider::EthDataProvider,
     },
@@ -91,6 +92,8 @@ pub struct Katana {
     pub eoa: KakarotEOA<Arc<JsonRpcClient<HttpTransport>>>,
     /// The Ethereum client which contains the mempool and the eth provider
     pub eth_client: EthClient<Arc<JsonRpcClient<HttpTransport>>>,
+    /// The `MongoDB` database used by the eth provider.
+    pub database: Database,
     /// Stored headers to insert into the headers collection.
     pub headers: Vec<StoredHeader>,
     /// Stored transactions to insert into the transactions collection.
@@ -134,6 +137,9 @@ impl<'a> Katana {
         // Set the starknet network in the environment variables.
         std::env::set_var("STARKNET_NETWORK", format!("{}", sequencer.url()));
 
+        // Reload the configuration with the relayer and the network of the sequencer.
+        Config::from_test_env().install_for_tests();
+
         // Initialize a MongoFuzzer instance with the specified random bytes size.
         let mut mongo_fuzzer = MongoFuzzer::new(0).await;
         mongo_fuzzer.headers.push(StoredHeader {
@@ -158,7 +164,7 @@ impl<'a> Katana {
         let eth_client = EthClient::new(
             starknet_provider,
             PoolConfig { gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() },
-            database,
+            Arc::new(database.clone()),
         );
 
         // Create a new Kakarot EOA instance with the private key and EthDataProvider instance.
@@ -169,11 +175,12 @@ impl<'a> Katana {
             sequencer,
             eoa,
             eth_client,
+            database,
             container: Some(mongo_fuzzer.container),
-            transactions: mongo_fuzzer.transactions,
-            receipts: mongo_fuzzer.receipts,
-            logs: mongo_fuzzer.logs,
-            headers: mongo_fuzzer.headers,
+            transactions: mongo_fuzzer.data.transactions,
+            receipts: mongo_fuzzer.data.receipts,
+            logs: mongo_fuzzer.data.logs,
+            headers: mongo_fuzzer.data.headers,
         }
     }
 
@@ -195,6 +202,9 @@ impl<'a> Katana {
         // Set the starknet network in the environment variables.
         std::env::set_var("STARKNET_NETWORK", format!("{}", sequencer.url()));
 
+        // Reload the configuration with the relayer and the network of the sequencer.
+        Config::from_test_env().install_for_tests();
+
         // Initialize a MongoFuzzer instance with the specified random bytes size.
         let mut mongo_fuzzer = MongoFuzzer::new(rnd_bytes_size).await;
 
@@ -209,7 +219,7 @@ impl<'a> Katana {
         let eth_client = EthClient::new(
             starknet_provider,
             PoolConfig { gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() },
-            database,
+            Arc::new(database.clone()),
         );
 
         // Create a new Kakarot EOA instance with the private key and EthDataProvider instance.
@@ -220,11 +230,12 @@ impl<'a> Katana {
             sequencer,
             eoa,
             eth_client,
+            database,
             container: Some(mongo_fuzzer.container),
-            transactions: mongo_fuzzer.transactions,
-            receipts: mongo_fuzzer.receipts,
-            logs: mongo_fuzzer.logs,
-            headers: mongo_fuzzer.headers,
+            transactions: mongo_fuzzer.data.transactions,
+            receipts: mongo_fuzzer.data.receipts,
+            logs: mongo_fuzzer.data.logs,
+            headers: mongo_fuzzer.data.headers,
         }
     }
 
@@ -250,11 +261,7 @@ impl<'a> Katana {
 
     /// Adds mock logs to the database.
     pub async fn add_mock_logs(&self, n_logs: usize) {
-        // Get the Ethereum provider instance.
-        let provider = self.eth_provider();
-
-        // Get the database instance from the provider.
-        let database = provider.database();
+        let database = &self.database;
 
         // Create a mock log object with predefined values.
         let log = Log {
@@ -294,8 +301,7 @@ impl<'a> Katana {
 
     /// Adds transactions to the database along with a corresponding header.
     pub async fn add_transactions_with_header_to_database(&self, txs: Vec<ExtendedTransaction>, header: Header) {
-        let provider = self.eth_provider();
-        let database = provider.database();
+        let database = &self.database;
         let Header { number, .. } = header;
         let block_number = number;
 
@@ -1,7 +1,8 @@
 use crate::providers::eth_provider::{
     database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt, transaction::ExtendedTransaction},
     provider::EthApiResult,
-    BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
+    BlockProvider, ChainProvider, GasProvider, LogProvider, LogsCursor, LogsPage, ReceiptProvider, StateProvider,
+    TransactionProvider,
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{Address, Bytes, B256, U256, U64};
@@ -58,6 +59,8 @@ mock! {
     #[async_trait]
     impl LogProvider for EthereumProviderStruct {
         async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges>;
+
+        async fn get_logs_paged(&self, filter: Filter, cursor: Option<LogsCursor>) -> EthApiResult<LogsPage>;
     }
 
     #[async_trait]
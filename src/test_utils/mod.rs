@@ -7,5 +7,6 @@ pub mod katana;
 pub mod macros;
 pub mod mock_provider;
 pub mod mongo;
+pub mod postgres;
 pub mod rpc;
 pub mod tx_waiter;
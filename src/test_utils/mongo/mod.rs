@@ -1,6 +1,8 @@
 use crate::providers::eth_provider::{
     constant::U64_HEX_STRING_LEN,
     database::{
+        ethereum::{EthereumIndexWriter, EthereumTransactionStore},
+        memory::InMemoryDatabase,
         types::{
             header::StoredHeader,
             log::StoredLog,
@@ -10,7 +12,7 @@ use crate::providers::eth_provider::{
         CollectionName, Database,
     },
 };
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{Bloom, B256, U256};
 use alloy_rpc_types::Transaction;
 use arbitrary::Arbitrary;
 use mongodb::{
@@ -20,7 +22,11 @@ use mongodb::{
 };
 use reth_primitives::TxType;
 use serde::Serialize;
-use std::sync::LazyLock;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::LazyLock,
+};
 use strum::{EnumIter, IntoEnumIterator};
 use testcontainers::{
     core::{IntoContainerPort, WaitFor},
@@ -64,10 +70,10 @@ pub enum CollectionDB {
     Logs,
 }
 
-/// Struct representing a data generator for `MongoDB`.
+/// Struct representing a data generator for the Ethereum storages.
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
-#[derive(Debug)]
-pub struct MongoFuzzer {
+#[derive(Debug, Default)]
+pub struct StoreFuzzer {
     /// Stored headers to insert into the headers collection.
     pub headers: Vec<StoredHeader>,
     /// Stored transactions to insert into the transactions collection.
@@ -76,14 +82,38 @@ pub struct MongoFuzzer {
     pub receipts: Vec<StoredTransactionReceipt>,
     /// Stored logs to insert into the logs collection.
     pub logs: Vec<StoredLog>,
-    /// Connection to the [`MongoDB`] database.
-    mongodb: Database,
     /// Random bytes size.
     rnd_bytes_size: usize,
+}
+
+/// Struct representing a data generator for `MongoDB`.
+#[cfg(any(test, feature = "arbitrary", feature = "testing"))]
+#[derive(Debug)]
+pub struct MongoFuzzer {
+    /// Generated data to insert into the collections.
+    pub data: StoreFuzzer,
+    /// Connection to the [`MongoDB`] database.
+    mongodb: Database,
     /// Container
     pub container: ContainerAsync<MongoImage>,
 }
 
+#[cfg(any(test, feature = "arbitrary", feature = "testing"))]
+impl Deref for MongoFuzzer {
+    type Target = StoreFuzzer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+#[cfg(any(test, feature = "arbitrary", feature = "testing"))]
+impl DerefMut for MongoFuzzer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
+
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 impl MongoFuzzer {
     /// Asynchronously creates a new instance of `MongoFuzzer`.
@@ -107,19 +137,12 @@ impl MongoFuzzer {
             )
             .into();
 
-        Self {
-            headers: vec![],
-            transactions: vec![],
-            receipts: vec![],
-            logs: vec![],
-            mongodb,
-            rnd_bytes_size,
-            container,
-        }
+        Self { data: StoreFuzzer::new(rnd_bytes_size), mongodb, container }
     }
 
     /// Finalizes the data generation and returns the `MongoDB` database.
-    pub async fn finalize(&self) -> Database {
+    pub async fn finalize(&mut self) -> Database {
+        self.accrue_logs_blooms();
         futures::future::join_all(CollectionDB::iter().map(|collection| self.update_collection(collection))).await;
         self.mongodb.clone()
     }
@@ -130,6 +153,141 @@ impl MongoFuzzer {
         self.finalize().await
     }
 
+    /// Updates the collection with the given collection type.
+    async fn update_collection(&self, collection: CollectionDB) {
+        match collection {
+            CollectionDB::Headers => {
+                self.update_documents::<StoredHeader>(
+                    StoredHeader::collection_name(),
+                    &self.headers,
+                    "header",
+                    "number",
+                    "number",
+                )
+                .await;
+            }
+            CollectionDB::Transactions => {
+                self.update_documents::<StoredTransaction>(
+                    StoredTransaction::collection_name(),
+                    &self.transactions,
+                    "tx",
+                    "hash",
+                    "blockNumber",
+                )
+                .await;
+            }
+            CollectionDB::Receipts => {
+                self.update_documents::<StoredTransactionReceipt>(
+                    StoredTransactionReceipt::collection_name(),
+                    &self.receipts,
+                    "receipt",
+                    "transactionHash",
+                    "blockNumber",
+                )
+                .await;
+            }
+            CollectionDB::Logs => {
+                self.update_documents::<StoredLog>(
+                    StoredLog::collection_name(),
+                    &self.logs,
+                    "log",
+                    "transactionHash",
+                    "blockNumber",
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Updates the documents in the collection with the given documents.
+    async fn update_documents<T: Serialize>(
+        &self,
+        collection_name: &str,
+        documents: &[T],
+        doc: &str,
+        value: &str,
+        block_number: &str,
+    ) {
+        let collection = self.mongodb.inner().collection::<Document>(collection_name);
+
+        let key = [doc, value].join(".");
+        let block_key = [doc, block_number].join(".");
+
+        for document in documents {
+            // Serialize the StoredData into BSON
+            let serialized_data = bson::to_document(document).expect("Failed to serialize StoredData");
+
+            // Insert the document in the collection
+            collection
+                .update_one(
+                    doc! {&key: serialized_data.get_document(doc).unwrap().get_str(value).unwrap()},
+                    UpdateModifications::Document(doc! {"$set": serialized_data.clone()}),
+                )
+                .with_options(UpdateOptions::builder().upsert(true).build())
+                .await
+                .expect("Failed to insert documents");
+
+            let number = serialized_data.get_document(doc).unwrap().get_str(block_number).unwrap();
+            let padded_number = format!("0x{:0>width$}", &number[2..], width = U64_HEX_STRING_LEN);
+
+            // Update the document by padding the block number to U64_HEX_STRING_LEN value.
+            collection
+                .update_one(
+                    doc! {&block_key: &number},
+                    UpdateModifications::Document(doc! {"$set": {&block_key: padded_number}}),
+                )
+                .with_options(UpdateOptions::builder().upsert(true).build())
+                .await
+                .expect("Failed to insert documents");
+        }
+    }
+}
+
+#[cfg(any(test, feature = "arbitrary", feature = "testing"))]
+impl StoreFuzzer {
+    /// Creates a new empty generator.
+    pub fn new(rnd_bytes_size: usize) -> Self {
+        Self { rnd_bytes_size, ..Default::default() }
+    }
+
+    /// Mocks an in-memory database with the given number of transactions.
+    pub async fn mock_in_memory_database(&mut self, n_transactions: usize) -> InMemoryDatabase {
+        self.add_random_transactions(n_transactions).expect("Failed to add documents");
+        self.accrue_logs_blooms();
+        let database = InMemoryDatabase::new();
+        self.write_to(&database).await;
+        database
+    }
+
+    /// Writes the headers, transactions and receipts, along with the logs of the receipts, to
+    /// the storage.
+    pub async fn write_to<S>(&self, store: &S)
+    where
+        S: EthereumIndexWriter + EthereumTransactionStore,
+    {
+        for header in &self.headers {
+            store.upsert_header(header.header.clone()).await.expect("Failed to upsert header");
+        }
+        for transaction in &self.transactions {
+            store.upsert_transaction(transaction.tx.clone()).await.expect("Failed to upsert transaction");
+        }
+        for receipt in &self.receipts {
+            store.upsert_receipt(receipt.receipt.clone()).await.expect("Failed to upsert receipt");
+        }
+    }
+
+    /// Sets the logs bloom of each header to the bloom of the logs of its block, keeping
+    /// the headers consistent with the logs collection.
+    fn accrue_logs_blooms(&mut self) {
+        let mut blooms = HashMap::<u64, Bloom>::new();
+        for log in &self.logs {
+            blooms.entry(log.block_number.unwrap_or_default()).or_default().accrue_log(&log.inner);
+        }
+        for header in &mut self.headers {
+            header.header.logs_bloom = blooms.get(&header.number).copied().unwrap_or_default();
+        }
+    }
+
     /// Adds random logs to the collection of logs.
     pub fn add_random_logs(&mut self, n_logs: usize) -> Result<(), Box<dyn std::error::Error>> {
         for _ in 0..n_logs {
@@ -277,95 +435,6 @@ impl MongoFuzzer {
         header.header.number = transaction.block_number.unwrap();
         header
     }
-
-    /// Updates the collection with the given collection type.
-    async fn update_collection(&self, collection: CollectionDB) {
-        match collection {
-            CollectionDB::Headers => {
-                self.update_documents::<StoredHeader>(
-                    StoredHeader::collection_name(),
-                    &self.headers,
-                    "header",
-                    "number",
-                    "number",
-                )
-                .await;
-            }
-            CollectionDB::Transactions => {
-                self.update_documents::<StoredTransaction>(
-                    StoredTransaction::collection_name(),
-                    &self.transactions,
-                    "tx",
-                    "hash",
-                    "blockNumber",
-                )
-                .await;
-            }
-            CollectionDB::Receipts => {
-                self.update_documents::<StoredTransactionReceipt>(
-                    StoredTransactionReceipt::collection_name(),
-                    &self.receipts,
-                    "receipt",
-                    "transactionHash",
-                    "blockNumber",
-                )
-                .await;
-            }
-            CollectionDB::Logs => {
-                self.update_documents::<StoredLog>(
-                    StoredLog::collection_name(),
-                    &self.logs,
-                    "log",
-                    "transactionHash",
-                    "blockNumber",
-                )
-                .await;
-            }
-        }
-    }
-
-    /// Updates the documents in the collection with the given documents.
-    async fn update_documents<T: Serialize>(
-        &self,
-        collection_name: &str,
-        documents: &[T],
-        doc: &str,
-        value: &str,
-        block_number: &str,
-    ) {
-        let collection = self.mongodb.inner().collection::<Document>(collection_name);
-
-        let key = [doc, value].join(".");
-        let block_key = [doc, block_number].join(".");
-
-        for document in documents {
-            // Serialize the StoredData into BSON
-            let serialized_data = bson::to_document(document).expect("Failed to serialize StoredData");
-
-            // Insert the document in the collection
-            collection
-                .update_one(
-                    doc! {&key: serialized_data.get_document(doc).unwrap().get_str(value).unwrap()},
-                    UpdateModifications::Document(doc! {"$set": serialized_data.clone()}),
-                )
-                .with_options(UpdateOptions::builder().upsert(true).build())
-                .await
-                .expect("Failed to insert documents");
-
-            let number = serialized_data.get_document(doc).unwrap().get_str(block_number).unwrap();
-            let padded_number = format!("0x{:0>width$}", &number[2..], width = U64_HEX_STRING_LEN);
-
-            // Update the document by padding the block number to U64_HEX_STRING_LEN value.
-            collection
-                .update_one(
-                    doc! {&block_key: &number},
-                    UpdateModifications::Document(doc! {"$set": {&block_key: padded_number}}),
-                )
-                .with_options(UpdateOptions::builder().upsert(true).build())
-                .await
-                .expect("Failed to insert documents");
-        }
-    }
 }
 
 #[cfg(test)]
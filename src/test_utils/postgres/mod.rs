@@ -0,0 +1,66 @@
+use crate::providers::eth_provider::database::postgres::PostgresDatabase;
+use std::time::Duration;
+use testcontainers::{
+    core::{IntoContainerPort, WaitFor},
+    runners::AsyncRunner,
+    ContainerAsync, Image, ImageExt,
+};
+
+/// The number of attempts to connect to the `PostgreSQL` container while it starts.
+const CONNECTION_ATTEMPTS: usize = 30;
+
+#[derive(Default, Debug)]
+pub struct PostgresImage;
+
+impl Image for PostgresImage {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    fn tag(&self) -> &str {
+        "16-alpine"
+    }
+
+    fn ready_conditions(&self) -> Vec<WaitFor> {
+        vec![WaitFor::Nothing]
+    }
+}
+
+/// A `PostgreSQL` container with the migrated Kakarot schema.
+#[derive(Debug)]
+pub struct PostgresContainer {
+    /// The database of the container.
+    pub database: PostgresDatabase,
+    /// The container, stopped on drop.
+    pub container: ContainerAsync<PostgresImage>,
+}
+
+impl PostgresContainer {
+    /// Starts a `PostgreSQL` container and applies the migrations.
+    pub async fn new() -> Self {
+        let container = PostgresImage
+            .with_env_var("POSTGRES_HOST_AUTH_METHOD", "trust")
+            .start()
+            .await
+            .expect("Failed to start PostgreSQL container");
+        let host_ip = container.get_host().await.expect("Failed to get host IP");
+        let port = container.get_host_port_ipv4(5432.tcp()).await.expect("Failed to get host port");
+        let url = format!("postgres://postgres@{host_ip}:{port}/postgres");
+
+        // The server restarts once initialized, retry until it accepts connections.
+        let mut attempts = 0;
+        let database = loop {
+            match PostgresDatabase::connect(&url).await {
+                Ok(database) => break database,
+                Err(err) if attempts >= CONNECTION_ATTEMPTS => panic!("Failed to connect to PostgreSQL: {err}"),
+                Err(_) => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        };
+        database.migrate().await.expect("Failed to migrate PostgreSQL");
+
+        Self { database, container }
+    }
+}
@@ -1,9 +1,12 @@
 use super::katana::Katana;
-use crate::eth_rpc::{config::RPCConfig, rpc::KakarotRpcModuleBuilder, run_server};
+use crate::{
+    config::config,
+    eth_rpc::{config::RPCConfig, middleware::health::HealthChecker, rpc::KakarotRpcModuleBuilder, run_server},
+};
 use jsonrpsee::server::ServerHandle;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 /// Sets up the environment for Kakarot RPC integration tests by deploying the Kakarot contracts
 /// and starting the Kakarot RPC server.
@@ -55,13 +58,15 @@ use std::net::SocketAddr;
 /// `allow(dead_code)` is used because this function is used in tests,
 /// and each test is compiled separately, so the compiler thinks this function is unused
 pub async fn start_kakarot_rpc_server(katana: &Katana) -> Result<(SocketAddr, ServerHandle), eyre::Report> {
-    let eth_client = katana.eth_client();
+    let eth_client = Arc::new(katana.eth_client());
+    let readiness = Arc::new(HealthChecker::new(eth_client.clone(), None, config().health));
     Ok(run_server(
-        KakarotRpcModuleBuilder::new(eth_client.into()).rpc_module()?,
+        KakarotRpcModuleBuilder::new(eth_client).rpc_module()?,
         #[cfg(feature = "testing")]
         RPCConfig::new_test_config_from_port(rand::random()),
         #[cfg(not(feature = "testing"))]
         RPCConfig::from_port(3030),
+        readiness,
     )
     .await?)
 }
@@ -403,6 +403,7 @@ mod tests {
             "UNINITIALIZED_ACCOUNT_CLASS_HASH",
             "CHECK THE KAKAROT UNINITIALIZED ACCOUNT CLASS HASH FOR THE BLOCK YOU ARE DEBUGGING",
         );
+        crate::config::Config::from_test_env().install_for_tests();
 
         // Given
         let url = Url::parse("https://juno-kakarot-dev.karnot.xyz/").unwrap();
@@ -429,7 +430,8 @@ mod tests {
             ),
         );
 
-        let eth_provider = Arc::new(EthDataProvider::new(db, StarknetProvider::new(Arc::new(starknet_provider))));
+        let eth_provider =
+            Arc::new(EthDataProvider::new(Arc::new(db), StarknetProvider::new(Arc::new(starknet_provider))));
         let tracer = TracerBuilder::new(eth_provider)
             .await
             .unwrap()
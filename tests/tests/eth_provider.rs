@@ -14,19 +14,20 @@ use alloy_sol_types::{sol, SolCall};
 use arbitrary::Arbitrary;
 use kakarot_rpc::{
     client::{KakarotTransactions, TransactionHashProvider},
+    config::Config,
     into_via_try_wrapper,
     models::felt::Felt252Wrapper,
     providers::eth_provider::{
-        constant::{MAX_LOGS, STARKNET_MODULUS},
+        constant::STARKNET_MODULUS,
         database::{
             ethereum::EthereumTransactionStore,
-            filter,
-            filter::EthDatabaseFilterBuilder,
             types::transaction::{EthStarknetHashes, StoredEthStarknetTransactionHash, StoredTransaction},
         },
+        error::{EthApiError, TransactionError},
         provider::EthereumProvider,
         starknet::relayer::Relayer,
-        BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
+        BlockProvider, ChainProvider, GasProvider, LogProvider, LogsCursor, ReceiptProvider, StateProvider,
+        TransactionProvider,
     },
     test_utils::{
         eoa::Eoa,
@@ -121,8 +122,8 @@ async fn test_block_by_number(#[future] katana: Katana, _setup: ()) {
     // When: Retrieving finalized block
     let block = eth_provider.block_by_number(BlockNumberOrTag::Finalized, false).await.unwrap().unwrap();
 
-    // Then: Ensure the retrieved block has the same block number as the most recent transaction
-    assert_eq!(block.header.number, block_number);
+    // Then: Ensure the retrieved block is the genesis block, as Katana doesn't settle on L1
+    assert_eq!(block.header.number, 0);
 
     // When: Retrieving safe block
     let block = eth_provider.block_by_number(BlockNumberOrTag::Safe, false).await.unwrap().unwrap();
@@ -329,14 +330,46 @@ async fn test_get_logs_limit(#[future] katana: Katana, _setup: ()) {
 
     // Set the limit of logs to be retrieved.
     std::env::set_var("MAX_LOGS", "500");
+    let max_logs = Config::from_test_env().install_for_tests().max_logs.unwrap();
 
     // Add mock logs to the Katana instance's database.
     // The number of logs added is MAX_LOGS + 20, ensuring there are more logs than the limit.
-    katana.add_mock_logs(((*MAX_LOGS).unwrap() + 20) as usize).await;
+    katana.add_mock_logs((max_logs + 20) as usize).await;
 
-    // Assert that the number of logs returned by filter_logs is equal to the limit.
+    // Assert that the query fails instead of returning partial results.
     // This ensures that the log retrieval respects the MAX_LOGS constraint.
-    assert_eq!(filter_logs(Filter::default(), provider.clone()).await.len(), (*MAX_LOGS).unwrap() as usize);
+    let err = provider.get_logs(Filter::default()).await.unwrap_err();
+    assert!(err.to_string().starts_with(&format!("query returned more than {max_logs} results")));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_logs_paged(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let provider = katana.eth_provider();
+    let expected_logs = filter_logs(Filter::default(), provider.clone()).await;
+
+    // When
+    let mut logs = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = provider.get_logs_paged(Filter::default(), cursor).await.expect("Failed to get logs page");
+        logs.extend(page.logs);
+        cursor = page.cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    // Then
+    assert_eq!(logs, expected_logs);
+
+    // A cursor past the current block returns an empty last page.
+    let current_block = provider.block_number().await.unwrap().to::<u64>();
+    let page = provider.get_logs_paged(Filter::default(), Some(LogsCursor::new(current_block + 1, 0))).await.unwrap();
+    assert!(page.logs.is_empty());
+    assert!(page.cursor.is_none());
 }
 
 #[rstest]
@@ -535,11 +568,90 @@ async fn test_estimate_gas(#[future] counter: (Katana, KakarotEvmContract), _set
         ..Default::default()
     };
 
+    // When
+    let estimate = eth_provider.estimate_gas(request.clone(), None).await.unwrap();
+
+    // Then
+    assert!(estimate > U256::from(21_000));
+    let request = TransactionRequest { gas: Some(estimate.to()), ..request };
+    eth_provider.call(request, None, None, None).await.expect("Failed to call with the estimated gas");
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_estimate_gas_transfer(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let request = TransactionRequest {
+        from: Some(katana.eoa().evm_address().unwrap()),
+        to: Some(TxKind::Call(Address::random())),
+        value: Some(U256::from(1)),
+        ..Default::default()
+    };
+
     // When
     let estimate = eth_provider.estimate_gas(request, None).await.unwrap();
 
+    // Then: the gas used is the required gas, returned without a binary search
+    assert_eq!(estimate, U256::from(21_000));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_estimate_gas_binary_search(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    // Init code reverting unless `gasleft()` is at least 1_000_000, so that the gas used by the
+    // execution isn't enough for it to succeed:
+    // GAS PUSH3 1_000_000 GT PUSH1 0x0a JUMPI STOP JUMPDEST PUSH1 0 DUP1 REVERT
+    let request = TransactionRequest {
+        from: Some(katana.eoa().evm_address().unwrap()),
+        to: Some(TxKind::Create),
+        input: TransactionInput { input: None, data: Some(bytes!("5a620f424011600a57005b600080fd")) },
+        ..Default::default()
+    };
+
+    // When
+    let estimate = eth_provider.estimate_gas(request.clone(), None).await.unwrap().to::<u64>();
+
     // Then
-    assert!(estimate > U256::from(0));
+    assert!(estimate > 1_000_000);
+    assert!(estimate < 1_100_000);
+    let enough_gas = TransactionRequest { gas: Some(estimate), ..request.clone() };
+    eth_provider.call(enough_gas, None, None, None).await.expect("Failed to call with the estimated gas");
+    let not_enough_gas = TransactionRequest { gas: Some(1_000_000), ..request };
+    assert!(eth_provider.call(not_enough_gas, None, None, None).await.is_err());
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_estimate_gas_underfunded_sender(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let request = TransactionRequest {
+        from: Some(Address::random()),
+        to: Some(TxKind::Call(Address::random())),
+        gas_price: Some(1),
+        ..Default::default()
+    };
+
+    // When
+    let no_allowance = eth_provider.estimate_gas(request.clone(), None).await;
+    let no_allowance_eip1559 = eth_provider
+        .estimate_gas(TransactionRequest { gas_price: None, max_fee_per_gas: Some(1), ..request.clone() }, None)
+        .await;
+    let no_funds = eth_provider.estimate_gas(TransactionRequest { value: Some(U256::from(1)), ..request }, None).await;
+
+    // Then
+    assert!(matches!(no_allowance, Err(EthApiError::Transaction(TransactionError::GasRequiredExceedsAllowance(0)))));
+    assert!(matches!(
+        no_allowance_eip1559,
+        Err(EthApiError::Transaction(TransactionError::GasRequiredExceedsAllowance(0)))
+    ));
+    assert!(matches!(no_funds, Err(EthApiError::Transaction(TransactionError::InsufficientFundsForTransfer))));
 }
 
 #[rstest]
@@ -756,30 +868,25 @@ async fn test_send_raw_transaction(#[future] katana_empty: Katana, _setup: ()) {
         katana.eoa.relayer.address(),
         relayer_balance,
         &(*(*eth_client.starknet_provider())),
-        Some(Arc::new(eth_client.eth_provider().database().clone())),
+        Some(eth_client.eth_provider().database().clone()),
     )
     .relay_transaction(&transaction_signed)
     .await
     .expect("Failed to relay transaction");
 
     // Retrieve the hash mapping from the database (Ethereum -> StarkNet)
-    // 1. Prepare the filter
-    let filter = EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default()
-        .with_tx_hash(&transaction_signed.hash)
-        .build();
-
-    // 2. Retrieve the hash mapping
-    let hash_mapping: Option<StoredEthStarknetTransactionHash> = eth_client
+    // 1. Retrieve the hash mapping
+    let hash_mapping = eth_client
         .eth_provider()
         .database()
-        .get_one(filter, None)
+        .transaction_hashes(&transaction_signed.hash)
         .await
         .expect("Failed to retrieve updated transaction hash mapping");
 
-    // 3. Prepare the transaction hashes
+    // 2. Prepare the transaction hashes
     let transaction_hashes = EthStarknetHashes { eth_hash: transaction_signed.hash, starknet_hash };
 
-    // 4. Assert that the hash mapping was inserted correctly
+    // 3. Assert that the hash mapping was inserted correctly
     assert_eq!(
         hash_mapping,
         Some(StoredEthStarknetTransactionHash::from(transaction_hashes)),
@@ -997,6 +1104,7 @@ async fn test_send_raw_transaction_pre_eip_155(#[future] katana_empty: Katana, _
     let hash = transaction_signed.hash();
     let random_hash = B256::random();
     std::env::set_var("WHITE_LISTED_EIP_155_TRANSACTION_HASHES", format!("{hash}, {random_hash}"));
+    Config::from_test_env().install_for_tests();
 
     let mempool_size = eth_client.mempool().pool_size();
     // Assert that the number of pending and total transactions in the mempool is 0
@@ -1028,7 +1136,7 @@ async fn test_send_raw_transaction_pre_eip_155(#[future] katana_empty: Katana, _
         katana.eoa.relayer.address(),
         relayer_balance,
         &(*(*katana.eth_client.starknet_provider())),
-        Some(Arc::new(katana.eth_client.eth_provider().database().clone())),
+        Some(katana.eth_client.eth_provider().database().clone()),
     )
     .relay_transaction(&transaction_signed)
     .await
@@ -1087,6 +1195,33 @@ async fn test_send_raw_transaction_wrong_signature(#[future] katana: Katana, _se
     assert_eq!(mempool_size_after_send.total, 0);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_send_raw_transaction_shutting_down(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = katana.eth_client();
+    let chain_id = katana.eth_provider().chain_id().await.unwrap_or_default().unwrap_or_default().to();
+    let transaction = Transaction::Eip1559(TxEip1559 {
+        chain_id,
+        gas_limit: 21000,
+        to: TxKind::Call(Address::random()),
+        value: U256::from(1000),
+        max_fee_per_gas: 875_000_000,
+        ..Default::default()
+    });
+    let signature = sign_message(katana.eoa().private_key(), transaction.signature_hash()).unwrap();
+    let transaction_signed = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+    // When
+    eth_client.stop_accepting_transactions();
+    let result = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
+
+    // Then
+    assert!(matches!(result, Err(EthApiError::Transaction(TransactionError::ShuttingDown))));
+    assert_eq!(eth_client.mempool().pool_size().total, 0);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -1401,7 +1536,7 @@ async fn test_transaction_by_hash(#[future] katana_empty: Katana, _setup: ()) {
         katana_empty.eoa.relayer.address(),
         relayer_balance,
         &(*(*katana_empty.eth_client.starknet_provider())),
-        Some(Arc::new(katana_empty.eth_client.eth_provider().database().clone())),
+        Some(katana_empty.eth_client.eth_provider().database().clone()),
     )
     .relay_transaction(&transaction_signed)
     .await
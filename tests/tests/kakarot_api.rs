@@ -3,6 +3,7 @@
 
 use alloy_primitives::B256;
 use kakarot_rpc::{
+    config::Config,
     providers::eth_provider::constant::Constant,
     test_utils::{
         fixtures::{katana, setup},
@@ -38,6 +39,7 @@ async fn test_send_raw_transaction_forwarding(#[future] katana: Katana, _setup:
 
     // Set the MAIN_RPC_URL environment variable
     env::set_var("MAIN_RPC_URL", server.url());
+    Config::from_test_env().install_for_tests();
 
     // Start the Kakarot RPC
     let (address, handle) = start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
@@ -76,10 +78,12 @@ async fn test_kakarot_get_config(#[future] katana: Katana, _setup: ()) {
     std::env::set_var("MAX_LOGS", max_logs.to_string());
     std::env::set_var("MAX_FELTS_IN_CALLDATA", max_felts_in_calldata.to_string());
     std::env::set_var("KAKAROT_ADDRESS", "0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+    Config::from_test_env().install_for_tests();
 
     // Hardcoded expected values
     let expected_constant = Constant {
         max_logs: Some(max_logs),
+        max_logs_block_range: None,
         starknet_network: (starknet_network).to_string(),
         max_felts_in_calldata,
         white_listed_eip_155_transaction_hashes: vec![B256::from_str(white_listed_eip_155_transaction_hashes).unwrap()],
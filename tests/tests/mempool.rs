@@ -1,10 +1,11 @@
 #![allow(clippy::used_underscore_binding)]
 #![cfg(feature = "testing")]
 use alloy_consensus::{TxEip1559, EMPTY_ROOT_HASH};
-use alloy_eips::eip2718::Encodable2718;
+use alloy_eips::{eip2718::Encodable2718, BlockNumberOrTag};
 use alloy_primitives::{Address, TxKind, B64, U256};
 use alloy_rpc_types::Header;
 use kakarot_rpc::{
+    client::{PendingBlockProvider, TransactionCountProvider},
     constants::KKRT_BLOCK_GAS_LIMIT,
     pool::mempool::maintain_transaction_pool,
     providers::eth_provider::{
@@ -14,8 +15,9 @@ use kakarot_rpc::{
             types::header::StoredHeader,
         },
         error::SignatureError,
-        ChainProvider,
+        BlockProvider, ChainProvider,
     },
+    shutdown::Shutdown,
     test_utils::{
         eoa::Eoa,
         fixtures::{katana, katana_empty, setup},
@@ -395,7 +397,7 @@ async fn test_maintain_mempool(#[future] katana: Katana, _setup: ()) {
     let prune_duration = Duration::from_millis(100);
     let eth_client_clone = Arc::clone(&eth_client);
     let maintain_task = tokio::spawn(async move {
-        maintain_transaction_pool(eth_client_clone, prune_duration);
+        maintain_transaction_pool(eth_client_clone, prune_duration, Shutdown::never());
     });
 
     // Initialize the block number based on the current blockchain state from katana.
@@ -414,15 +416,14 @@ async fn test_maintain_mempool(#[future] katana: Katana, _setup: ()) {
         let padded_block_number = format_hex(last_block_number, U64_HEX_STRING_LEN);
 
         // Get the block header collection from the database.
-        let header_collection = eth_client.eth_provider().database().collection::<StoredHeader>();
+        let header_collection = katana.database.collection::<StoredHeader>();
 
         // Build a filter for updating the header based on the new block number.
         let filter = EthDatabaseFilterBuilder::<filter::Header>::default().with_block_number(last_block_number).build();
 
         // Insert a new header for the new block number in the database.
-        eth_client
-            .eth_provider()
-            .database()
+        katana
+            .database
             .update_one(
                 StoredHeader {
                     header: Header {
@@ -486,3 +487,70 @@ async fn test_mempool_config(#[future] katana: Katana, _setup: ()) {
     // Check the gas limit for Kakarot blocks
     assert_eq!(eth_client.mempool().config().gas_limit, KKRT_BLOCK_GAS_LIMIT);
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_block_includes_pool_and_in_flight_transactions(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = katana.eth_client();
+    let transactions = create_sample_transactions(&katana, 2).await.expect("Failed to create sample transactions");
+    let ((transaction1, signed1), (transaction2, signed2)) = (transactions[0].clone(), transactions[1].clone());
+    eth_client.mempool().add_transaction(TransactionOrigin::Local, transaction1).await.unwrap();
+    eth_client.mempool().add_transaction(TransactionOrigin::Local, transaction2).await.unwrap();
+
+    // The first transaction is picked up by a relayer.
+    let in_flight = eth_client.mempool().get(&signed1.hash()).unwrap();
+    eth_client.mempool().remove_transactions(vec![signed1.hash()]);
+    eth_client.in_flight().insert(in_flight);
+
+    // When
+    let block = eth_client.pending_block(false).await.unwrap().unwrap();
+
+    // Then
+    let latest_block_number = eth_client.eth_provider().block_number().await.unwrap().to::<u64>();
+    assert_eq!(block.header.number, latest_block_number + 1);
+    assert!(block.header.hash.is_zero());
+    let hashes = block.transactions.hashes().collect::<Vec<_>>();
+    let position = |hash| hashes.iter().position(|h| *h == hash).expect("missing pending transaction");
+    assert!(position(signed1.hash()) < position(signed2.hash()));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_transaction_count_includes_pool_and_in_flight_transactions(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = katana.eth_client();
+    let address = katana.eoa().evm_address().expect("Failed to get eoa address");
+    let latest_nonce = eth_client.transaction_count(address, Some(BlockNumberOrTag::Latest.into())).await.unwrap();
+    let transactions = create_sample_transactions(&katana, 5).await.expect("Failed to create sample transactions");
+    let ((transaction1, signed1), (transaction2, signed2)) = (transactions[0].clone(), transactions[1].clone());
+    let (gapped_transaction, _) = transactions[4].clone();
+    eth_client.mempool().add_transaction(TransactionOrigin::Local, transaction1).await.unwrap();
+    eth_client.mempool().add_transaction(TransactionOrigin::Local, transaction2).await.unwrap();
+
+    // When
+    let pending_nonce = eth_client.transaction_count(address, Some(BlockNumberOrTag::Pending.into())).await.unwrap();
+
+    // Then
+    assert_eq!(pending_nonce, latest_nonce + U256::from(2));
+
+    // When: both transactions are picked up by relayers
+    for hash in [signed1.hash(), signed2.hash()] {
+        let in_flight = eth_client.mempool().get(&hash).unwrap();
+        eth_client.mempool().remove_transactions(vec![hash]);
+        eth_client.in_flight().insert(in_flight);
+    }
+    let pending_nonce = eth_client.transaction_count(address, Some(BlockNumberOrTag::Pending.into())).await.unwrap();
+
+    // Then
+    assert_eq!(pending_nonce, latest_nonce + U256::from(2));
+
+    // When: a transaction is queued after a nonce gap
+    eth_client.mempool().add_transaction(TransactionOrigin::Local, gapped_transaction).await.unwrap();
+    let pending_nonce = eth_client.transaction_count(address, Some(BlockNumberOrTag::Pending.into())).await.unwrap();
+
+    // Then: the first nonce of the gap is returned
+    assert_eq!(pending_nonce, latest_nonce + U256::from(2));
+}